@@ -0,0 +1,139 @@
+use crate::{
+    analysis::{cfa::SectionAddress, is_valid_jump_table_addr},
+    obj::{ObjInfo, ObjRelocKind, ObjSectionKind, ObjSymbolKind, SymbolIndex},
+};
+
+/// A table of absolute code addresses within a single function, as used by
+/// `bctr`-driven switch statements.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JumpTable {
+    pub address: SectionAddress,
+    pub entry_count: u32,
+    pub function: SymbolIndex,
+}
+
+/// Locates jump tables in data sections by searching for runs of consecutive
+/// absolute relocations that all target the same function.
+pub fn find_jump_tables(obj: &ObjInfo) -> Vec<JumpTable> {
+    let mut jump_tables = Vec::new();
+    for (section_index, section) in obj.sections.iter() {
+        if !is_valid_jump_table_addr(obj, SectionAddress::new(section_index, 0)) {
+            continue;
+        }
+        let mut current: Option<JumpTable> = None;
+        for (address, reloc) in section.relocations.iter() {
+            let function = if reloc.kind == ObjRelocKind::Absolute {
+                function_for_target(obj, reloc.target_symbol, reloc.addend)
+            } else {
+                None
+            };
+            // Continue the current table if this entry directly follows it, targets the same
+            // function, and doesn't start a new symbol
+            if let Some(table) = &mut current {
+                if function == Some(table.function)
+                    && address == table.address.address + table.entry_count * 4
+                    && obj.symbols.at_section_address(section_index, address).next().is_none()
+                {
+                    table.entry_count += 1;
+                    continue;
+                }
+            }
+            if let Some(table) = current.take() {
+                push_jump_table(&mut jump_tables, table);
+            }
+            current = function.map(|function| JumpTable {
+                address: SectionAddress::new(section_index, address),
+                entry_count: 1,
+                function,
+            });
+        }
+        if let Some(table) = current {
+            push_jump_table(&mut jump_tables, table);
+        }
+    }
+    jump_tables
+}
+
+fn push_jump_table(jump_tables: &mut Vec<JumpTable>, table: JumpTable) {
+    // A single entry is indistinguishable from a function pointer
+    if table.entry_count > 1 {
+        log::debug!(
+            "Found jump table @ {:#010X} with entry count {}",
+            table.address,
+            table.entry_count
+        );
+        jump_tables.push(table);
+    }
+}
+
+/// Returns the function containing the relocation target, if any.
+fn function_for_target(
+    obj: &ObjInfo,
+    target_symbol: SymbolIndex,
+    addend: i64,
+) -> Option<SymbolIndex> {
+    let symbol = &obj.symbols[target_symbol];
+    let section_index = symbol.section?;
+    if obj.sections[section_index].kind != ObjSectionKind::Code {
+        return None;
+    }
+    let target = (symbol.address as i64 + addend) as u32;
+    obj.symbols
+        .for_section_range(section_index, ..=target)
+        .filter(|(_, s)| s.kind == ObjSymbolKind::Function && s.size_known)
+        .next_back()
+        .filter(|(_, s)| (target as u64) < s.address + s.size)
+        .map(|(index, _)| index)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::obj::{
+        fixtures::{test_obj, test_reloc, test_section_sized, test_symbol},
+        ObjKind, ObjReloc, ObjSymbol,
+    };
+
+    #[test]
+    fn test_find_jump_tables() {
+        let mut obj = test_obj(
+            ObjKind::Executable,
+            vec![
+                ObjSymbol {
+                    kind: ObjSymbolKind::Function,
+                    ..test_symbol("func", 0, 0x80003100, 0x40)
+                },
+                ObjSymbol {
+                    kind: ObjSymbolKind::Function,
+                    ..test_symbol("other", 0, 0x80003140, 0x10)
+                },
+                ObjSymbol {
+                    kind: ObjSymbolKind::Object,
+                    ..test_symbol("jumptable", 1, 0x80004000, 0x10)
+                },
+            ],
+            vec![
+                test_section_sized(".text", ObjSectionKind::Code, 0x80003100, 0x50),
+                test_section_sized(".rodata", ObjSectionKind::ReadOnlyData, 0x80004000, 0x18),
+            ],
+        );
+        let rodata = &mut obj.sections[1];
+        for (i, addend) in [0x10, 0x18, 0x20, 0x2C].into_iter().enumerate() {
+            rodata
+                .relocations
+                .insert(0x80004000 + i as u32 * 4, ObjReloc {
+                    addend,
+                    ..test_reloc(ObjRelocKind::Absolute, 0)
+                })
+                .unwrap();
+        }
+        // Function pointer to a different function
+        rodata.relocations.insert(0x80004010, test_reloc(ObjRelocKind::Absolute, 1)).unwrap();
+
+        assert_eq!(find_jump_tables(&obj), vec![JumpTable {
+            address: SectionAddress::new(1, 0x80004000),
+            entry_count: 4,
+            function: 0,
+        }]);
+    }
+}
@@ -13,10 +13,13 @@ use crate::{
 
 pub mod cfa;
 pub mod executor;
+pub mod jump_tables;
 pub mod objects;
 pub mod pass;
+pub mod references;
 pub mod signatures;
 pub mod slices;
+pub mod strings;
 pub mod tracker;
 pub mod vm;
 
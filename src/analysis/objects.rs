@@ -1,7 +1,8 @@
 use anyhow::Result;
 
 use crate::{
-    obj::{ObjDataKind, ObjInfo, ObjSectionKind, ObjSymbolKind, SymbolIndex},
+    analysis::{cfa::SectionAddress, slices::check_prologue_sequence},
+    obj::{ObjDataKind, ObjInfo, ObjSectionKind, ObjSymbol, ObjSymbolKind, SymbolIndex},
     util::split::is_linker_generated_label,
 };
 
@@ -63,6 +64,30 @@ pub fn detect_objects(obj: &mut ObjInfo) -> Result<()> {
     Ok(())
 }
 
+/// Upgrades unknown symbols in code sections to functions when they begin with a
+/// typical function prologue (`stwu r1, d(r1)`; `mflr r0`; `stw r0, d(r1)`).
+pub fn detect_functions(obj: &mut ObjInfo) -> Result<()> {
+    let mut replace_symbols = vec![];
+    for (section_index, section) in
+        obj.sections.iter().filter(|(_, s)| s.kind == ObjSectionKind::Code)
+    {
+        for (idx, symbol) in
+            obj.symbols.for_section(section_index).filter(|(_, s)| s.kind == ObjSymbolKind::Unknown)
+        {
+            let addr = SectionAddress::new(section_index, symbol.address as u32);
+            if check_prologue_sequence(section, addr, None)? {
+                log::debug!("Detected function {} @ {:#010X}", symbol.name, addr);
+                replace_symbols
+                    .push((idx, ObjSymbol { kind: ObjSymbolKind::Function, ..symbol.clone() }));
+            }
+        }
+    }
+    for (idx, symbol) in replace_symbols {
+        obj.symbols.replace(idx, symbol)?;
+    }
+    Ok(())
+}
+
 pub fn detect_strings(obj: &mut ObjInfo) -> Result<()> {
     let mut symbols_set = Vec::<(SymbolIndex, ObjDataKind, usize)>::new();
     for (section_index, section) in obj
@@ -161,3 +186,39 @@ pub fn detect_strings(obj: &mut ObjInfo) -> Result<()> {
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::obj::{
+        fixtures::{test_obj, test_section, test_symbol},
+        ObjKind, ObjSection,
+    };
+
+    #[test]
+    fn test_detect_functions() {
+        let mut data = vec![];
+        for ins in [
+            0x9421FFF0u32, // stwu r1, -0x10(r1)
+            0x7C0802A6,    // mflr r0
+            0x90010014,    // stw r0, 0x14(r1)
+            0x4E800020,    // blr
+            0x80003100,    // pointer-like data
+            0x00000000,
+        ] {
+            data.extend_from_slice(&ins.to_be_bytes());
+        }
+        let mut obj = test_obj(
+            ObjKind::Executable,
+            vec![test_symbol("func", 0, 0x80003100, 0x10), test_symbol("table", 0, 0x80003110, 8)],
+            vec![ObjSection {
+                elf_index: 1,
+                ..test_section(".text", ObjSectionKind::Code, 0x80003100, data)
+            }],
+        );
+
+        detect_functions(&mut obj).unwrap();
+        assert_eq!(obj.symbols[0].kind, ObjSymbolKind::Function);
+        assert_eq!(obj.symbols[1].kind, ObjSymbolKind::Unknown);
+    }
+}
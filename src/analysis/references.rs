@@ -0,0 +1,105 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::obj::{ObjInfo, ObjSymbolKind, SectionIndex, SymbolIndex};
+
+/// Symbol references derived from relocations.
+/// An edge A -> B exists if a relocation within symbol A targets symbol B.
+#[derive(Debug, Clone, Default)]
+pub struct ReferenceGraph {
+    pub edges: BTreeMap<SymbolIndex, BTreeSet<SymbolIndex>>,
+}
+
+impl ReferenceGraph {
+    /// Returns the symbols directly referenced by the given symbol.
+    pub fn references(&self, symbol: SymbolIndex) -> impl Iterator<Item = SymbolIndex> + '_ {
+        self.edges.get(&symbol).into_iter().flatten().copied()
+    }
+
+    /// Returns all symbols reachable from the given roots, including the roots themselves.
+    pub fn reachable<I>(&self, roots: I) -> BTreeSet<SymbolIndex>
+    where I: IntoIterator<Item = SymbolIndex> {
+        let mut visited = BTreeSet::new();
+        let mut queue = roots.into_iter().collect::<Vec<_>>();
+        while let Some(symbol) = queue.pop() {
+            if visited.insert(symbol) {
+                queue.extend(self.references(symbol).filter(|s| !visited.contains(s)));
+            }
+        }
+        visited
+    }
+}
+
+pub fn build_reference_graph(obj: &ObjInfo) -> ReferenceGraph {
+    let mut graph = ReferenceGraph::default();
+    for (section_index, section) in obj.sections.iter() {
+        for (address, reloc) in section.relocations.iter() {
+            let Some(source) = symbol_containing(obj, section_index, address) else {
+                continue;
+            };
+            // Resolve section-relative targets to the symbol containing the target address
+            let target_symbol = &obj.symbols[reloc.target_symbol];
+            let target = match (target_symbol.kind, target_symbol.section) {
+                (ObjSymbolKind::Section, Some(target_section)) => symbol_containing(
+                    obj,
+                    target_section,
+                    (target_symbol.address as i64 + reloc.addend) as u32,
+                ),
+                _ => Some(reloc.target_symbol),
+            };
+            if let Some(target) = target {
+                graph.edges.entry(source).or_default().insert(target);
+            }
+        }
+    }
+    graph
+}
+
+/// Finds the sized, non-section symbol containing the given address.
+fn symbol_containing(
+    obj: &ObjInfo,
+    section_index: SectionIndex,
+    address: u32,
+) -> Option<SymbolIndex> {
+    obj.symbols
+        .for_section_range(section_index, ..=address)
+        .filter(|(_, s)| s.kind != ObjSymbolKind::Section && s.size > 0)
+        .next_back()
+        .filter(|(_, s)| (address as u64) < s.address + s.size)
+        .map(|(index, _)| index)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::obj::{
+        fixtures::{test_obj, test_reloc, test_section, test_symbol},
+        ObjKind, ObjRelocKind, ObjSection, ObjSectionKind, ObjSymbol,
+    };
+
+    #[test]
+    fn test_reference_graph() {
+        let mut obj = test_obj(
+            ObjKind::Executable,
+            [("a", 0x80003100), ("b", 0x80003110), ("c", 0x80003120), ("d", 0x80003130)]
+                .map(|(name, address)| ObjSymbol {
+                    kind: ObjSymbolKind::Function,
+                    ..test_symbol(name, 0, address, 0x10)
+                })
+                .to_vec(),
+            vec![ObjSection {
+                elf_index: 1,
+                ..test_section(".text", ObjSectionKind::Code, 0x80003100, vec![0; 0x40])
+            }],
+        );
+        let text = &mut obj.sections[0];
+        text.relocations.insert(0x80003104, test_reloc(ObjRelocKind::PpcRel24, 1)).unwrap();
+        text.relocations.insert(0x80003118, test_reloc(ObjRelocKind::PpcRel24, 2)).unwrap();
+
+        let graph = build_reference_graph(&obj);
+        assert_eq!(graph.references(0).collect::<Vec<_>>(), vec![1]);
+        assert_eq!(graph.references(1).collect::<Vec<_>>(), vec![2]);
+        assert_eq!(graph.references(2).count(), 0);
+        assert_eq!(graph.reachable([0]), BTreeSet::from([0, 1, 2]));
+        assert_eq!(graph.reachable([3]), BTreeSet::from([3]));
+    }
+}
@@ -77,7 +77,7 @@ fn check_sequence(
     Ok(found)
 }
 
-fn check_prologue_sequence(
+pub(crate) fn check_prologue_sequence(
     section: &ObjSection,
     addr: SectionAddress,
     ins: Option<Ins>,
@@ -0,0 +1,48 @@
+use crate::obj::{ObjSection, ObjSectionKind};
+
+/// Scans a section for NUL-terminated runs of printable ASCII, such as the
+/// string pools (`@stringBase0`) emitted by CodeWarrior, returning the address
+/// and contents of each.
+///
+/// CodeWarrior merges strings that are suffixes of others, so a string may be
+/// referenced from within a longer one. Only full NUL-terminated spans are
+/// reported; suffixes are resolved by their references.
+pub fn find_strings(section: &ObjSection) -> Vec<(u64, String)> {
+    if section.kind == ObjSectionKind::Bss {
+        return vec![];
+    }
+    let mut strings = vec![];
+    let mut start = 0;
+    for (offset, &b) in section.data.iter().enumerate() {
+        if b != 0 {
+            continue;
+        }
+        let span = &section.data[start..offset];
+        let printable = span.iter().all(|&b| b.is_ascii_graphic() || b.is_ascii_whitespace());
+        if !span.is_empty() && printable {
+            let string = span.iter().map(|&b| b as char).collect();
+            strings.push((section.address + start as u64, string));
+        }
+        start = offset + 1;
+    }
+    strings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::obj::fixtures::test_section;
+
+    #[test]
+    fn test_find_strings() {
+        // A reference to "world" would point within "hello world",
+        // but only the full span is reported
+        let data = b"hello world\0%d\n\0\0\x01\x02\x03\0abc\0def".to_vec();
+        let section = test_section(".rodata", ObjSectionKind::ReadOnlyData, 0x80004000, data);
+        assert_eq!(find_strings(&section), vec![
+            (0x80004000, "hello world".to_string()),
+            (0x8000400C, "%d\n".to_string()),
+            (0x80004015, "abc".to_string()),
+        ]);
+    }
+}
@@ -782,7 +782,8 @@ impl Tracker {
                 })?;
                 (symbol_idx, 0)
             };
-            let reloc = ObjReloc { kind: reloc_kind, target_symbol, addend, module: None };
+            let reloc =
+                ObjReloc { kind: reloc_kind, target_symbol, addend, module: None, pair: None };
             let section = &mut obj.sections[addr.section];
             if replace {
                 section.relocations.replace(addr.address, reloc);
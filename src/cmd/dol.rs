@@ -21,7 +21,7 @@ use xxhash_rust::xxh3::xxh3_64;
 use crate::{
     analysis::{
         cfa::{AnalyzerState, SectionAddress},
-        objects::{detect_objects, detect_strings},
+        objects::{detect_functions, detect_objects, detect_strings},
         pass::{
             AnalysisPass, FindRelCtorsDtors, FindRelRodataData, FindSaveRestSleds,
             FindTRKInterruptVectorTable,
@@ -50,11 +50,11 @@ use crate::{
             buf_copy_with_hash, buf_writer, check_hash_str, touch, verify_hash, FileIterator,
             FileReadInfo,
         },
-        lcf::{asm_path_for_unit, generate_ldscript, obj_path_for_unit},
+        lcf::{asm_path_for_unit, dir_for_unit, generate_ldscript, obj_path_for_unit},
         map::apply_map_file,
         path::{check_path_buf, native_path},
         rel::{process_rel, process_rel_header, update_rel_section_alignment},
-        rso::{process_rso, DOL_SECTION_ABS, DOL_SECTION_ETI, DOL_SECTION_NAMES},
+        rso::{process_rso_bytes, DOL_SECTION_ABS, DOL_SECTION_ETI, DOL_SECTION_NAMES},
         split::{is_linker_generated_object, split_obj, update_splits},
         IntoCow, ToCow,
     },
@@ -214,6 +214,10 @@ pub struct ProjectConfig {
     pub detect_objects: bool,
     #[serde(default = "bool_true", skip_serializing_if = "is_true")]
     pub detect_strings: bool,
+    /// Creates function symbols from unclaimed prologues in code sections.
+    /// Requires `detect_objects`.
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub detect_function_prologues: bool,
     #[serde(default = "bool_true", skip_serializing_if = "is_true")]
     pub write_asm: bool,
     /// Specifies the start of the common BSS section.
@@ -249,6 +253,7 @@ impl Default for ProjectConfig {
             modules: vec![],
             detect_objects: true,
             detect_strings: true,
+            detect_function_prologues: false,
             write_asm: true,
             common_start: None,
             symbols_known: false,
@@ -391,15 +396,11 @@ pub fn run(args: Args) -> Result<()> {
 }
 
 fn apply_selfile(obj: &mut ObjInfo, buf: &[u8]) -> Result<()> {
-    let rso = process_rso(&mut Cursor::new(buf))?;
+    let rso = process_rso_bytes(buf)?;
     for (_, symbol) in rso.symbols.iter() {
-        let dol_section_index = match symbol.section {
-            Some(section) => section,
-            None => bail!(
-                "Expected section for symbol '{}' @ {:#010X} in selfile",
-                symbol.name,
-                symbol.address
-            ),
+        let Some(dol_section_index) = symbol.section else {
+            // Imports are undefined
+            continue;
         };
         let (section, address, section_kind) = if dol_section_index
             == DOL_SECTION_ABS as SectionIndex
@@ -712,6 +713,7 @@ fn create_relocations(
             } else {
                 Some(rel_reloc.module_id)
             },
+            pair: None,
         };
         let (_, source_section) =
             obj.sections.get_elf_index_mut(rel_reloc.section as SectionIndex).unwrap();
@@ -880,6 +882,11 @@ fn split_write_obj(
     tracker.apply(&mut module.obj, false)?;
 
     if !config.symbols_known && config.detect_objects {
+        if config.detect_function_prologues {
+            debug!("Detecting functions from prologues");
+            detect_functions(&mut module.obj)?;
+        }
+
         debug!("Detecting object boundaries");
         detect_objects(&mut module.obj)?;
     }
@@ -949,9 +956,7 @@ fn split_write_obj(
             code_size: split_obj.code_size(),
             data_size: split_obj.data_size(),
         });
-        if let Some(parent) = out_path.parent() {
-            DirBuilder::new().recursive(true).create(parent)?;
-        }
+        DirBuilder::new().recursive(true).create(obj_dir.join(dir_for_unit(&unit.name)))?;
         write_if_changed(&out_path, &out_obj)?;
     }
 
@@ -1480,7 +1485,10 @@ fn validate(obj: &ObjInfo, elf_file: &Utf8NativePath, state: &AnalyzerState) ->
                         && real_reloc.addend != 0
                         && matches!(
                             real_reloc.kind,
-                            ObjRelocKind::PpcRel14 | ObjRelocKind::PpcRel24
+                            ObjRelocKind::PpcRel14
+                                | ObjRelocKind::PpcRel14BrTaken
+                                | ObjRelocKind::PpcRel14BrnTaken
+                                | ObjRelocKind::PpcRel24
                         )
                     {
                         continue;
@@ -2007,6 +2015,7 @@ fn apply_add_relocations(obj: &mut ObjInfo, relocations: &[AddRelocationConfig])
             target_symbol,
             addend: reloc.addend,
             module: None,
+            pair: None,
         });
     }
     Ok(())
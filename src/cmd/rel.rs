@@ -433,6 +433,7 @@ fn info(args: InfoArgs) -> Result<()> {
             ObjSectionKind::Data => "data",
             ObjSectionKind::ReadOnlyData => "rodata",
             ObjSectionKind::Bss => "bss",
+            ObjSectionKind::Note => "note",
         };
         println!(
             "{: >10} | {: <10} | {: <#10X} | {: <#10X} | {: <10}",
@@ -580,6 +581,7 @@ fn merge(args: MergeArgs) -> Result<()> {
                 target_symbol: symbol_idx,
                 addend,
                 module: None,
+                pair: None,
             })?;
         }
     }
@@ -643,7 +645,9 @@ fn link_relocations(obj: &mut ObjInfo) -> Result<()> {
                     );
                     ins = (ins & !0x3fffffc) | (diff as u32 & 0x3fffffc);
                 }
-                ObjRelocKind::PpcRel14 => {
+                ObjRelocKind::PpcRel14
+                | ObjRelocKind::PpcRel14BrTaken
+                | ObjRelocKind::PpcRel14BrnTaken => {
                     let diff = target_address as i32 - source_address as i32;
                     ensure!(
                         (-0x2000..0x2000).contains(&diff),
@@ -651,9 +655,10 @@ fn link_relocations(obj: &mut ObjInfo) -> Result<()> {
                     );
                     ins = (ins & !0xfffc) | (diff as u32 & 0xfffc);
                 }
-                ObjRelocKind::PpcEmbSda21 => {
+                ObjRelocKind::PpcEmbSda21 | ObjRelocKind::PpcSdaRel16 => {
                     // Unused in RELs
                 }
+                ObjRelocKind::Other(r_type) => bail!("Unsupported relocation type {}", r_type),
             };
             *ins_ref = ins.to_be_bytes();
         }
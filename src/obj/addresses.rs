@@ -1,4 +1,4 @@
-use crate::analysis::cfa::SectionAddress;
+use crate::{analysis::cfa::SectionAddress, obj::SectionIndex};
 
 /// A collection of address ranges.
 /// Slow to insert, but fast to check if an address is contained in any of the ranges.
@@ -44,6 +44,47 @@ impl AddressRanges {
             && address.address >= start.address
             && address.address < *end
     }
+
+    /// Updates the section index of each range, removing ranges for which `f` returns `None`.
+    pub fn remap_sections<F>(&mut self, mut f: F)
+    where F: FnMut(SectionIndex) -> Option<SectionIndex> {
+        self.inner.retain_mut(|(start, _)| match f(start.section) {
+            Some(section) => {
+                start.section = section;
+                true
+            }
+            None => false,
+        });
+        self.inner.sort_by_key(|&(start, _)| start);
+    }
+
+    /// Moves ranges at or after `address` in the given section into the following section,
+    /// dividing any range that spans it. Ranges in later sections are shifted up by one.
+    pub fn split_section(&mut self, section: SectionIndex, address: u32) {
+        let mut split = vec![];
+        for (start, end) in &mut self.inner {
+            if start.section > section {
+                start.section += 1;
+            } else if start.section == section {
+                if start.address >= address {
+                    start.section += 1;
+                } else if *end > address {
+                    split.push((SectionAddress::new(section + 1, address), *end));
+                    *end = address;
+                }
+            }
+        }
+        self.inner.extend(split);
+        self.inner.sort_by_key(|&(start, _)| start);
+    }
+
+    /// Shifts every range by the given delta.
+    pub fn rebase(&mut self, delta: i64) {
+        for (start, end) in &mut self.inner {
+            start.address = (start.address as i64 + delta) as u32;
+            *end = (*end as i64 + delta) as u32;
+        }
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,184 @@
+use anyhow::{bail, ensure, Result};
+
+use crate::obj::{
+    ObjArchitecture, ObjInfo, ObjKind, ObjReloc, ObjSection, ObjSymbol, SectionIndex, SymbolIndex,
+};
+
+/// Incrementally constructs an [`ObjInfo`], validating references between
+/// sections, symbols and relocations as they're added.
+#[derive(Debug, Clone)]
+pub struct ObjInfoBuilder {
+    kind: ObjKind,
+    architecture: ObjArchitecture,
+    name: String,
+    symbols: Vec<ObjSymbol>,
+    sections: Vec<ObjSection>,
+}
+
+impl ObjInfoBuilder {
+    pub fn new(kind: ObjKind, architecture: ObjArchitecture, name: String) -> Self {
+        Self { kind, architecture, name, symbols: vec![], sections: vec![] }
+    }
+
+    pub fn add_section(&mut self, section: ObjSection) -> SectionIndex {
+        let index = self.sections.len() as SectionIndex;
+        self.sections.push(section);
+        index
+    }
+
+    /// Adds a symbol, ensuring that its section (if any) has already been added.
+    pub fn add_symbol(&mut self, symbol: ObjSymbol) -> Result<SymbolIndex> {
+        if let Some(section_index) = symbol.section {
+            ensure!(
+                (section_index as usize) < self.sections.len(),
+                "Symbol {} references invalid section index {} (count {})",
+                symbol.name,
+                section_index,
+                self.sections.len()
+            );
+        }
+        let index = self.symbols.len() as SymbolIndex;
+        self.symbols.push(symbol);
+        Ok(index)
+    }
+
+    /// Adds a relocation to a section, ensuring that the section and target symbol exist.
+    pub fn add_relocation(
+        &mut self,
+        section_index: SectionIndex,
+        address: u32,
+        reloc: ObjReloc,
+    ) -> Result<()> {
+        ensure!(
+            (reloc.target_symbol as usize) < self.symbols.len(),
+            "Relocation @ {:#010X} targets invalid symbol index {} (count {})",
+            address,
+            reloc.target_symbol,
+            self.symbols.len()
+        );
+        let section_count = self.sections.len();
+        let Some(section) = self.sections.get_mut(section_index as usize) else {
+            bail!(
+                "Relocation @ {:#010X} references invalid section index {} (count {})",
+                address,
+                section_index,
+                section_count
+            );
+        };
+        section.relocations.insert(address, reloc)?;
+        Ok(())
+    }
+
+    /// Builds the object, ensuring that sections don't overlap in executables
+    /// and that every relocation lies within its section.
+    pub fn build(self) -> Result<ObjInfo> {
+        if self.kind == ObjKind::Executable {
+            check_section_overlaps(&self.sections)?;
+        }
+        let obj =
+            ObjInfo::new(self.kind, self.architecture, self.name, self.symbols, self.sections);
+        obj.validate_relocations()?;
+        Ok(obj)
+    }
+}
+
+fn check_section_overlaps(sections: &[ObjSection]) -> Result<()> {
+    let mut ranges = sections.iter().filter(|s| s.size > 0).collect::<Vec<_>>();
+    ranges.sort_by_key(|s| s.address);
+    for pair in ranges.windows(2) {
+        let (prev, next) = (pair[0], pair[1]);
+        ensure!(
+            prev.address + prev.size <= next.address,
+            "Section {} {:#010X}-{:#010X} overlaps section {} {:#010X}-{:#010X}",
+            prev.name,
+            prev.address,
+            prev.address + prev.size,
+            next.name,
+            next.address,
+            next.address + next.size
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::obj::{
+        fixtures::{test_reloc, test_section_sized, test_symbol},
+        ObjRelocKind, ObjSectionKind,
+    };
+
+    fn new_builder() -> ObjInfoBuilder {
+        ObjInfoBuilder::new(ObjKind::Executable, ObjArchitecture::PowerPc, "test".to_string())
+    }
+
+    #[test]
+    fn test_build() {
+        let mut builder = new_builder();
+        let data = builder.add_section(test_section_sized(
+            ".data",
+            ObjSectionKind::Data,
+            0x80004000,
+            0x10,
+        ));
+        let value = builder.add_symbol(test_symbol("value", data, 0x80004000, 4)).unwrap();
+        builder.add_symbol(test_symbol("ptr", data, 0x80004004, 4)).unwrap();
+        builder
+            .add_relocation(data, 0x80004004, test_reloc(ObjRelocKind::Absolute, value))
+            .unwrap();
+        let obj = builder.build().unwrap();
+        assert_eq!(obj.symbols.count(), 2);
+        assert_eq!(obj.sections[data].relocations.at(0x80004004).unwrap().target_symbol, value);
+    }
+
+    #[test]
+    fn test_invalid_references() {
+        let mut builder = new_builder();
+        let data = builder.add_section(test_section_sized(
+            ".data",
+            ObjSectionKind::Data,
+            0x80004000,
+            0x10,
+        ));
+        assert!(builder.add_symbol(test_symbol("value", 1, 0x80004000, 4)).is_err());
+        let value = builder.add_symbol(test_symbol("value", data, 0x80004000, 4)).unwrap();
+        assert!(builder
+            .add_relocation(data, 0x80004004, test_reloc(ObjRelocKind::Absolute, 1))
+            .is_err());
+        assert!(builder
+            .add_relocation(1, 0x80004004, test_reloc(ObjRelocKind::Absolute, value))
+            .is_err());
+        builder
+            .add_relocation(data, 0x80004004, test_reloc(ObjRelocKind::Absolute, value))
+            .unwrap();
+        assert!(builder
+            .add_relocation(data, 0x80004004, test_reloc(ObjRelocKind::Absolute, value))
+            .is_err());
+    }
+
+    #[test]
+    fn test_build_validation() {
+        let mut builder = new_builder();
+        builder.add_section(test_section_sized(".data", ObjSectionKind::Data, 0x80004000, 0x10));
+        builder.add_section(test_section_sized(".bss", ObjSectionKind::Data, 0x8000400C, 0x10));
+        let err = builder.build().unwrap_err().to_string();
+        assert_eq!(
+            err,
+            "Section .data 0x80004000-0x80004010 overlaps section .bss 0x8000400C-0x8000401C"
+        );
+
+        let mut builder = new_builder();
+        let data = builder.add_section(test_section_sized(
+            ".data",
+            ObjSectionKind::Data,
+            0x80004000,
+            0x10,
+        ));
+        let value = builder.add_symbol(test_symbol("value", data, 0x80004000, 4)).unwrap();
+        builder
+            .add_relocation(data, 0x80004010, test_reloc(ObjRelocKind::Absolute, value))
+            .unwrap();
+        assert!(builder.build().is_err());
+    }
+}
@@ -0,0 +1,66 @@
+//! Builders for objects used across unit tests.
+
+use crate::obj::{
+    ObjArchitecture, ObjInfo, ObjKind, ObjReloc, ObjRelocKind, ObjSection, ObjSectionKind,
+    ObjSymbol, SectionIndex, SymbolIndex,
+};
+
+/// Builds an object named `test`.
+pub(crate) fn test_obj(
+    kind: ObjKind,
+    symbols: Vec<ObjSymbol>,
+    sections: Vec<ObjSection>,
+) -> ObjInfo {
+    ObjInfo::new(kind, ObjArchitecture::PowerPc, "test".to_string(), symbols, sections)
+}
+
+/// Builds a section sized to `data`.
+pub(crate) fn test_section(
+    name: &str,
+    kind: ObjSectionKind,
+    address: u64,
+    data: Vec<u8>,
+) -> ObjSection {
+    ObjSection {
+        name: name.to_string(),
+        kind,
+        address,
+        size: data.len() as u64,
+        data: data.into(),
+        align: 4,
+        elf_index: 0,
+        relocations: Default::default(),
+        virtual_address: None,
+        file_offset: 0,
+        section_known: true,
+        splits: Default::default(),
+    }
+}
+
+/// Builds a zero-filled section of `size` bytes. BSS sections have no data.
+pub(crate) fn test_section_sized(
+    name: &str,
+    kind: ObjSectionKind,
+    address: u64,
+    size: u64,
+) -> ObjSection {
+    let data = if kind.is_bss() { vec![] } else { vec![0; size as usize] };
+    ObjSection { size, ..test_section(name, kind, address, data) }
+}
+
+/// Builds a symbol of known size within `section`.
+pub(crate) fn test_symbol(name: &str, section: SectionIndex, address: u64, size: u64) -> ObjSymbol {
+    ObjSymbol {
+        name: name.to_string(),
+        address,
+        section: Some(section),
+        size,
+        size_known: true,
+        ..Default::default()
+    }
+}
+
+/// Builds a relocation without an addend.
+pub(crate) fn test_reloc(kind: ObjRelocKind, target_symbol: SymbolIndex) -> ObjReloc {
+    ObjReloc { kind, target_symbol, addend: 0, module: None, pair: None }
+}
@@ -1,21 +1,32 @@
 mod addresses;
+mod builder;
+#[cfg(test)]
+pub(crate) mod fixtures;
 mod relocations;
 mod sections;
 mod splits;
 mod symbols;
 
 use std::{
-    cmp::{max, min},
+    cmp::{max, min, Ordering, Reverse},
     collections::{BTreeMap, BTreeSet},
+    fmt::Write as _,
     hash::Hash,
+    io::Cursor,
+    mem::take,
+    ops::Range,
 };
 
-use anyhow::{anyhow, bail, ensure, Result};
+use anyhow::{anyhow, bail, ensure, Context, Result};
+pub use builder::ObjInfoBuilder;
+use cwdemangle::{demangle, DemangleOptions};
 use objdiff_core::obj::split_meta::SplitMeta;
 pub use relocations::{ObjReloc, ObjRelocKind, ObjRelocations};
 pub use sections::{
-    section_kind_for_section, ObjSection, ObjSectionKind, ObjSections, SectionIndex,
+    section_kind_for_section, ObjSection, ObjSectionKind, ObjSections, SectionData, SectionIndex,
 };
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 pub use splits::{ObjSplit, ObjSplits};
 pub use symbols::{
     best_match_for_reloc, ObjDataKind, ObjSymbol, ObjSymbolFlagSet, ObjSymbolFlags, ObjSymbolKind,
@@ -23,12 +34,24 @@ pub use symbols::{
 };
 
 use crate::{
-    analysis::cfa::SectionAddress,
+    analysis::{cfa::SectionAddress, references::build_reference_graph},
     obj::addresses::AddressRanges,
-    util::{comment::MWComment, rel::RelReloc},
+    util::{
+        comment::MWComment,
+        dol::{DolFile, DolLike},
+        dwarf2::LineTable,
+        reader::{Endian, FromReader},
+        rel::RelReloc,
+    },
 };
 
+/// Linker-generated symbol addressed by r13 in SDA21 relocations.
+pub const SDA_BASE_SYMBOL: &str = "_SDA_BASE_";
+/// Linker-generated symbol addressed by r2 in SDA21 relocations.
+pub const SDA2_BASE_SYMBOL: &str = "_SDA2_BASE_";
+
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum ObjKind {
     /// Fully linked object
     Executable,
@@ -37,12 +60,14 @@ pub enum ObjKind {
 }
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum ObjArchitecture {
     PowerPc,
 }
 
 /// Translation unit information.
 #[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct ObjUnit {
     pub name: String,
     /// Generated, replaceable by user.
@@ -53,7 +78,52 @@ pub struct ObjUnit {
     pub order: Option<i32>,
 }
 
+/// Result of [`ObjInfo::verify_against_dol`].
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct VerifyReport {
+    /// Sections whose relocated data matches the DOL.
+    pub matched_sections: Vec<SectionIndex>,
+    /// The first mismatch of each section that doesn't match.
+    pub mismatches: Vec<VerifyMismatch>,
+}
+
+impl VerifyReport {
+    #[inline]
+    pub fn is_match(&self) -> bool { self.mismatches.is_empty() }
+}
+
+/// First differing byte within a section.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct VerifyMismatch {
+    pub section: SectionIndex,
+    pub address: u32,
+    /// Byte in the DOL.
+    pub expected: u8,
+    /// Byte in the relocated section data.
+    pub found: u8,
+    /// Symbol containing the mismatch, if any.
+    pub symbol: Option<SymbolIndex>,
+}
+
+/// Memory usage overview, as returned by [`ObjInfo::memory_summary`].
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+pub struct MemorySummary {
+    /// Total size of code sections.
+    pub code_size: u64,
+    /// Total size of data sections.
+    pub data_size: u64,
+    /// Total size of read-only data sections.
+    pub rodata_size: u64,
+    /// Total size of BSS sections.
+    pub bss_size: u64,
+    /// End address of the highest loaded section.
+    pub end_address: Option<u64>,
+    /// Size of the arena (`__ArenaHi` - `__ArenaLo`), if both are known.
+    pub arena_size: Option<u32>,
+}
+
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct ObjInfo {
     pub kind: ObjKind,
     pub architecture: ObjArchitecture,
@@ -61,7 +131,11 @@ pub struct ObjInfo {
     pub symbols: ObjSymbols,
     pub sections: ObjSections,
     pub entry: Option<u64>,
+    /// Not serialized: only used to write the `.comment` section of split objects.
+    #[cfg_attr(feature = "serde", serde(skip))]
     pub mw_comment: Option<MWComment>,
+    /// Not serialized: only used to write the `.note.split` section of split objects.
+    #[cfg_attr(feature = "serde", serde(skip))]
     pub split_meta: Option<SplitMeta>,
 
     // Linker generated
@@ -72,19 +146,45 @@ pub struct ObjInfo {
     pub db_stack_addr: Option<u32>,
     pub arena_lo: Option<u32>,
     pub arena_hi: Option<u32>,
+    /// Start of the constructor table (`_ctors`)
+    pub ctors_address: Option<u32>,
+    /// Start of the destructor table (`_dtors`)
+    pub dtors_address: Option<u32>,
+    /// `__init_cpp_exceptions_reference`, the first entry of the constructor table
+    pub init_cpp_exceptions_reference: Option<u32>,
 
     // Extracted
     pub link_order: Vec<ObjUnit>,
+    /// Not serialized: analysis inputs from the project config, not results.
+    #[cfg_attr(feature = "serde", serde(skip))]
     pub blocked_relocation_sources: AddressRanges,
+    #[cfg_attr(feature = "serde", serde(skip))]
     pub blocked_relocation_targets: AddressRanges,
 
     // From .ctors, .dtors and extab
+    /// Not serialized: only guides analysis, and the functions are already in `symbols`.
+    #[cfg_attr(feature = "serde", serde(skip))]
     pub known_functions: BTreeMap<SectionAddress, Option<u32>>,
 
+    // From DWARF 2 .debug_line
+    /// Not serialized: only used to annotate disassembly.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub line_table: Option<LineTable>,
+
+    /// Counts of relocations skipped while loading, by ELF relocation type
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub skipped_relocations: BTreeMap<u32, usize>,
+
     // REL
     /// Module ID (0 for main)
     pub module_id: u32,
+    /// Not serialized: raw REL relocations, only needed to write the module back out.
+    #[cfg_attr(feature = "serde", serde(skip))]
     pub unresolved_relocations: Vec<RelReloc>,
+
+    /// Section data was discarded by [`ObjInfo::strip_section_data`].
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub data_stripped: bool,
 }
 
 impl ObjInfo {
@@ -111,29 +211,64 @@ impl ObjInfo {
             db_stack_addr: None,
             arena_lo: None,
             arena_hi: None,
+            ctors_address: None,
+            dtors_address: None,
+            init_cpp_exceptions_reference: None,
             link_order: vec![],
             blocked_relocation_sources: Default::default(),
             blocked_relocation_targets: Default::default(),
             known_functions: Default::default(),
+            line_table: None,
             module_id: 0,
             unresolved_relocations: vec![],
+            skipped_relocations: Default::default(),
+            data_stripped: false,
         }
     }
 
+    /// Discards the data of every section to save memory, keeping sizes, symbols and
+    /// relocations. The object can no longer be written out afterwards.
+    pub fn strip_section_data(&mut self) {
+        for (_, section) in self.sections.iter_mut() {
+            section.data = Default::default();
+        }
+        self.data_stripped = true;
+    }
+
     pub fn add_symbol(&mut self, in_symbol: ObjSymbol, replace: bool) -> Result<SymbolIndex> {
         match in_symbol.name.as_str() {
-            "_SDA_BASE_" => self.sda_base = Some(in_symbol.address as u32),
-            "_SDA2_BASE_" => self.sda2_base = Some(in_symbol.address as u32),
+            SDA_BASE_SYMBOL => self.sda_base = Some(in_symbol.address as u32),
+            SDA2_BASE_SYMBOL => self.sda2_base = Some(in_symbol.address as u32),
             "_stack_addr" => self.stack_address = Some(in_symbol.address as u32),
             "_stack_end" => self.stack_end = Some(in_symbol.address as u32),
             "_db_stack_addr" => self.db_stack_addr = Some(in_symbol.address as u32),
             "__ArenaLo" => self.arena_lo = Some(in_symbol.address as u32),
             "__ArenaHi" => self.arena_hi = Some(in_symbol.address as u32),
+            "_ctors" => self.ctors_address = Some(in_symbol.address as u32),
+            "_dtors" => self.dtors_address = Some(in_symbol.address as u32),
+            "__init_cpp_exceptions_reference" => {
+                self.init_cpp_exceptions_reference = Some(in_symbol.address as u32)
+            }
             _ => {}
         }
         self.symbols.add(in_symbol, replace)
     }
 
+    /// Renames a symbol, updating its demangled name. Relocations reference symbols by
+    /// index, so they're unaffected. Fails if a different global symbol already has the name.
+    pub fn rename_symbol(&mut self, symbol_index: SymbolIndex, name: String) -> Result<()> {
+        if let Some((_, existing)) = self
+            .symbols
+            .for_name(&name)
+            .find(|&(index, symbol)| index != symbol_index && symbol.flags.is_global())
+        {
+            bail!("Can't rename symbol to {}: conflicts with existing symbol {:?}", name, existing);
+        }
+        let symbol = self.symbols[symbol_index].clone();
+        let demangled_name = demangle(&name, &DemangleOptions::default());
+        self.symbols.replace(symbol_index, ObjSymbol { name, demangled_name, ..symbol })
+    }
+
     pub fn add_split(
         &mut self,
         section_index: SectionIndex,
@@ -303,6 +438,562 @@ impl ObjInfo {
         Ok(())
     }
 
+    /// Removes the section with the given name, along with any symbols within it.
+    /// Section indices of remaining symbols, relocations and splits are updated accordingly.
+    pub fn remove_section(&mut self, name: &str) -> Result<()> {
+        let (removed_index, _) =
+            self.sections.by_name(name)?.ok_or_else(|| anyhow!("Section {} not found", name))?;
+        self.sections.remove(removed_index);
+        let remap_section = |section_index: SectionIndex| match section_index.cmp(&removed_index) {
+            Ordering::Less => Some(section_index),
+            Ordering::Equal => None,
+            Ordering::Greater => Some(section_index - 1),
+        };
+
+        // Drop symbols within the removed section, along with relocations against them
+        let mut symbol_map = vec![None; self.symbols.count() as usize];
+        let mut symbol_count = 0;
+        for (symbol_index, symbol) in self.symbols.iter() {
+            if symbol.section != Some(removed_index) {
+                symbol_map[symbol_index as usize] = Some(symbol_count);
+                symbol_count += 1;
+            }
+        }
+        self.remap_symbols(&symbol_map)?;
+        let symbols = self
+            .symbols
+            .iter()
+            .map(|(_, symbol)| ObjSymbol {
+                section: symbol.section.and_then(remap_section),
+                ..symbol.clone()
+            })
+            .collect();
+        self.symbols = ObjSymbols::new(self.kind, symbols);
+
+        self.known_functions = take(&mut self.known_functions)
+            .into_iter()
+            .filter_map(|(addr, size)| {
+                remap_section(addr.section)
+                    .map(|section| (SectionAddress::new(section, addr.address), size))
+            })
+            .collect();
+        self.blocked_relocation_sources.remap_sections(remap_section);
+        self.blocked_relocation_targets.remap_sections(remap_section);
+        Ok(())
+    }
+
+    /// Reorders symbols according to a map of old to new symbol indices, updating relocation
+    /// targets. Symbols mapped to `None` are dropped, along with any relocations against them.
+    pub(crate) fn remap_symbols(&mut self, mapping: &[Option<SymbolIndex>]) -> Result<()> {
+        ensure!(
+            mapping.len() == self.symbols.count() as usize,
+            "Symbol mapping length {} doesn't match symbol count {}",
+            mapping.len(),
+            self.symbols.count()
+        );
+        let mut symbols = vec![None; mapping.iter().flatten().count()];
+        for ((_, symbol), &new_index) in self.symbols.iter().zip(mapping) {
+            let Some(new_index) = new_index else {
+                continue;
+            };
+            let Some(slot) = symbols.get_mut(new_index as usize) else {
+                bail!("Symbol {} mapped to out of range index {}", symbol.name, new_index);
+            };
+            ensure!(slot.is_none(), "Multiple symbols mapped to index {}", new_index);
+            *slot = Some(symbol.clone());
+        }
+        self.symbols = ObjSymbols::new(self.kind, symbols.into_iter().flatten().collect());
+
+        for (_, section) in self.sections.iter_mut() {
+            section.relocations.retain(|addr, reloc| match mapping[reloc.target_symbol as usize] {
+                Some(target_symbol) => {
+                    reloc.target_symbol = target_symbol;
+                    true
+                }
+                None => {
+                    log::warn!(
+                        "Removing relocation @ {} {:#010X} against removed symbol",
+                        section.name,
+                        addr
+                    );
+                    false
+                }
+            });
+        }
+        Ok(())
+    }
+
+    /// Merges physically adjacent sections of the given kinds into a single section.
+    /// Data, relocations and splits are appended to the lower section, and symbols within
+    /// the merged section are moved into it. Section indices are updated accordingly.
+    /// Only supported for executables.
+    pub fn coalesce_sections(&mut self, kinds: &[ObjSectionKind]) -> Result<()> {
+        ensure!(self.kind == ObjKind::Executable, "Coalescing sections requires an executable");
+        while let Some((target_index, merged_index)) = self.find_adjacent_sections(kinds) {
+            for section_index in [target_index, merged_index] {
+                let section = &self.sections[section_index];
+                let expected = if section.kind.is_bss() { 0 } else { section.size };
+                ensure!(
+                    section.data.len() as u64 == expected,
+                    "Section {} data length {:#X} doesn't match size {:#X}",
+                    section.name,
+                    section.data.len(),
+                    expected
+                );
+            }
+            let merged = self.sections.remove(merged_index);
+            let target_index =
+                if target_index > merged_index { target_index - 1 } else { target_index };
+            let target = self.sections.get_mut(target_index).unwrap();
+            log::debug!(
+                "Coalescing section {} {:#010X}-{:#010X} into {} {:#010X}-{:#010X}",
+                merged.name,
+                merged.address,
+                merged.address + merged.size,
+                target.name,
+                target.address,
+                target.address + target.size
+            );
+            target.size += merged.size;
+            target.data.extend_from_slice(&merged.data);
+            target.align = max(target.align, merged.align);
+            for (addr, reloc) in merged.relocations.iter() {
+                target.relocations.insert(addr, reloc.clone())?;
+            }
+            for (addr, split) in merged.splits.iter() {
+                target.splits.push(addr, split.clone());
+            }
+
+            let remap_section = |section_index: SectionIndex| match section_index.cmp(&merged_index)
+            {
+                Ordering::Less => section_index,
+                Ordering::Equal => target_index,
+                Ordering::Greater => section_index - 1,
+            };
+            let symbols = self
+                .symbols
+                .iter()
+                .map(|(_, symbol)| ObjSymbol {
+                    section: symbol.section.map(remap_section),
+                    ..symbol.clone()
+                })
+                .collect();
+            self.symbols = ObjSymbols::new(self.kind, symbols);
+            self.known_functions = take(&mut self.known_functions)
+                .into_iter()
+                .map(|(addr, size)| {
+                    (SectionAddress::new(remap_section(addr.section), addr.address), size)
+                })
+                .collect();
+            self.blocked_relocation_sources.remap_sections(|index| Some(remap_section(index)));
+            self.blocked_relocation_targets.remap_sections(|index| Some(remap_section(index)));
+        }
+        Ok(())
+    }
+
+    /// Divides a section in two at the given address, the inverse of [`coalesce_sections`].
+    /// Data, relocations, splits and symbols at or after the address are moved into a new
+    /// section inserted directly after the original, named like a duplicate ELF section.
+    /// Section indices are updated accordingly. Only supported for executables.
+    ///
+    /// [`coalesce_sections`]: ObjInfo::coalesce_sections
+    pub fn split_section(&mut self, section_index: SectionIndex, at_address: u32) -> Result<()> {
+        ensure!(self.kind == ObjKind::Executable, "Splitting sections requires an executable");
+        let section = self
+            .sections
+            .get(section_index)
+            .ok_or_else(|| anyhow!("Invalid section index {}", section_index))?;
+        let start = section.address as u32;
+        let end = (section.address + section.size) as u32;
+        ensure!(
+            at_address > start && at_address < end,
+            "Split address {:#010X} outside of section {} {:#010X}-{:#010X}",
+            at_address,
+            section.name,
+            start,
+            end
+        );
+        let align = section.align.max(1) as u32;
+        ensure!(
+            at_address % align == 0,
+            "Split address {:#010X} not aligned to section {} alignment {}",
+            at_address,
+            section.name,
+            align
+        );
+        if let Some((_, symbol)) = self
+            .symbols
+            .for_section_range(section_index, ..at_address)
+            .find(|(_, s)| s.address + s.size > at_address as u64)
+        {
+            bail!("Symbol {} spans split address {:#010X}", symbol.name, at_address);
+        }
+        if let Some((addr, split)) = section.splits.for_address(at_address - 1) {
+            ensure!(
+                split.end <= at_address,
+                "Split {} {:#010X}-{:#010X} spans split address {:#010X}",
+                split.unit,
+                addr,
+                split.end,
+                at_address
+            );
+        }
+        let mut index = 0;
+        let name = loop {
+            index += 1;
+            let name = format!("{}.{}", section.name, index);
+            if self.sections.by_name(&name)?.is_none() {
+                break name;
+            }
+        };
+
+        let section = self.sections.get_mut(section_index).unwrap();
+        let offset = (at_address - start) as usize;
+        let data = if section.data.is_empty() { vec![] } else { section.data.split_off(offset) };
+        let mut relocations = vec![];
+        section.relocations.retain(|addr, reloc| {
+            if addr < at_address {
+                return true;
+            }
+            relocations.push((addr, reloc.clone()));
+            false
+        });
+        let mut splits = ObjSplits::default();
+        let upper_splits = section
+            .splits
+            .for_range(at_address..)
+            .map(|(addr, split)| (addr, split.clone()))
+            .collect::<Vec<_>>();
+        for (addr, split) in upper_splits {
+            section.splits.remove(addr);
+            splits.push(addr, split);
+        }
+        let new_section = ObjSection {
+            name,
+            kind: section.kind,
+            address: at_address as u64,
+            size: end as u64 - at_address as u64,
+            data: data.into(),
+            align: section.align,
+            elf_index: 0,
+            relocations: ObjRelocations::new(relocations)?,
+            virtual_address: section.virtual_address.map(|addr| addr + offset as u64),
+            file_offset: section.file_offset + offset as u64,
+            section_known: section.section_known,
+            splits,
+        };
+        log::debug!(
+            "Splitting section {} at {:#010X} into {}",
+            section.name,
+            at_address,
+            new_section.name
+        );
+        section.size = offset as u64;
+        self.sections.insert(section_index + 1, new_section);
+
+        let remap_section = |index: SectionIndex, address: u64| match index.cmp(&section_index) {
+            Ordering::Less => index,
+            Ordering::Equal if address < at_address as u64 => index,
+            _ => index + 1,
+        };
+        let symbols = self
+            .symbols
+            .iter()
+            .map(|(_, symbol)| ObjSymbol {
+                section: symbol.section.map(|index| remap_section(index, symbol.address)),
+                ..symbol.clone()
+            })
+            .collect();
+        self.symbols = ObjSymbols::new(self.kind, symbols);
+        self.known_functions = take(&mut self.known_functions)
+            .into_iter()
+            .map(|(addr, size)| {
+                let section = remap_section(addr.section, addr.address as u64);
+                (SectionAddress::new(section, addr.address), size)
+            })
+            .collect();
+        self.blocked_relocation_sources.split_section(section_index, at_address);
+        self.blocked_relocation_targets.split_section(section_index, at_address);
+        Ok(())
+    }
+
+    /// Finds a pair of sections of the given kinds where the second immediately follows
+    /// the first in memory.
+    fn find_adjacent_sections(
+        &self,
+        kinds: &[ObjSectionKind],
+    ) -> Option<(SectionIndex, SectionIndex)> {
+        self.sections.iter().filter(|(_, section)| kinds.contains(&section.kind)).find_map(
+            |(index, section)| {
+                self.sections
+                    .iter()
+                    .find(|&(other_index, other)| {
+                        other_index != index
+                            && other.kind == section.kind
+                            && other.address == section.address + section.size
+                    })
+                    .map(|(other_index, _)| (index, other_index))
+            },
+        )
+    }
+
+    /// Shifts the object to a new base address by adding `delta` to every section and
+    /// section-relative symbol address. Relocations, splits and linker-generated addresses
+    /// move along with their sections; absolute symbols are left unchanged.
+    pub fn rebase(&mut self, delta: i64) -> Result<()> {
+        let shift = |address: u32| (address as i64 + delta) as u32;
+        for (_, section) in self.sections.iter_mut() {
+            section.address = (section.address as i64 + delta) as u64;
+            section.relocations = ObjRelocations::new(
+                section
+                    .relocations
+                    .iter()
+                    .map(|(addr, reloc)| (shift(addr), reloc.clone()))
+                    .collect(),
+            )?;
+            let mut splits = ObjSplits::default();
+            for (addr, split) in section.splits.iter() {
+                let end = if split.end == 0 { 0 } else { shift(split.end) };
+                splits.push(shift(addr), ObjSplit { end, ..split.clone() });
+            }
+            section.splits = splits;
+        }
+
+        let symbols = self
+            .symbols
+            .iter()
+            .map(|(_, symbol)| match symbol.section {
+                Some(_) => {
+                    ObjSymbol { address: (symbol.address as i64 + delta) as u64, ..symbol.clone() }
+                }
+                None => symbol.clone(),
+            })
+            .collect();
+        self.symbols = ObjSymbols::new(self.kind, symbols);
+
+        self.entry = self.entry.map(|entry| (entry as i64 + delta) as u64);
+        for base in [
+            &mut self.sda2_base,
+            &mut self.sda_base,
+            &mut self.stack_address,
+            &mut self.stack_end,
+            &mut self.db_stack_addr,
+            &mut self.arena_lo,
+            &mut self.arena_hi,
+            &mut self.ctors_address,
+            &mut self.dtors_address,
+            &mut self.init_cpp_exceptions_reference,
+        ] {
+            *base = base.map(shift);
+        }
+        self.blocked_relocation_sources.rebase(delta);
+        self.blocked_relocation_targets.rebase(delta);
+        self.known_functions = take(&mut self.known_functions)
+            .into_iter()
+            .map(|(addr, size)| (SectionAddress::new(addr.section, shift(addr.address)), size))
+            .collect();
+        if let Some(line_table) = &mut self.line_table {
+            line_table.rows = take(&mut line_table.rows)
+                .into_iter()
+                .map(|(addr, row)| (shift(addr), row))
+                .collect();
+        }
+        Ok(())
+    }
+
+    /// Ensures that every relocation patches data within its section,
+    /// and that every relocation target symbol exists.
+    pub fn validate_relocations(&self) -> Result<()> {
+        for (_, section) in self.sections.iter() {
+            let section_end = section.address + section.size;
+            for (addr, reloc) in section.relocations.iter() {
+                let (r_offset, _) = reloc.to_elf(addr);
+                let width = reloc.kind.width() as u64;
+                ensure!(
+                    r_offset >= section.address && r_offset + width <= section_end,
+                    "Relocation {:?} @ {} {:#010X}-{:#010X} is outside section {:#010X}-{:#010X}",
+                    reloc.kind,
+                    section.name,
+                    r_offset,
+                    r_offset + width,
+                    section.address,
+                    section_end
+                );
+                ensure!(
+                    reloc.target_symbol < self.symbols.count(),
+                    "Relocation {:?} @ {} {:#010X} targets invalid symbol index {} (count {})",
+                    reloc.kind,
+                    section.name,
+                    addr,
+                    reloc.target_symbol,
+                    self.symbols.count()
+                );
+                if let Some(target) = self.rel24_out_of_range(addr, reloc) {
+                    bail!(
+                        "Relocation {:?} @ {} {:#010X} target {} {:#010X} is out of branch range",
+                        reloc.kind,
+                        section.name,
+                        addr,
+                        self.symbols[reloc.target_symbol].name,
+                        target
+                    );
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Locates `PpcRel24` branches whose targets are out of range (±32MB), and would
+    /// require a trampoline. Returns the section index, branch address and target symbol.
+    pub fn trampoline_sites(&self) -> Vec<(SectionIndex, u32, SymbolIndex)> {
+        self.relocations_of_kind(ObjRelocKind::PpcRel24)
+            .filter(|&(_, addr, reloc)| self.rel24_out_of_range(addr, reloc).is_some())
+            .map(|(section_index, addr, reloc)| (section_index, addr, reloc.target_symbol))
+            .collect()
+    }
+
+    /// Returns the target address of a `PpcRel24` relocation if it can't be encoded.
+    /// Only checked for executables, where target addresses are final.
+    fn rel24_out_of_range(&self, addr: u32, reloc: &ObjReloc) -> Option<u32> {
+        if self.kind != ObjKind::Executable
+            || reloc.kind != ObjRelocKind::PpcRel24
+            || reloc.module.is_some()
+        {
+            return None;
+        }
+        if reloc.target_symbol >= self.symbols.count() {
+            return None;
+        }
+        let symbol = &self.symbols[reloc.target_symbol];
+        let target = symbol.address as i64 + reloc.addend;
+        let displacement = target - addr as i64;
+        (!(-0x2000000..0x2000000).contains(&displacement)).then_some(target as u32)
+    }
+
+    /// Looks up the source file and line for the given address using the DWARF 2 line table.
+    pub fn line_for_address(&self, address: u64) -> Option<(&str, u32)> {
+        self.line_table.as_ref()?.lookup(address as u32)
+    }
+
+    /// Returns the unit whose split contains the given symbol. A symbol on a split boundary
+    /// belongs to the unit starting there.
+    pub fn unit_for_symbol(&self, symbol_index: SymbolIndex) -> Option<&str> {
+        if symbol_index >= self.symbols.count() {
+            return None;
+        }
+        let symbol = &self.symbols[symbol_index];
+        let section = self.sections.get(symbol.section?)?;
+        section.splits.for_address(symbol.address as u32).map(|(_, split)| split.unit.as_str())
+    }
+
+    /// Returns the base register implied by an SDA21 or SDAREL16 relocation, based on its
+    /// target section: r13 ([`SDA_BASE_SYMBOL`]) for `.sdata`/`.sbss`, r2
+    /// ([`SDA2_BASE_SYMBOL`]) for `.sdata2`/`.sbss2`, and r0 for absolute targets.
+    pub fn sda_register(&self, reloc: &ObjReloc) -> Result<u8> {
+        ensure!(
+            matches!(reloc.kind, ObjRelocKind::PpcEmbSda21 | ObjRelocKind::PpcSdaRel16),
+            "Expected SDA relocation, got {:?}",
+            reloc.kind
+        );
+        ensure!(
+            reloc.target_symbol < self.symbols.count(),
+            "Invalid relocation target symbol {}",
+            reloc.target_symbol
+        );
+        let symbol = &self.symbols[reloc.target_symbol];
+        let Some(section_index) = symbol.section else {
+            return Ok(0);
+        };
+        let section = &self.sections[section_index];
+        match section.name.as_str() {
+            ".sdata" | ".sbss" => Ok(13),
+            ".sdata2" | ".sbss2" => Ok(2),
+            name => bail!(
+                "SDA relocation against {} in section {} not addressable from {} or {}",
+                symbol.name,
+                name,
+                SDA_BASE_SYMBOL,
+                SDA2_BASE_SYMBOL
+            ),
+        }
+    }
+
+    /// Finds function and object symbols that aren't reachable via relocations from the
+    /// given root symbols. If `implicit_roots` is set, weak and exported symbols are also
+    /// treated as roots.
+    pub fn find_unreachable(
+        &self,
+        roots: &[&str],
+        implicit_roots: bool,
+    ) -> Result<Vec<SymbolIndex>> {
+        let mut root_indexes = Vec::with_capacity(roots.len());
+        for &name in roots {
+            let (index, _) = self
+                .symbols
+                .by_name(name)?
+                .ok_or_else(|| anyhow!("Root symbol {} not found", name))?;
+            root_indexes.push(index);
+        }
+        if implicit_roots {
+            root_indexes.extend(
+                self.symbols
+                    .iter()
+                    .filter(|(_, s)| s.flags.is_weak() || s.flags.is_exported())
+                    .map(|(index, _)| index),
+            );
+        }
+        let reachable = build_reference_graph(self).reachable(root_indexes);
+        Ok(self
+            .symbols
+            .iter()
+            .filter(|(_, s)| {
+                s.section.is_some()
+                    && matches!(s.kind, ObjSymbolKind::Function | ObjSymbolKind::Object)
+            })
+            .map(|(index, _)| index)
+            .filter(|index| !reachable.contains(index))
+            .collect())
+    }
+
+    /// Counts relocations by kind across all sections.
+    pub fn relocation_stats(&self) -> BTreeMap<ObjRelocKind, usize> {
+        let mut stats = BTreeMap::new();
+        for (_, section) in self.sections.iter() {
+            for (_, reloc) in section.relocations.iter() {
+                *stats.entry(reloc.kind).or_default() += 1;
+            }
+        }
+        stats
+    }
+
+    /// Counts relocations by kind for each section containing relocations.
+    pub fn section_relocation_stats(
+        &self,
+    ) -> BTreeMap<SectionIndex, BTreeMap<ObjRelocKind, usize>> {
+        let mut stats = BTreeMap::<SectionIndex, BTreeMap<ObjRelocKind, usize>>::new();
+        for (section_index, section) in self.sections.iter() {
+            for (_, reloc) in section.relocations.iter() {
+                *stats.entry(section_index).or_default().entry(reloc.kind).or_default() += 1;
+            }
+        }
+        stats
+    }
+
+    /// Iterates over relocations of the given kind across all sections, in section and
+    /// address order. Yields the section index, address and relocation.
+    pub fn relocations_of_kind(
+        &self,
+        kind: ObjRelocKind,
+    ) -> impl Iterator<Item = (SectionIndex, u32, &ObjReloc)> {
+        self.sections.iter().flat_map(move |(section_index, section)| {
+            section
+                .relocations
+                .iter()
+                .filter(move |(_, reloc)| reloc.kind == kind)
+                .map(move |(addr, reloc)| (section_index, addr, reloc))
+        })
+    }
+
     pub fn is_unit_autogenerated(&self, unit: &str) -> bool {
         self.sections
             .all_splits()
@@ -323,7 +1014,7 @@ impl ObjInfo {
     pub fn data_size(&self) -> u32 {
         self.sections
             .iter()
-            .filter(|(_, section)| section.kind != ObjSectionKind::Code)
+            .filter(|(_, section)| section.kind.loadable() && section.kind != ObjSectionKind::Code)
             .map(|(_, section)| section.size as u32)
             .chain(
                 // Include common symbols
@@ -334,4 +1025,1251 @@ impl ObjInfo {
             )
             .sum()
     }
+
+    /// Summarizes section sizes by kind, the highest address used and the arena size.
+    /// Note sections aren't loaded, so they're excluded.
+    pub fn memory_summary(&self) -> MemorySummary {
+        let mut summary = MemorySummary {
+            arena_size: self.arena_lo.zip(self.arena_hi).map(|(lo, hi)| hi.saturating_sub(lo)),
+            ..Default::default()
+        };
+        for (_, section) in self.sections.iter() {
+            let size = match section.kind {
+                ObjSectionKind::Code => &mut summary.code_size,
+                ObjSectionKind::Data => &mut summary.data_size,
+                ObjSectionKind::ReadOnlyData => &mut summary.rodata_size,
+                ObjSectionKind::Bss => &mut summary.bss_size,
+                ObjSectionKind::Note => continue,
+            };
+            *size += section.size;
+            let end = section.address + section.size;
+            summary.end_address = Some(summary.end_address.map_or(end, |e| max(e, end)));
+        }
+        summary
+    }
+
+    /// Returns the compiler that produced the object: the first string in an opaque
+    /// `.comment` section, or the version from a Metrowerks `.comment` header.
+    pub fn producer(&self) -> Option<String> {
+        if let Some(comment) = &self.mw_comment {
+            let [major, minor, patch, build] = comment.compiler_version;
+            return Some(format!("Metrowerks CodeWarrior {major}.{minor}.{patch}.{build}"));
+        }
+        let (_, section) = self.sections.by_name(".comment").ok()??;
+        // GNU as emits a leading NUL, so skip empty strings
+        let producer = section.data.split(|&b| b == 0).find(|s| !s.is_empty())?;
+        Some(String::from_utf8_lossy(producer).into_owned())
+    }
+
+    /// Returns the first section with the given name.
+    pub fn section_by_name(&self, name: &str) -> Option<&ObjSection> {
+        self.sections.iter().map(|(_, s)| s).find(|s| s.name == name)
+    }
+
+    /// Returns the first section with the given name.
+    pub fn section_by_name_mut(&mut self, name: &str) -> Option<&mut ObjSection> {
+        self.sections.iter_mut().map(|(_, s)| s).find(|s| s.name == name)
+    }
+
+    /// Returns the section loaded from the given ELF (or RSO/REL) section index.
+    pub fn section_by_elf_index(&self, elf_index: SectionIndex) -> Option<&ObjSection> {
+        self.sections.get_elf_index(elf_index).map(|(_, s)| s)
+    }
+
+    /// Locates the section containing the given address. In relocatable objects, where
+    /// section addresses typically overlap at zero, only an unambiguous match is returned.
+    pub fn address_to_section(&self, address: u64) -> Option<SectionIndex> {
+        let mut matches = self
+            .sections
+            .iter()
+            .filter(|(_, s)| (s.address..s.address + s.size).contains(&address))
+            .map(|(index, _)| index);
+        let first = matches.next()?;
+        match self.kind {
+            ObjKind::Executable => Some(first),
+            ObjKind::Relocatable => matches.next().is_none().then_some(first),
+        }
+    }
+
+    /// Returns the symbol at the entry point (usually `__start`), preferring functions.
+    /// Returns `None` if no symbol starts at the entry address.
+    pub fn entry_symbol(&self) -> Option<&ObjSymbol> {
+        let entry = self.entry?;
+        let section_index = self.address_to_section(entry)?;
+        self.symbols
+            .at_section_address(section_index, entry as u32)
+            .map(|(_, symbol)| symbol)
+            .filter(|symbol| symbol.kind != ObjSymbolKind::Section)
+            .max_by_key(|symbol| symbol.kind == ObjSymbolKind::Function)
+    }
+
+    /// Returns the symbols of each section (indexed by section index), sorted by address.
+    /// Symbols at the same address are sorted by size descending, so that containing
+    /// symbols come before the symbols nested within them.
+    pub fn sorted_symbols(&self) -> Vec<Vec<SymbolIndex>> {
+        self.sections
+            .iter()
+            .map(|(section_index, _)| {
+                let mut symbols = self.symbols.for_section(section_index).collect::<Vec<_>>();
+                symbols.sort_by_key(|&(_, symbol)| (symbol.address, Reverse(symbol.size)));
+                symbols.into_iter().map(|(symbol_index, _)| symbol_index).collect()
+            })
+            .collect()
+    }
+
+    /// Retargets `Absolute` and `ADDR16_*` relocations against defined symbols to the
+    /// containing section's symbol, folding the symbol's offset into the addend. Section
+    /// symbols are created where missing. This is needed before writing a split unit as a
+    /// relocatable object.
+    pub fn absolutize_to_relative(&mut self) -> Result<()> {
+        let mut section_symbols = vec![None; self.sections.len() as usize];
+        for (symbol_index, symbol) in self.symbols.iter() {
+            if let (ObjSymbolKind::Section, Some(section_index)) = (symbol.kind, symbol.section) {
+                section_symbols[section_index as usize].get_or_insert(symbol_index);
+            }
+        }
+
+        let mut updates = vec![];
+        for (section_index, section) in self.sections.iter() {
+            for (addr, reloc) in section.relocations.iter() {
+                if reloc.module.is_some()
+                    || !matches!(
+                        reloc.kind,
+                        ObjRelocKind::Absolute
+                            | ObjRelocKind::PpcAddr16Hi
+                            | ObjRelocKind::PpcAddr16Ha
+                            | ObjRelocKind::PpcAddr16Lo
+                    )
+                {
+                    continue;
+                }
+                let target = &self.symbols[reloc.target_symbol];
+                let Some(target_section) = target.section else {
+                    continue;
+                };
+                if target.kind == ObjSymbolKind::Section {
+                    continue;
+                }
+                let offset = target.address as i64 - self.sections[target_section].address as i64;
+                updates.push((section_index, addr, target_section, offset + reloc.addend));
+            }
+        }
+
+        for (section_index, addr, target_section, addend) in updates {
+            let target_symbol = match section_symbols[target_section as usize] {
+                Some(symbol_index) => symbol_index,
+                None => {
+                    let section = &self.sections[target_section];
+                    let symbol_index = self.symbols.add_direct(ObjSymbol {
+                        name: section.name.clone(),
+                        address: section.address,
+                        section: Some(target_section),
+                        flags: ObjSymbolFlagSet(ObjSymbolFlags::Local.into()),
+                        kind: ObjSymbolKind::Section,
+                        ..Default::default()
+                    })?;
+                    section_symbols[target_section as usize] = Some(symbol_index);
+                    symbol_index
+                }
+            };
+            let reloc = self.sections[section_index].relocations.at_mut(addr).unwrap();
+            reloc.target_symbol = target_symbol;
+            reloc.addend = addend;
+        }
+        Ok(())
+    }
+
+    /// Returns a copy of the section's data with relocations applied, as if each section
+    /// were loaded at the corresponding address in `base_addresses` (indexed by section
+    /// index). Relocations against other modules are left untouched, and relocations of
+    /// [`ObjRelocKind::Other`] types are skipped with a warning.
+    pub fn apply_relocations(
+        &self,
+        section_index: SectionIndex,
+        base_addresses: &[u64],
+    ) -> Result<Vec<u8>> {
+        ensure!(
+            base_addresses.len() == self.sections.len() as usize,
+            "Expected {} base addresses, got {}",
+            self.sections.len(),
+            base_addresses.len()
+        );
+        let section = &self.sections[section_index];
+        ensure!(section.kind != ObjSectionKind::Bss, "Can't apply relocations to BSS section");
+        let load_address = |section_index: SectionIndex, address: u64| {
+            let section = &self.sections[section_index];
+            base_addresses[section_index as usize] + (address - section.address)
+        };
+        let mut data = section.data.to_vec();
+        for (addr, reloc) in section.relocations.iter() {
+            if reloc.module.is_some() {
+                continue;
+            }
+            let symbol = &self.symbols[reloc.target_symbol];
+            let symbol_address = match symbol.section {
+                Some(target_section) => load_address(target_section, symbol.address),
+                None => symbol.address,
+            };
+            let target = (symbol_address as i64 + reloc.addend) as u32;
+            // Relocations loaded from ELF may point at the relocated halfword
+            let ins_addr = match reloc.kind {
+                ObjRelocKind::Absolute => addr,
+                _ => addr & !3,
+            };
+            let source = load_address(section_index, ins_addr as u64) as u32;
+            let offset = (ins_addr as u64 - section.address) as usize;
+            let ins_ref = data.get_mut(offset..offset + 4).ok_or_else(|| {
+                anyhow!("Relocation @ {} {:#010X} is outside of section data", section.name, addr)
+            })?;
+            let mut ins = u32::from_be_bytes(<[u8; 4]>::try_from(&*ins_ref).unwrap());
+            match reloc.kind {
+                ObjRelocKind::Absolute => ins = target,
+                ObjRelocKind::PpcAddr16Hi => ins = (ins & !0xFFFF) | (target >> 16),
+                ObjRelocKind::PpcAddr16Ha => {
+                    ins = (ins & !0xFFFF) | (target.wrapping_add(0x8000) >> 16)
+                }
+                ObjRelocKind::PpcAddr16Lo => ins = (ins & !0xFFFF) | (target & 0xFFFF),
+                ObjRelocKind::PpcRel24 => {
+                    let diff = target as i64 - source as i64;
+                    ensure!(
+                        (-0x2000000..0x2000000).contains(&diff),
+                        "R_PPC_REL24 relocation @ {} {:#010X} out of range",
+                        section.name,
+                        addr
+                    );
+                    ins = (ins & !0x3FFFFFC) | (diff as u32 & 0x3FFFFFC);
+                }
+                ObjRelocKind::PpcRel14
+                | ObjRelocKind::PpcRel14BrTaken
+                | ObjRelocKind::PpcRel14BrnTaken => {
+                    let diff = target as i64 - source as i64;
+                    ensure!(
+                        (-0x8000..0x8000).contains(&diff),
+                        "R_PPC_REL14 relocation @ {} {:#010X} out of range",
+                        section.name,
+                        addr
+                    );
+                    ins = (ins & !0xFFFC) | (diff as u32 & 0xFFFC);
+                }
+                ObjRelocKind::PpcEmbSda21 | ObjRelocKind::PpcSdaRel16 => {
+                    let name = match reloc.kind {
+                        ObjRelocKind::PpcEmbSda21 => "R_PPC_EMB_SDA21",
+                        _ => "R_PPC_SDAREL16",
+                    };
+                    let register = self.sda_register(reloc)?;
+                    let base = match register {
+                        13 => self.sda_base,
+                        2 => self.sda2_base,
+                        _ => Some(0),
+                    }
+                    .ok_or_else(|| {
+                        anyhow!(
+                            "{} relocation @ {} {:#010X} requires unknown SDA base for r{}",
+                            name,
+                            section.name,
+                            addr,
+                            register
+                        )
+                    })?;
+                    let diff = target as i64 - base as i64;
+                    ensure!(
+                        (-0x8000..0x8000).contains(&diff),
+                        "{} relocation @ {} {:#010X} out of range",
+                        name,
+                        section.name,
+                        addr
+                    );
+                    ins = if reloc.kind == ObjRelocKind::PpcEmbSda21 {
+                        (ins & !0x1FFFFF) | ((register as u32) << 16) | (diff as u32 & 0xFFFF)
+                    } else {
+                        (ins & !0xFFFF) | (diff as u32 & 0xFFFF)
+                    };
+                }
+                ObjRelocKind::Other(r_type) => {
+                    log::warn!(
+                        "Skipping unsupported relocation type {} @ {} {:#010X}",
+                        r_type,
+                        section.name,
+                        addr
+                    );
+                    continue;
+                }
+            }
+            ins_ref.copy_from_slice(&ins.to_be_bytes());
+        }
+        Ok(data)
+    }
+
+    /// Returns the address ranges of a section not covered by any sized symbol, in ascending
+    /// order. Coverage only depends on symbol sizes, so BSS sections are handled the same way.
+    pub fn section_coverage(&self, section_index: SectionIndex) -> Vec<Range<u64>> {
+        let section = &self.sections[section_index];
+        let end = section.address + section.size;
+        let mut gaps = vec![];
+        let mut address = section.address;
+        for (_, symbol) in self.symbols.for_section(section_index) {
+            if symbol.kind == ObjSymbolKind::Section || symbol.size == 0 {
+                continue;
+            }
+            if symbol.address > address {
+                gaps.push(address..min(symbol.address, end));
+            }
+            address = max(address, symbol.address + symbol.size);
+            if address >= end {
+                break;
+            }
+        }
+        if address < end {
+            gaps.push(address..end);
+        }
+        gaps
+    }
+
+    /// Compares each loaded section, with relocations applied at its current address,
+    /// against the image loaded from the given DOL.
+    pub fn verify_against_dol(&self, dol_bytes: &[u8]) -> Result<VerifyReport> {
+        ensure!(self.kind == ObjKind::Executable, "Can only verify executables against a DOL");
+        let dol = DolFile::from_reader(&mut Cursor::new(dol_bytes), Endian::Big)?;
+        let base_addresses = self.sections.iter().map(|(_, s)| s.address).collect::<Vec<_>>();
+        let mut report = VerifyReport::default();
+        for (section_index, section) in self.sections.iter() {
+            if matches!(section.kind, ObjSectionKind::Bss | ObjSectionKind::Note) {
+                continue;
+            }
+            let data = self.apply_relocations(section_index, &base_addresses)?;
+            let expected = dol
+                .virtual_data_at(dol_bytes, section.address as u32, section.size as u32)
+                .with_context(|| format!("Section {} not found in DOL", section.name))?;
+            let Some(offset) = data.iter().zip(expected).position(|(a, b)| a != b) else {
+                report.matched_sections.push(section_index);
+                continue;
+            };
+            let address = (section.address + offset as u64) as u32;
+            let symbol = self
+                .symbols
+                .for_section_range(section_index, ..=address)
+                .filter(|(_, s)| s.kind != ObjSymbolKind::Section && s.size > 0)
+                .next_back()
+                .filter(|(_, s)| (address as u64) < s.address + s.size)
+                .map(|(index, _)| index);
+            report.mismatches.push(VerifyMismatch {
+                section: section_index,
+                address,
+                expected: expected[offset],
+                found: data[offset],
+                symbol,
+            });
+        }
+        Ok(report)
+    }
+
+    /// Lists every relocation as `section:offset kind -> symbol+addend`, one per line,
+    /// ordered by section and offset. Target names are demangled where possible.
+    pub fn relocation_report(&self) -> String {
+        let mut out = String::new();
+        for (_, section) in self.sections.iter() {
+            for (addr, reloc) in section.relocations.iter() {
+                let symbol = &self.symbols[reloc.target_symbol];
+                write!(
+                    out,
+                    "{}:{:#010X} {:?} -> {}",
+                    section.name,
+                    addr as u64 - section.address,
+                    reloc.kind,
+                    symbol.display_name()
+                )
+                .unwrap();
+                match reloc.addend.cmp(&0) {
+                    Ordering::Greater => write!(out, "+{:#X}", reloc.addend).unwrap(),
+                    Ordering::Less => write!(out, "-{:#X}", -reloc.addend).unwrap(),
+                    Ordering::Equal => {}
+                }
+                if let Some(module) = reloc.module {
+                    write!(out, " (module {})", module).unwrap();
+                }
+                out.push('\n');
+            }
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::obj::fixtures::{test_obj, test_reloc, test_section_sized, test_symbol};
+
+    #[test]
+    fn test_remove_section() {
+        let mut obj = test_obj(
+            ObjKind::Executable,
+            vec![
+                test_symbol("func", 0, 0x80000000, 4),
+                test_symbol("data", 1, 0x80001000, 4),
+                test_symbol("bss", 2, 0x80002000, 4),
+            ],
+            vec![
+                test_section_sized(".text", ObjSectionKind::Code, 0x80000000, 0x10),
+                test_section_sized(".data", ObjSectionKind::Data, 0x80001000, 0x10),
+                test_section_sized(".bss", ObjSectionKind::Bss, 0x80002000, 0x10),
+            ],
+        );
+        let reloc = test_reloc(ObjRelocKind::Absolute, 2);
+        obj.sections[0].relocations.insert(0x80000000, reloc.clone()).unwrap();
+        obj.sections[0]
+            .relocations
+            .insert(0x80000004, ObjReloc { target_symbol: 1, ..reloc })
+            .unwrap();
+
+        obj.remove_section(".data").unwrap();
+        assert_eq!(obj.sections.len(), 2);
+        assert_eq!(obj.sections[1].name, ".bss");
+        assert_eq!(obj.symbols.count(), 2);
+        let (bss_index, bss) = obj.symbols.by_name("bss").unwrap().unwrap();
+        assert_eq!(bss.section, Some(1));
+        assert!(obj.symbols.by_name("data").unwrap().is_none());
+
+        let text = &obj.sections[0];
+        assert_eq!(text.relocations.len(), 1);
+        assert_eq!(text.relocations.at(0x80000000).unwrap().target_symbol, bss_index);
+        assert!(obj.remove_section(".data").is_err());
+    }
+
+    #[test]
+    fn test_validate_relocations() {
+        let mut obj =
+            test_obj(ObjKind::Executable, vec![test_symbol("func", 0, 0x80000000, 4)], vec![
+                test_section_sized(".text", ObjSectionKind::Code, 0x80000000, 0x10),
+            ]);
+        let reloc = test_reloc(ObjRelocKind::PpcAddr16Lo, 0);
+        obj.sections[0].relocations.insert(0x8000000C, reloc.clone()).unwrap();
+        obj.validate_relocations().unwrap();
+
+        // Out of bounds
+        obj.sections[0].relocations.insert(0x80000010, reloc.clone()).unwrap();
+        assert!(obj.validate_relocations().is_err());
+        obj.sections[0].relocations.retain(|addr, _| addr != 0x80000010);
+
+        // Invalid target symbol
+        obj.sections[0].relocations.replace(0x8000000C, ObjReloc { target_symbol: 1, ..reloc });
+        assert!(obj.validate_relocations().is_err());
+    }
+
+    #[test]
+    fn test_rel24_out_of_range() {
+        let mut obj = test_obj(
+            ObjKind::Executable,
+            vec![test_symbol("near", 1, 0x81FFFFFC, 4), test_symbol("far", 1, 0x82000000, 4)],
+            vec![
+                test_section_sized(".text", ObjSectionKind::Code, 0x80000000, 0x10),
+                test_section_sized(".text1", ObjSectionKind::Code, 0x81FFFFFC, 0x10),
+            ],
+        );
+        let reloc = test_reloc(ObjRelocKind::PpcRel24, 0);
+        obj.sections[0].relocations.insert(0x80000000, reloc.clone()).unwrap();
+        obj.validate_relocations().unwrap();
+        assert!(obj.trampoline_sites().is_empty());
+
+        // Just outside of range
+        obj.sections[0].relocations.replace(0x80000000, ObjReloc { target_symbol: 1, ..reloc });
+        assert!(obj.validate_relocations().is_err());
+        assert_eq!(obj.trampoline_sites(), vec![(0, 0x80000000, 1)]);
+    }
+
+    #[test]
+    fn test_address_to_section() {
+        let sections = vec![
+            test_section_sized(".text", ObjSectionKind::Code, 0x80000000, 0x10),
+            test_section_sized(".data", ObjSectionKind::Data, 0x80001000, 0x10),
+        ];
+        let obj = test_obj(ObjKind::Executable, vec![], sections);
+        assert_eq!(obj.address_to_section(0x80000000), Some(0));
+        assert_eq!(obj.address_to_section(0x8000100C), Some(1));
+        assert_eq!(obj.address_to_section(0x80000010), None);
+        assert_eq!(obj.address_to_section(0x80001010), None);
+
+        let obj = test_obj(ObjKind::Relocatable, vec![], vec![
+            test_section_sized(".text", ObjSectionKind::Code, 0, 0x10),
+            test_section_sized(".data", ObjSectionKind::Data, 0, 0x20),
+        ]);
+        // Ambiguous between .text and .data
+        assert_eq!(obj.address_to_section(0x8), None);
+        assert_eq!(obj.address_to_section(0x18), Some(1));
+        assert_eq!(obj.address_to_section(0x20), None);
+    }
+
+    #[test]
+    fn test_sorted_symbols() {
+        let obj = test_obj(
+            ObjKind::Executable,
+            vec![
+                ObjSymbol { size: 0, ..symbol("label", 0, 0x80000000) },
+                test_symbol("inner", 0, 0x80000008, 4),
+                ObjSymbol { size: 0x10, ..symbol("func", 0, 0x80000000) },
+                test_symbol("data", 1, 0x80001000, 4),
+            ],
+            vec![
+                test_section_sized(".text", ObjSectionKind::Code, 0x80000000, 0x10),
+                test_section_sized(".data", ObjSectionKind::Data, 0x80001000, 0x10),
+                test_section_sized(".bss", ObjSectionKind::Bss, 0x80002000, 0x10),
+            ],
+        );
+        assert_eq!(obj.sorted_symbols(), vec![vec![2, 0, 1], vec![3], vec![]]);
+    }
+
+    #[test]
+    fn test_remap_symbols() {
+        let mut obj = test_obj(
+            ObjKind::Executable,
+            vec![
+                test_symbol("a", 0, 0x80000000, 4),
+                test_symbol("b", 0, 0x80000004, 4),
+                test_symbol("c", 0, 0x80000008, 4),
+                test_symbol("d", 0, 0x8000000C, 4),
+            ],
+            vec![test_section_sized(".text", ObjSectionKind::Code, 0x80000000, 0x10)],
+        );
+        let reloc = test_reloc(ObjRelocKind::PpcRel24, 0);
+        for (address, target_symbol) in [(0x80000000, 3), (0x80000004, 1), (0x80000008, 0)] {
+            obj.sections[0]
+                .relocations
+                .insert(address, ObjReloc { target_symbol, ..reloc.clone() })
+                .unwrap();
+        }
+
+        // Reverse the symbols, dropping b
+        obj.remap_symbols(&[Some(2), None, Some(1), Some(0)]).unwrap();
+        let names = obj.symbols.iter().map(|(_, s)| s.name.as_str()).collect::<Vec<_>>();
+        assert_eq!(names, vec!["d", "c", "a"]);
+        let targets = obj.sections[0]
+            .relocations
+            .iter()
+            .map(|(addr, reloc)| (addr, reloc.target_symbol))
+            .collect::<Vec<_>>();
+        assert_eq!(targets, vec![(0x80000000, 0), (0x80000008, 2)]);
+        assert_eq!(obj.symbols.by_name("a").unwrap().map(|(idx, _)| idx), Some(2));
+
+        // Invalid mappings
+        assert!(obj.remap_symbols(&[Some(0), Some(0), Some(1)]).is_err());
+        assert!(obj.remap_symbols(&[Some(0), Some(1), Some(3)]).is_err());
+        assert!(obj.remap_symbols(&[Some(0)]).is_err());
+    }
+
+    #[test]
+    fn test_coalesce_sections() {
+        let mut obj = test_obj(
+            ObjKind::Executable,
+            vec![
+                test_symbol("func1", 0, 0x80000000, 4),
+                test_symbol("data", 1, 0x80001000, 4),
+                test_symbol("func2", 2, 0x80000010, 4),
+            ],
+            vec![
+                test_section_sized(".text", ObjSectionKind::Code, 0x80000000, 0x10),
+                test_section_sized(".data", ObjSectionKind::Data, 0x80001000, 0x10),
+                test_section_sized(".text1", ObjSectionKind::Code, 0x80000010, 0x10),
+                test_section_sized(".text2", ObjSectionKind::Code, 0x80002000, 0x10),
+            ],
+        );
+        obj.sections[2].data[4..8].copy_from_slice(&[0x48, 0x00, 0x00, 0x01]);
+        let reloc = test_reloc(ObjRelocKind::PpcRel24, 0);
+        obj.sections[2].relocations.insert(0x80000014, reloc).unwrap();
+
+        obj.coalesce_sections(&[ObjSectionKind::Code]).unwrap();
+        assert_eq!(obj.sections.len(), 3);
+        let (text_index, text) = obj.sections.by_name(".text").unwrap().unwrap();
+        assert_eq!(text.size, 0x20);
+        assert_eq!(text.data.len(), 0x20);
+        assert_eq!(&text.data[0x14..0x18], &[0x48, 0x00, 0x00, 0x01]);
+        let (reloc_addr, reloc) = text.relocations.iter().next().unwrap();
+        assert_eq!(reloc.kind, ObjRelocKind::PpcRel24);
+        assert_eq!(reloc_addr as u64 - text.address, 0x14);
+        let (_, func2) = obj.symbols.by_name("func2").unwrap().unwrap();
+        assert_eq!(func2.section, Some(text_index));
+        assert_eq!(func2.address, 0x80000010);
+
+        // Non-adjacent sections are left alone
+        let (text2_index, _) = obj.sections.by_name(".text2").unwrap().unwrap();
+        assert_eq!(text2_index, 2);
+        let (_, data) = obj.symbols.by_name("data").unwrap().unwrap();
+        assert_eq!(data.section, Some(1));
+    }
+
+    #[test]
+    fn test_coalesce_sections_errors() {
+        let sections = || {
+            vec![
+                test_section_sized(".text", ObjSectionKind::Code, 0x80000000, 0x10),
+                test_section_sized(".text1", ObjSectionKind::Code, 0x80000010, 0x10),
+            ]
+        };
+        let mut obj = test_obj(ObjKind::Relocatable, vec![], sections());
+        let err = obj.coalesce_sections(&[ObjSectionKind::Code]).unwrap_err();
+        assert_eq!(err.to_string(), "Coalescing sections requires an executable");
+        assert_eq!(obj.sections.len(), 2);
+
+        let mut obj = test_obj(ObjKind::Executable, vec![], sections());
+        obj.sections[1].data.truncate(8);
+        let err = obj.coalesce_sections(&[ObjSectionKind::Code]).unwrap_err();
+        assert_eq!(err.to_string(), "Section .text1 data length 0x8 doesn't match size 0x10");
+        assert_eq!(obj.sections.len(), 2);
+    }
+
+    #[test]
+    fn test_split_section() {
+        let mut obj = test_obj(
+            ObjKind::Executable,
+            vec![
+                test_symbol("value", 1, 0x80001000, 4),
+                test_symbol("table", 1, 0x80001010, 4),
+                test_symbol("zeroed", 2, 0x80002000, 4),
+            ],
+            vec![
+                test_section_sized(".text", ObjSectionKind::Code, 0x80000000, 0x10),
+                test_section_sized(".data", ObjSectionKind::Data, 0x80001000, 0x20),
+                test_section_sized(".bss", ObjSectionKind::Bss, 0x80002000, 0x10),
+            ],
+        );
+        let data = &mut obj.sections[1];
+        data.data.iter_mut().enumerate().for_each(|(i, b)| *b = i as u8);
+        let reloc = |target_symbol| test_reloc(ObjRelocKind::Absolute, target_symbol);
+        data.relocations.insert(0x80001004, reloc(1)).unwrap();
+        data.relocations.insert(0x80001014, reloc(0)).unwrap();
+
+        assert!(obj.split_section(1, 0x80001002).is_err());
+        assert!(obj.split_section(1, 0x80001020).is_err());
+        obj.split_section(1, 0x80001010).unwrap();
+
+        assert_eq!(obj.sections.len(), 4);
+        let data = &obj.sections[1];
+        assert_eq!((data.size, data.data.len()), (0x10, 0x10));
+        assert_eq!(data.relocations.iter().map(|(addr, _)| addr).collect::<Vec<_>>(), vec![
+            0x80001004
+        ]);
+        let upper = &obj.sections[2];
+        assert_eq!(upper.name, ".data.1");
+        assert_eq!((upper.address, upper.size), (0x80001010, 0x10));
+        assert_eq!(upper.data[0], 0x10);
+        let (addr, reloc) = upper.relocations.iter().next().unwrap();
+        assert_eq!((addr, reloc.target_symbol), (0x80001014, 0));
+
+        let section_of = |name| obj.symbols.by_name(name).unwrap().unwrap().1.section;
+        assert_eq!(section_of("value"), Some(1));
+        assert_eq!(section_of("table"), Some(2));
+        assert_eq!(section_of("zeroed"), Some(3));
+    }
+
+    #[test]
+    fn test_sda_register() {
+        let obj = test_obj(
+            ObjKind::Executable,
+            vec![
+                test_symbol("small", 0, 0x80005000, 4),
+                test_symbol("small_ro", 1, 0x80006000, 4),
+                test_symbol("data", 2, 0x80007000, 4),
+            ],
+            vec![
+                test_section_sized(".sdata", ObjSectionKind::Data, 0x80005000, 0x10),
+                test_section_sized(".sdata2", ObjSectionKind::ReadOnlyData, 0x80006000, 0x10),
+                test_section_sized(".data", ObjSectionKind::Data, 0x80007000, 0x10),
+            ],
+        );
+        let reloc = test_reloc(ObjRelocKind::PpcEmbSda21, 0);
+        assert_eq!(obj.sda_register(&reloc).unwrap(), 13);
+        assert_eq!(obj.sda_register(&ObjReloc { target_symbol: 1, ..reloc.clone() }).unwrap(), 2);
+        assert!(obj.sda_register(&ObjReloc { target_symbol: 2, ..reloc.clone() }).is_err());
+        assert!(obj.sda_register(&ObjReloc { kind: ObjRelocKind::PpcAddr16Lo, ..reloc }).is_err());
+    }
+
+    #[test]
+    fn test_rebase() {
+        let mut obj = test_obj(
+            ObjKind::Executable,
+            vec![
+                test_symbol("func", 0, 0x80003100, 4),
+                test_symbol("data", 1, 0x80004000, 4),
+                ObjSymbol { name: "abs".to_string(), address: 0x1234, ..Default::default() },
+            ],
+            vec![
+                test_section_sized(".text", ObjSectionKind::Code, 0x80003100, 0x10),
+                test_section_sized(".data", ObjSectionKind::Data, 0x80004000, 0x10),
+            ],
+        );
+        obj.sda_base = Some(0x80008000);
+        let reloc = test_reloc(ObjRelocKind::Absolute, 0);
+        obj.sections[1].relocations.insert(0x80004004, reloc).unwrap();
+
+        obj.rebase(0x1000).unwrap();
+        assert_eq!(obj.sections[0].address, 0x80004100);
+        assert_eq!(obj.sections[1].address, 0x80005000);
+        let (_, func) = obj.symbols.by_name("func").unwrap().unwrap();
+        assert_eq!(func.address, 0x80004100);
+        let (_, data) = obj.symbols.by_name("data").unwrap().unwrap();
+        assert_eq!(data.address, 0x80005000);
+        let (_, abs) = obj.symbols.by_name("abs").unwrap().unwrap();
+        assert_eq!(abs.address, 0x1234);
+        assert_eq!(obj.symbols.at_section_address(0, 0x80004100).count(), 1);
+        assert!(obj.sections[1].relocations.at(0x80005004).is_some());
+        assert_eq!(obj.sda_base, Some(0x80009000));
+        obj.validate_relocations().unwrap();
+    }
+
+    #[test]
+    fn test_find_unreachable() {
+        let function = |name: &str, address: u64| ObjSymbol {
+            kind: ObjSymbolKind::Function,
+            size: 0x10,
+            ..symbol(name, 0, address)
+        };
+        let mut obj = test_obj(
+            ObjKind::Executable,
+            vec![
+                function("main", 0x80003100),
+                function("used", 0x80003110),
+                function("orphan", 0x80003120),
+                ObjSymbol {
+                    flags: ObjSymbolFlagSet(ObjSymbolFlags::Weak.into()),
+                    ..function("weak", 0x80003130)
+                },
+            ],
+            vec![test_section_sized(".text", ObjSectionKind::Code, 0x80003100, 0x40)],
+        );
+        let reloc = test_reloc(ObjRelocKind::PpcRel24, 1);
+        obj.sections[0].relocations.insert(0x80003104, reloc).unwrap();
+
+        assert_eq!(obj.find_unreachable(&["main"], false).unwrap(), vec![2, 3]);
+        assert_eq!(obj.find_unreachable(&["main"], true).unwrap(), vec![2]);
+        assert!(obj.find_unreachable(&["missing"], false).is_err());
+    }
+
+    #[test]
+    fn test_relocation_stats() {
+        let mut obj = test_obj(
+            ObjKind::Executable,
+            vec![test_symbol("func", 0, 0x80000000, 4), test_symbol("data", 1, 0x80001000, 4)],
+            vec![
+                test_section_sized(".text", ObjSectionKind::Code, 0x80000000, 0x10),
+                test_section_sized(".data", ObjSectionKind::Data, 0x80001000, 0x10),
+                test_section_sized(".bss", ObjSectionKind::Bss, 0x80002000, 0x10),
+            ],
+        );
+        let reloc = test_reloc(ObjRelocKind::PpcRel24, 0);
+        let text = &mut obj.sections[0].relocations;
+        text.insert(0x80000000, reloc.clone()).unwrap();
+        text.insert(0x80000004, reloc.clone()).unwrap();
+        let sda21 = ObjReloc { kind: ObjRelocKind::PpcEmbSda21, target_symbol: 1, ..reloc };
+        text.insert(0x80000008, sda21.clone()).unwrap();
+        let absolute = ObjReloc { kind: ObjRelocKind::Absolute, ..sda21 };
+        obj.sections[1].relocations.insert(0x80001000, absolute).unwrap();
+
+        assert_eq!(
+            obj.relocation_stats(),
+            BTreeMap::from([
+                (ObjRelocKind::Absolute, 1),
+                (ObjRelocKind::PpcRel24, 2),
+                (ObjRelocKind::PpcEmbSda21, 1),
+            ])
+        );
+        assert_eq!(
+            obj.section_relocation_stats(),
+            BTreeMap::from([
+                (0, BTreeMap::from([(ObjRelocKind::PpcRel24, 2), (ObjRelocKind::PpcEmbSda21, 1)])),
+                (1, BTreeMap::from([(ObjRelocKind::Absolute, 1)])),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_relocations_of_kind() {
+        let mut obj = test_obj(
+            ObjKind::Executable,
+            vec![test_symbol("func", 1, 0x80003100, 4), test_symbol("data", 2, 0x80004000, 4)],
+            vec![
+                test_section_sized(".init", ObjSectionKind::Code, 0x80003000, 0x10),
+                test_section_sized(".text", ObjSectionKind::Code, 0x80003100, 0x10),
+                test_section_sized(".sdata", ObjSectionKind::Data, 0x80004000, 0x10),
+            ],
+        );
+        let rel24 = test_reloc(ObjRelocKind::PpcRel24, 0);
+        let sda21 = ObjReloc { kind: ObjRelocKind::PpcEmbSda21, target_symbol: 1, ..rel24.clone() };
+        let init = &mut obj.sections[0].relocations;
+        init.insert(0x80003004, rel24.clone()).unwrap();
+        init.insert(0x80003008, sda21.clone()).unwrap();
+        let text = &mut obj.sections[1].relocations;
+        text.insert(0x80003100, sda21).unwrap();
+        text.insert(0x8000310C, rel24).unwrap();
+
+        let found = obj
+            .relocations_of_kind(ObjRelocKind::PpcRel24)
+            .map(|(section_index, addr, reloc)| (section_index, addr, reloc.kind))
+            .collect::<Vec<_>>();
+        assert_eq!(found, vec![
+            (0, 0x80003004, ObjRelocKind::PpcRel24),
+            (1, 0x8000310C, ObjRelocKind::PpcRel24),
+        ]);
+        assert_eq!(obj.relocations_of_kind(ObjRelocKind::Absolute).count(), 0);
+    }
+
+    #[test]
+    fn test_absolutize_to_relative() {
+        let mut obj = test_obj(
+            ObjKind::Executable,
+            vec![test_symbol("func", 0, 0x80003100, 4), test_symbol("value", 1, 0x80004008, 4)],
+            vec![
+                test_section_sized(".text", ObjSectionKind::Code, 0x80003100, 0x10),
+                test_section_sized(".data", ObjSectionKind::Data, 0x80004000, 0x10),
+            ],
+        );
+        // lis r3, (value+0x4)@ha; addi r3, r3, (value+0x4)@l
+        obj.sections[0].data[..8]
+            .copy_from_slice(&[0x3C, 0x60, 0x00, 0x00, 0x38, 0x63, 0x00, 0x00]);
+        let reloc =
+            |kind, target_symbol, addend| ObjReloc { addend, ..test_reloc(kind, target_symbol) };
+        let text = &mut obj.sections[0].relocations;
+        text.insert(0x80003100, reloc(ObjRelocKind::PpcAddr16Ha, 1, 4)).unwrap();
+        text.insert(0x80003104, reloc(ObjRelocKind::PpcAddr16Lo, 1, 4)).unwrap();
+        obj.sections[1]
+            .relocations
+            .insert(0x80004000, reloc(ObjRelocKind::Absolute, 0, 8))
+            .unwrap();
+
+        let bases = [[0x80003100, 0x80004000], [0x80010000, 0x80020000]];
+        let expected = bases
+            .iter()
+            .map(|b| [obj.apply_relocations(0, b).unwrap(), obj.apply_relocations(1, b).unwrap()])
+            .collect::<Vec<_>>();
+        obj.absolutize_to_relative().unwrap();
+
+        let data_reloc = obj.sections[1].relocations.at(0x80004000).unwrap();
+        let target = &obj.symbols[data_reloc.target_symbol];
+        assert_eq!(
+            (target.kind, target.section, data_reloc.addend),
+            (ObjSymbolKind::Section, Some(0), 8)
+        );
+        let lo_reloc = obj.sections[0].relocations.at(0x80003104).unwrap();
+        let target = &obj.symbols[lo_reloc.target_symbol];
+        assert_eq!(
+            (target.kind, target.section, lo_reloc.addend),
+            (ObjSymbolKind::Section, Some(1), 0xC)
+        );
+        // Relocated data is unchanged wherever the sections are placed
+        for (b, expected) in bases.iter().zip(expected) {
+            assert_eq!(
+                [obj.apply_relocations(0, b).unwrap(), obj.apply_relocations(1, b).unwrap()],
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn test_apply_sda21_relocation() {
+        let mut obj = test_obj(
+            ObjKind::Executable,
+            vec![test_symbol("value", 1, 0x80004010, 4), test_symbol("const", 2, 0x80005000, 4)],
+            vec![
+                test_section_sized(".text", ObjSectionKind::Code, 0x80003100, 0x10),
+                test_section_sized(".sdata", ObjSectionKind::Data, 0x80004000, 0x20),
+                test_section_sized(".sdata2", ObjSectionKind::ReadOnlyData, 0x80005000, 0x10),
+            ],
+        );
+        // lwz r3, value@sda21(r0); lfs f1, const@sda21(r0)
+        // lwz r3, value@sdarel(r13); lfs f1, const@sdarel(r2)
+        obj.sections[0].data.copy_from_slice(&[
+            0x80, 0x60, 0x00, 0x00, 0xC0, 0x20, 0x00, 0x00, 0x80, 0x6D, 0x00, 0x00, 0xC0, 0x22,
+            0x00, 0x00,
+        ]);
+        let reloc = ObjReloc { addend: 4, ..test_reloc(ObjRelocKind::PpcEmbSda21, 0) };
+        let relocations = &mut obj.sections[0].relocations;
+        relocations.insert(0x80003100, reloc.clone()).unwrap();
+        relocations.insert(0x80003104, ObjReloc { target_symbol: 1, addend: 0, ..reloc }).unwrap();
+        let reloc = ObjReloc { addend: 8, ..test_reloc(ObjRelocKind::PpcSdaRel16, 0) };
+        relocations.insert(0x8000310A, reloc.clone()).unwrap();
+        relocations.insert(0x8000310E, ObjReloc { target_symbol: 1, addend: 4, ..reloc }).unwrap();
+        let bases = obj.sections.iter().map(|(_, s)| s.address).collect::<Vec<_>>();
+
+        let err = obj.apply_relocations(0, &bases).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "R_PPC_EMB_SDA21 relocation @ .text 0x80003100 requires unknown SDA base for r13"
+        );
+
+        obj.sda_base = Some(0x8000C000);
+        obj.sda2_base = Some(0x8000D000);
+        let text = obj.apply_relocations(0, &bases).unwrap();
+        // value+4 - _SDA_BASE_ = -0x7FEC, encoded with r13
+        assert_eq!(text[..4], [0x80, 0x6D, 0x80, 0x14]);
+        // const - _SDA2_BASE_ = -0x8000, encoded with r2
+        assert_eq!(text[4..8], [0xC0, 0x22, 0x80, 0x00]);
+        // value+8 - _SDA_BASE_ = -0x7FE8
+        assert_eq!(text[8..12], [0x80, 0x6D, 0x80, 0x18]);
+        // const+4 - _SDA2_BASE_ = -0x7FFC
+        assert_eq!(text[12..16], [0xC0, 0x22, 0x80, 0x04]);
+    }
+
+    #[test]
+    fn test_apply_relocations() {
+        let mut obj = test_obj(
+            ObjKind::Executable,
+            vec![test_symbol("func", 0, 0x80003100, 4), test_symbol("target", 0, 0x80003108, 4)],
+            vec![
+                test_section_sized(".text", ObjSectionKind::Code, 0x80003100, 0x10),
+                test_section_sized(".data", ObjSectionKind::Data, 0x80004000, 0x10),
+                test_section_sized(".bss", ObjSectionKind::Bss, 0x80005000, 0x10),
+            ],
+        );
+        obj.sections[0].data[..4].copy_from_slice(&[0x48, 0x00, 0x00, 0x01]);
+        let reloc = test_reloc(ObjRelocKind::PpcRel24, 1);
+        obj.sections[0].relocations.insert(0x80003100, reloc.clone()).unwrap();
+        let absolute =
+            ObjReloc { kind: ObjRelocKind::Absolute, target_symbol: 0, addend: 4, ..reloc };
+        obj.sections[1].relocations.insert(0x80004004, absolute).unwrap();
+        // R_PPC_TOC16 can't be applied, so it's skipped
+        let other = test_reloc(ObjRelocKind::Other(object::elf::R_PPC_TOC16), 1);
+        obj.sections[0].relocations.insert(0x80003104, other).unwrap();
+
+        let bases = obj.sections.iter().map(|(_, s)| s.address).collect::<Vec<_>>();
+        let text = obj.apply_relocations(0, &bases).unwrap();
+        assert_eq!(text[..8], [0x48, 0x00, 0x00, 0x09, 0x00, 0x00, 0x00, 0x00]);
+        let data = obj.apply_relocations(1, &bases).unwrap();
+        assert_eq!(data[..8], [0x00, 0x00, 0x00, 0x00, 0x80, 0x00, 0x31, 0x04]);
+        // Original data is untouched
+        assert_eq!(obj.sections[1].data[4..8], [0; 4]);
+
+        // Relocate .text elsewhere
+        let data = obj.apply_relocations(1, &[0x80010000, 0x80004000, 0x80005000]).unwrap();
+        assert_eq!(data[4..8], [0x80, 0x01, 0x00, 0x04]);
+
+        assert!(obj.apply_relocations(2, &bases).is_err());
+        assert!(obj.apply_relocations(0, &bases[..2]).is_err());
+    }
+
+    #[test]
+    fn test_symbol_display_name() {
+        // C symbols can't be demangled, so they display their raw name
+        let symbol = test_symbol("main", 0, 0x80003100, 4);
+        assert_eq!(symbol.demangled_name, None);
+        assert_eq!(symbol.display_name(), "main");
+        let symbol = ObjSymbol { demangled_name: Some("func(int)".to_string()), ..symbol };
+        assert_eq!(symbol.display_name(), "func(int)");
+    }
+
+    #[test]
+    fn test_rename_symbol() {
+        let mut obj = test_obj(
+            ObjKind::Executable,
+            vec![
+                test_symbol("fn_80003100", 0, 0x80003100, 4),
+                test_symbol("value", 1, 0x80004000, 4),
+            ],
+            vec![
+                test_section_sized(".text", ObjSectionKind::Code, 0x80003100, 0x10),
+                test_section_sized(".data", ObjSectionKind::Data, 0x80004000, 0x10),
+            ],
+        );
+        let reloc = test_reloc(ObjRelocKind::Absolute, 0);
+        obj.sections[1].relocations.insert(0x80004000, reloc).unwrap();
+
+        obj.rename_symbol(0, "func__Fi".to_string()).unwrap();
+        let (index, symbol) = obj.symbols.by_name("func__Fi").unwrap().unwrap();
+        assert_eq!(index, 0);
+        assert_eq!(symbol.demangled_name.as_deref(), Some("func(int)"));
+        assert!(obj.symbols.by_name("fn_80003100").unwrap().is_none());
+        assert_eq!(obj.sections[1].relocations.at(0x80004000).unwrap().target_symbol, 0);
+        // Renaming a symbol to its current name is allowed
+        obj.rename_symbol(0, "func__Fi".to_string()).unwrap();
+    }
+
+    #[test]
+    fn test_rename_symbol_collision() {
+        let mut obj = test_obj(
+            ObjKind::Executable,
+            vec![test_symbol("func", 0, 0x80003100, 4), test_symbol("other", 0, 0x80003104, 4)],
+            vec![test_section_sized(".text", ObjSectionKind::Code, 0x80003100, 0x10)],
+        );
+        let err = obj.rename_symbol(1, "func".to_string()).unwrap_err();
+        assert!(err.to_string().starts_with("Can't rename symbol to func"), "{err}");
+        assert_eq!(obj.symbols[1].name, "other");
+    }
+
+    #[test]
+    fn test_section_lookup() {
+        let mut obj = test_obj(ObjKind::Executable, vec![], vec![
+            ObjSection { elf_index: 1, ..section(".text", ObjSectionKind::Code, 0x80003100, 4) },
+            ObjSection { elf_index: 3, ..section(".data", ObjSectionKind::Data, 0x80004000, 4) },
+        ]);
+        assert_eq!(obj.section_by_name(".data").map(|s| s.address), Some(0x80004000));
+        assert!(obj.section_by_name(".bss").is_none());
+        obj.section_by_name_mut(".text").unwrap().align = 32;
+        assert_eq!(obj.sections[0].align, 32);
+        assert!(obj.section_by_name_mut(".bss").is_none());
+
+        assert_eq!(obj.section_by_elf_index(3).map(|s| s.name.as_str()), Some(".data"));
+        assert!(obj.section_by_elf_index(2).is_none());
+    }
+
+    #[test]
+    fn test_clone_shares_section_data() {
+        let obj = test_obj(ObjKind::Executable, vec![], vec![test_section_sized(
+            ".data",
+            ObjSectionKind::Data,
+            0x80004000,
+            8,
+        )]);
+        let mut copy = obj.clone();
+        assert!(copy.sections[0].data.ptr_eq(&obj.sections[0].data));
+
+        // Writing to the copy detaches it without affecting the original
+        copy.sections[0].data[0] = 0xFF;
+        assert!(!copy.sections[0].data.ptr_eq(&obj.sections[0].data));
+        assert_eq!(copy.sections[0].data, [0xFF, 0, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(obj.sections[0].data, [0; 8]);
+    }
+
+    #[test]
+    fn test_entry_symbol() {
+        let mut obj = test_obj(
+            ObjKind::Executable,
+            vec![test_symbol("lbl_80003100", 0, 0x80003100, 4), ObjSymbol {
+                kind: ObjSymbolKind::Function,
+                ..symbol("__start", 0, 0x80003100)
+            }],
+            vec![test_section_sized(".init", ObjSectionKind::Code, 0x80003100, 0x10)],
+        );
+        assert!(obj.entry_symbol().is_none());
+        obj.entry = Some(0x80003100);
+        assert_eq!(obj.entry_symbol().map(|s| s.name.as_str()), Some("__start"));
+        obj.entry = Some(0x80003104);
+        assert!(obj.entry_symbol().is_none());
+    }
+
+    #[test]
+    fn test_relocation_report() {
+        let mut obj = test_obj(
+            ObjKind::Executable,
+            vec![
+                ObjSymbol {
+                    demangled_name: Some("func(int)".to_string()),
+                    ..symbol("func__Fi", 0, 0x80003100)
+                },
+                test_symbol("value", 1, 0x80004000, 4),
+            ],
+            vec![
+                test_section_sized(".text", ObjSectionKind::Code, 0x80003100, 0x10),
+                test_section_sized(".data", ObjSectionKind::Data, 0x80004000, 0x10),
+            ],
+        );
+        let reloc = test_reloc(ObjRelocKind::Absolute, 1);
+        obj.sections[1].relocations.insert(0x80004008, reloc.clone()).unwrap();
+        obj.sections[1]
+            .relocations
+            .insert(0x80004004, ObjReloc { target_symbol: 0, addend: 0x10, ..reloc.clone() })
+            .unwrap();
+        obj.sections[0]
+            .relocations
+            .insert(0x80003104, ObjReloc { kind: ObjRelocKind::PpcAddr16Ha, addend: -4, ..reloc })
+            .unwrap();
+        assert_eq!(
+            obj.relocation_report(),
+            ".text:0x00000004 PpcAddr16Ha -> value-0x4\n\
+             .data:0x00000004 Absolute -> func(int)+0x10\n\
+             .data:0x00000008 Absolute -> value\n"
+        );
+    }
+
+    #[test]
+    fn test_strip_section_data() {
+        let mut obj = test_obj(
+            ObjKind::Executable,
+            vec![test_symbol("func", 0, 0x80003100, 4), test_symbol("value", 1, 0x80004000, 4)],
+            vec![
+                test_section_sized(".text", ObjSectionKind::Code, 0x80003100, 0x10),
+                test_section_sized(".data", ObjSectionKind::Data, 0x80004000, 0x10),
+            ],
+        );
+        let reloc = test_reloc(ObjRelocKind::Absolute, 0);
+        obj.sections[1].relocations.insert(0x80004004, reloc).unwrap();
+        assert!(crate::util::elf::write_elf(&obj, false).is_ok());
+
+        obj.strip_section_data();
+        assert!(obj.data_stripped);
+        for (_, section) in obj.sections.iter() {
+            assert!(section.data.is_empty());
+            assert_eq!(section.size, 0x10);
+        }
+        assert_eq!(obj.symbols.count(), 2);
+        assert_eq!(obj.sections[1].relocations.at(0x80004004).unwrap().target_symbol, 0);
+        assert!(crate::util::elf::write_elf(&obj, false).is_err());
+    }
+
+    #[test]
+    fn test_memory_summary() {
+        let mut obj = test_obj(ObjKind::Executable, vec![], vec![
+            test_section_sized(".init", ObjSectionKind::Code, 0x80003100, 0x100),
+            test_section_sized(".text", ObjSectionKind::Code, 0x80003200, 0x1000),
+            test_section_sized(".rodata", ObjSectionKind::ReadOnlyData, 0x80004200, 0x40),
+            test_section_sized(".data", ObjSectionKind::Data, 0x80004240, 0x20),
+            test_section_sized(".bss", ObjSectionKind::Bss, 0x80004260, 0x80),
+            test_section_sized(".sdata", ObjSectionKind::Data, 0x800042E0, 0x10),
+            test_section_sized(".note.split", ObjSectionKind::Note, 0, 0x20),
+        ]);
+        assert_eq!(obj.data_size(), 0xF0);
+        assert_eq!(obj.memory_summary(), MemorySummary {
+            code_size: 0x1100,
+            data_size: 0x30,
+            rodata_size: 0x40,
+            bss_size: 0x80,
+            end_address: Some(0x800042F0),
+            arena_size: None,
+        });
+
+        obj.arena_lo = Some(0x80010000);
+        obj.arena_hi = Some(0x81700000);
+        assert_eq!(obj.memory_summary().arena_size, Some(0x16F0000));
+    }
+
+    #[test]
+    fn test_section_coverage() {
+        let obj = test_obj(
+            ObjKind::Executable,
+            vec![
+                test_symbol("a", 0, 0x80003100, 4),
+                test_symbol("b", 0, 0x80003108, 4),
+                ObjSymbol { size: 0x8, ..symbol("c", 0, 0x80003110) },
+                test_symbol("d", 0, 0x80003114, 4),
+                test_symbol("e", 1, 0x80004004, 4),
+            ],
+            vec![
+                test_section_sized(".text", ObjSectionKind::Code, 0x80003100, 0x20),
+                test_section_sized(".bss", ObjSectionKind::Bss, 0x80004000, 0x8),
+            ],
+        );
+        assert_eq!(obj.section_coverage(0), vec![0x80003104..0x80003108, 0x80003118..0x80003120]);
+        assert_eq!(obj.section_coverage(1), vec![0x80004000..0x80004004]);
+    }
+
+    #[test]
+    fn test_unit_for_symbol() {
+        let mut obj = test_obj(
+            ObjKind::Executable,
+            vec![
+                test_symbol("a", 0, 0x80003100, 4),
+                test_symbol("b", 0, 0x80003110, 4),
+                test_symbol("c", 0, 0x8000312C, 4),
+                test_symbol("value", 1, 0x80004000, 4),
+            ],
+            vec![
+                test_section_sized(".text", ObjSectionKind::Code, 0x80003100, 0x30),
+                test_section_sized(".data", ObjSectionKind::Data, 0x80004000, 0x10),
+            ],
+        );
+        for (unit, start) in [("a.cpp", 0x80003100), ("b.cpp", 0x80003110), ("c.cpp", 0x80003120)] {
+            obj.sections[0].splits.push(start, ObjSplit {
+                unit: unit.to_string(),
+                end: start + 0x10,
+                align: None,
+                common: false,
+                autogenerated: false,
+                skip: false,
+                rename: None,
+            });
+        }
+        assert_eq!(obj.unit_for_symbol(0), Some("a.cpp"));
+        assert_eq!(obj.unit_for_symbol(1), Some("b.cpp"));
+        assert_eq!(obj.unit_for_symbol(2), Some("c.cpp"));
+        assert_eq!(obj.unit_for_symbol(3), None);
+        assert_eq!(obj.unit_for_symbol(4), None);
+    }
+
+    /// Builds a DOL with a single text and data section.
+    fn test_dol(text: (u32, &[u8]), data: (u32, &[u8])) -> Vec<u8> {
+        let mut dol = vec![0u8; 0x100];
+        let header = [
+            (0x00, 0x100),                       // text_offs[0]
+            (0x1C, 0x100 + text.1.len() as u32), // data_offs[0]
+            (0x48, text.0),                      // text_addrs[0]
+            (0x64, data.0),                      // data_addrs[0]
+            (0x90, text.1.len() as u32),         // text_sizes[0]
+            (0xAC, data.1.len() as u32),         // data_sizes[0]
+        ];
+        for (offset, value) in header {
+            dol[offset..offset + 4].copy_from_slice(&u32::to_be_bytes(value));
+        }
+        dol.extend_from_slice(text.1);
+        dol.extend_from_slice(data.1);
+        dol
+    }
+
+    #[test]
+    fn test_verify_against_dol() {
+        let mut obj = test_obj(
+            ObjKind::Executable,
+            vec![test_symbol("target", 0, 0x80003108, 4), test_symbol("value", 1, 0x80004004, 4)],
+            vec![
+                test_section_sized(".text", ObjSectionKind::Code, 0x80003100, 0x10),
+                test_section_sized(".data", ObjSectionKind::Data, 0x80004000, 0x8),
+            ],
+        );
+        obj.sections[0].data[..4].copy_from_slice(&[0x48, 0x00, 0x00, 0x01]);
+        let reloc = test_reloc(ObjRelocKind::PpcRel24, 0);
+        obj.sections[0].relocations.insert(0x80003100, reloc.clone()).unwrap();
+        let absolute = ObjReloc { kind: ObjRelocKind::Absolute, ..reloc };
+        obj.sections[1].relocations.insert(0x80004000, absolute).unwrap();
+
+        let mut text = [0; 0x10];
+        text[..4].copy_from_slice(&[0x48, 0x00, 0x00, 0x09]);
+        let data = [0x80, 0x00, 0x31, 0x08, 0x12, 0x34, 0x56, 0x78];
+        let dol = test_dol((0x80003100, &text), (0x80004000, &data));
+
+        let report = obj.verify_against_dol(&dol).unwrap();
+        assert!(!report.is_match());
+        assert_eq!(report.matched_sections, vec![0]);
+        assert_eq!(report.mismatches, vec![VerifyMismatch {
+            section: 1,
+            address: 0x80004004,
+            expected: 0x12,
+            found: 0x00,
+            symbol: Some(1),
+        }]);
+
+        obj.sections[1].data[4..8].copy_from_slice(&[0x12, 0x34, 0x56, 0x78]);
+        let report = obj.verify_against_dol(&dol).unwrap();
+        assert!(report.is_match());
+        assert_eq!(report.matched_sections, vec![0, 1]);
+    }
+
+    #[test]
+    fn test_verify_against_dol_sda() {
+        let mut obj = test_obj(
+            ObjKind::Executable,
+            vec![test_symbol("func", 0, 0x80003100, 8), test_symbol("value", 1, 0x80004004, 4)],
+            vec![
+                test_section_sized(".text", ObjSectionKind::Code, 0x80003100, 0x8),
+                test_section_sized(".sdata", ObjSectionKind::Data, 0x80004000, 0x8),
+            ],
+        );
+        // lwz r3, value@sda21(r0); stw r3, value@sdarel(r13)
+        obj.sections[0].data.copy_from_slice(&[0x80, 0x60, 0x00, 0x00, 0x90, 0x6D, 0x00, 0x00]);
+        let relocations = &mut obj.sections[0].relocations;
+        relocations.insert(0x80003100, test_reloc(ObjRelocKind::PpcEmbSda21, 1)).unwrap();
+        relocations.insert(0x80003106, test_reloc(ObjRelocKind::PpcSdaRel16, 1)).unwrap();
+        // value - _SDA_BASE_ = -0x7FFC
+        let text = [0x80, 0x6D, 0x80, 0x04, 0x90, 0x6D, 0x80, 0x04];
+        let dol = test_dol((0x80003100, &text), (0x80004000, &[0; 8]));
+
+        obj.sda_base = Some(0x8000C000);
+        let report = obj.verify_against_dol(&dol).unwrap();
+        assert!(report.is_match());
+        assert_eq!(report.matched_sections, vec![0, 1]);
+
+        obj.sda_base = Some(0x8000BFFC);
+        let report = obj.verify_against_dol(&dol).unwrap();
+        assert_eq!(report.matched_sections, vec![1]);
+        assert_eq!(report.mismatches, vec![VerifyMismatch {
+            section: 0,
+            address: 0x80003103,
+            expected: 0x04,
+            found: 0x08,
+            symbol: Some(0),
+        }]);
+    }
 }
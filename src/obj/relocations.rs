@@ -11,7 +11,7 @@ use serde::{Deserialize, Serialize};
 
 use crate::obj::SymbolIndex;
 
-#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
 pub enum ObjRelocKind {
     Absolute,
     PpcAddr16Hi,
@@ -19,21 +19,36 @@ pub enum ObjRelocKind {
     PpcAddr16Lo,
     PpcRel24,
     PpcRel14,
+    /// `R_PPC_REL14` with a static "branch taken" prediction. The linker sets the
+    /// instruction's prediction bit itself, so the original type must be kept.
+    PpcRel14BrTaken,
+    /// `R_PPC_REL14` with a static "branch not taken" prediction.
+    PpcRel14BrnTaken,
     PpcEmbSda21,
+    /// 16-bit offset from the small data base (`_SDA_BASE_`). The addend is relative to
+    /// the target symbol, and the linker subtracts the SDA base when resolving it.
+    PpcSdaRel16,
+    /// Relocation of a type we don't otherwise handle (e.g. `R_PPC_TOC16`), preserved
+    /// as-is with its raw ELF type. These can be written back out, but not applied.
+    Other(u32),
 }
 
 impl Serialize for ObjRelocKind {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where S: serde::Serializer {
-        serializer.serialize_str(match self {
-            ObjRelocKind::Absolute => "abs",
-            ObjRelocKind::PpcAddr16Hi => "hi",
-            ObjRelocKind::PpcAddr16Ha => "ha",
-            ObjRelocKind::PpcAddr16Lo => "l",
-            ObjRelocKind::PpcRel24 => "rel24",
-            ObjRelocKind::PpcRel14 => "rel14",
-            ObjRelocKind::PpcEmbSda21 => "sda21",
-        })
+        match self {
+            ObjRelocKind::Absolute => serializer.serialize_str("abs"),
+            ObjRelocKind::PpcAddr16Hi => serializer.serialize_str("hi"),
+            ObjRelocKind::PpcAddr16Ha => serializer.serialize_str("ha"),
+            ObjRelocKind::PpcAddr16Lo => serializer.serialize_str("l"),
+            ObjRelocKind::PpcRel24 => serializer.serialize_str("rel24"),
+            ObjRelocKind::PpcRel14 => serializer.serialize_str("rel14"),
+            ObjRelocKind::PpcRel14BrTaken => serializer.serialize_str("rel14_brtaken"),
+            ObjRelocKind::PpcRel14BrnTaken => serializer.serialize_str("rel14_brntaken"),
+            ObjRelocKind::PpcEmbSda21 => serializer.serialize_str("sda21"),
+            ObjRelocKind::PpcSdaRel16 => serializer.serialize_str("sdarel"),
+            ObjRelocKind::Other(r_type) => serializer.serialize_str(&format!("other:{}", r_type)),
+        }
     }
 }
 
@@ -47,15 +62,75 @@ impl<'de> Deserialize<'de> for ObjRelocKind {
             "PpcAddr16Lo" | "l" => Ok(ObjRelocKind::PpcAddr16Lo),
             "PpcRel24" | "rel24" => Ok(ObjRelocKind::PpcRel24),
             "PpcRel14" | "rel14" => Ok(ObjRelocKind::PpcRel14),
+            "PpcRel14BrTaken" | "rel14_brtaken" => Ok(ObjRelocKind::PpcRel14BrTaken),
+            "PpcRel14BrnTaken" | "rel14_brntaken" => Ok(ObjRelocKind::PpcRel14BrnTaken),
             "PpcEmbSda21" | "sda21" => Ok(ObjRelocKind::PpcEmbSda21),
+            "PpcSdaRel16" | "sdarel" => Ok(ObjRelocKind::PpcSdaRel16),
+            s if s.starts_with("other:") => s["other:".len()..]
+                .parse()
+                .map(ObjRelocKind::Other)
+                .map_err(serde::de::Error::custom),
             s => Err(serde::de::Error::unknown_variant(s, &[
-                "abs", "hi", "ha", "l", "rel24", "rel14", "sda21",
+                "abs",
+                "hi",
+                "ha",
+                "l",
+                "rel24",
+                "rel14",
+                "rel14_brtaken",
+                "rel14_brntaken",
+                "sda21",
+                "sdarel",
+                "other:<type>",
             ])),
         }
     }
 }
 
-#[derive(Debug, Clone)]
+impl ObjRelocKind {
+    /// The number of bytes patched by the relocation, starting at its ELF r_offset.
+    pub fn width(self) -> u32 {
+        match self {
+            ObjRelocKind::PpcAddr16Hi
+            | ObjRelocKind::PpcAddr16Ha
+            | ObjRelocKind::PpcAddr16Lo
+            | ObjRelocKind::PpcSdaRel16 => 2,
+            ObjRelocKind::Absolute
+            | ObjRelocKind::PpcRel24
+            | ObjRelocKind::PpcRel14
+            | ObjRelocKind::PpcRel14BrTaken
+            | ObjRelocKind::PpcRel14BrnTaken
+            | ObjRelocKind::PpcEmbSda21 => 4,
+            ObjRelocKind::Other(r_type) if is_half16(r_type) => 2,
+            ObjRelocKind::Other(_) => 4,
+        }
+    }
+}
+
+/// Whether an otherwise unhandled relocation type patches a halfword, which is
+/// assumed to be the low half of an instruction like the `@l` family.
+fn is_half16(r_type: u32) -> bool {
+    matches!(
+        r_type,
+        elf::R_PPC_ADDR16
+            | elf::R_PPC_GOT16..=elf::R_PPC_GOT16_HA
+            | elf::R_PPC_UADDR16
+            | elf::R_PPC_PLT16_LO..=elf::R_PPC_PLT16_HA
+            | elf::R_PPC_SECTOFF..=elf::R_PPC_SECTOFF_HA
+            | elf::R_PPC_TPREL16..=elf::R_PPC_TPREL16_HA
+            | elf::R_PPC_DTPREL16..=elf::R_PPC_DTPREL16_HA
+            | elf::R_PPC_GOT_TLSGD16..=elf::R_PPC_GOT_DTPREL16_HA
+            | elf::R_PPC_EMB_NADDR16..=elf::R_PPC_EMB_SDA2REL
+            | elf::R_PPC_EMB_RELSEC16..=elf::R_PPC_EMB_RELST_HA
+            | elf::R_PPC_EMB_RELSDA
+            | elf::R_PPC_DIAB_RELSDA_LO..=elf::R_PPC_DIAB_RELSDA_HA
+            | elf::R_PPC_REL16..=elf::R_PPC_REL16_HA
+            | elf::R_PPC_TOC16
+    )
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct ObjReloc {
     pub kind: ObjRelocKind,
     // pub address: u64,
@@ -63,6 +138,10 @@ pub struct ObjReloc {
     pub addend: i64,
     /// If present, relocation against external module
     pub module: Option<u32>,
+    /// Offset to the paired relocation: from an `@ha`/`@h` to its first `@l`,
+    /// or from an `@l` back to its `@ha`/`@h`.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub pair: Option<i32>,
 }
 
 impl ObjReloc {
@@ -97,16 +176,33 @@ impl ObjReloc {
                 r_offset &= !3;
                 elf::R_PPC_REL14
             }
+            ObjRelocKind::PpcRel14BrTaken => {
+                r_offset &= !3;
+                elf::R_PPC_REL14_BRTAKEN
+            }
+            ObjRelocKind::PpcRel14BrnTaken => {
+                r_offset &= !3;
+                elf::R_PPC_REL14_BRNTAKEN
+            }
             ObjRelocKind::PpcEmbSda21 => {
                 r_offset &= !3;
                 elf::R_PPC_EMB_SDA21
             }
+            ObjRelocKind::PpcSdaRel16 => {
+                r_offset = (r_offset & !3) + 2;
+                elf::R_PPC_SDAREL16
+            }
+            ObjRelocKind::Other(r_type) => {
+                r_offset = (r_offset & !3) + 4 - self.kind.width() as u64;
+                r_type
+            }
         };
         (r_offset, r_type)
     }
 }
 
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct ObjRelocations {
     relocations: BTreeMap<u32, ObjReloc>,
 }
@@ -142,6 +238,34 @@ impl ObjRelocations {
 
     pub fn len(&self) -> usize { self.relocations.len() }
 
+    /// Pairs each `@l` relocation with the closest preceding `@ha`/`@h` relocation
+    /// against the same symbol, regardless of addend. Every `@l` points back to its
+    /// `@ha`/`@h`, but an `@ha`/`@h` shared by several `@l` relocations only records the
+    /// first of them.
+    pub fn pair_hi_lo(&mut self) {
+        let mut high = BTreeMap::<SymbolIndex, u32>::new();
+        let mut pairs = vec![];
+        for (&addr, reloc) in &self.relocations {
+            match reloc.kind {
+                ObjRelocKind::PpcAddr16Hi | ObjRelocKind::PpcAddr16Ha => {
+                    high.insert(reloc.target_symbol, addr);
+                }
+                ObjRelocKind::PpcAddr16Lo => {
+                    if let Some(&hi_addr) = high.get(&reloc.target_symbol) {
+                        pairs.push((hi_addr, addr));
+                    }
+                }
+                _ => {}
+            }
+        }
+        for (hi_addr, lo_addr) in pairs {
+            let offset = (lo_addr - hi_addr) as i32;
+            let hi = self.relocations.get_mut(&hi_addr).unwrap();
+            hi.pair.get_or_insert(offset);
+            self.relocations.get_mut(&lo_addr).unwrap().pair = Some(-offset);
+        }
+    }
+
     pub fn insert(&mut self, address: u32, reloc: ObjReloc) -> Result<(), ExistingRelocationError> {
         let address = address & !3;
         match self.relocations.entry(address) {
@@ -181,4 +305,9 @@ impl ObjRelocations {
     }
 
     pub fn contains(&self, address: u32) -> bool { self.relocations.contains_key(&address) }
+
+    pub fn retain<F>(&mut self, mut f: F)
+    where F: FnMut(u32, &mut ObjReloc) -> bool {
+        self.relocations.retain(|&addr, reloc| f(addr, reloc))
+    }
 }
@@ -1,11 +1,15 @@
 use std::{
     cmp::min,
     collections::Bound,
-    ops::{Index, IndexMut, Range, RangeBounds},
+    ops::{Deref, DerefMut, Index, IndexMut, Range, RangeBounds},
+    sync::Arc,
 };
 
 use anyhow::{anyhow, bail, ensure, Result};
 use itertools::Itertools;
+use object::elf;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 
 use crate::{
     analysis::cfa::SectionAddress,
@@ -13,20 +17,56 @@ use crate::{
 };
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum ObjSectionKind {
     Code,
     Data,
     ReadOnlyData,
     Bss,
+    /// Opaque `.note.*` section, preserved as-is
+    Note,
 }
 
-#[derive(Debug, Clone)]
+impl ObjSectionKind {
+    #[inline]
+    pub fn is_bss(self) -> bool { self == ObjSectionKind::Bss }
+
+    /// Whether the section occupies memory at runtime.
+    #[inline]
+    pub fn loadable(self) -> bool { self != ObjSectionKind::Note }
+
+    /// ELF section flags (`SHF_*`) for this kind.
+    pub fn flags(self) -> u32 {
+        match self {
+            ObjSectionKind::Code => elf::SHF_ALLOC | elf::SHF_EXECINSTR,
+            ObjSectionKind::Data | ObjSectionKind::Bss => elf::SHF_ALLOC | elf::SHF_WRITE,
+            ObjSectionKind::ReadOnlyData => elf::SHF_ALLOC,
+            ObjSectionKind::Note => 0,
+        }
+    }
+
+    /// Sort key placing code first, followed by read-only data, data, BSS and
+    /// finally non-loadable sections.
+    pub fn canonical_order(self) -> u8 {
+        match self {
+            ObjSectionKind::Code => 0,
+            ObjSectionKind::ReadOnlyData => 1,
+            ObjSectionKind::Data => 2,
+            ObjSectionKind::Bss => 3,
+            ObjSectionKind::Note => 4,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct ObjSection {
     pub name: String,
     pub kind: ObjSectionKind,
     pub address: u64,
     pub size: u64,
-    pub data: Vec<u8>,
+    #[cfg_attr(feature = "serde", serde(with = "base64_serde"))]
+    pub data: SectionData,
     pub align: u64,
     /// REL files reference the original ELF section indices
     pub elf_index: SectionIndex,
@@ -37,7 +77,61 @@ pub struct ObjSection {
     pub splits: ObjSplits,
 }
 
-#[derive(Debug, Clone)]
+/// Section contents. Clones share the same bytes, which are only copied once one of
+/// them is modified through [`DerefMut`].
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct SectionData(Arc<Vec<u8>>);
+
+impl SectionData {
+    /// Whether both share the same underlying allocation.
+    #[inline]
+    pub fn ptr_eq(&self, other: &SectionData) -> bool { Arc::ptr_eq(&self.0, &other.0) }
+}
+
+impl Deref for SectionData {
+    type Target = Vec<u8>;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target { &self.0 }
+}
+
+impl DerefMut for SectionData {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut Self::Target { Arc::make_mut(&mut self.0) }
+}
+
+impl AsRef<[u8]> for SectionData {
+    #[inline]
+    fn as_ref(&self) -> &[u8] { &self.0 }
+}
+
+impl From<Vec<u8>> for SectionData {
+    #[inline]
+    fn from(data: Vec<u8>) -> Self { Self(Arc::new(data)) }
+}
+
+impl From<&[u8]> for SectionData {
+    #[inline]
+    fn from(data: &[u8]) -> Self { Self(Arc::new(data.to_vec())) }
+}
+
+impl PartialEq<[u8]> for SectionData {
+    #[inline]
+    fn eq(&self, other: &[u8]) -> bool { self.as_slice() == other }
+}
+
+impl PartialEq<Vec<u8>> for SectionData {
+    #[inline]
+    fn eq(&self, other: &Vec<u8>) -> bool { self.as_slice() == other.as_slice() }
+}
+
+impl<const N: usize> PartialEq<[u8; N]> for SectionData {
+    #[inline]
+    fn eq(&self, other: &[u8; N]) -> bool { self.as_slice() == other }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct ObjSections {
     obj_kind: ObjKind,
     sections: Vec<ObjSection>,
@@ -45,6 +139,24 @@ pub struct ObjSections {
 
 pub type SectionIndex = u32;
 
+/// Serializes section data as a base64 string.
+#[cfg(feature = "serde")]
+mod base64_serde {
+    use base64::{engine::general_purpose::STANDARD, Engine};
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S>(data: &[u8], s: S) -> Result<S::Ok, S::Error>
+    where S: Serializer {
+        s.serialize_str(&STANDARD.encode(data))
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<super::SectionData, D::Error>
+    where D: Deserializer<'de> {
+        let str = String::deserialize(deserializer)?;
+        STANDARD.decode(str).map(Into::into).map_err(D::Error::custom)
+    }
+}
+
 impl ObjSections {
     pub fn new(obj_kind: ObjKind, sections: Vec<ObjSection>) -> Self { Self { obj_kind, sections } }
 
@@ -131,6 +243,16 @@ impl ObjSections {
         index as SectionIndex
     }
 
+    /// Inserts a section at the given index, shifting all following sections up.
+    pub fn insert(&mut self, index: SectionIndex, section: ObjSection) {
+        self.sections.insert(index as usize, section);
+    }
+
+    /// Removes the section at the given index, shifting all following sections down.
+    pub fn remove(&mut self, index: SectionIndex) -> ObjSection {
+        self.sections.remove(index as usize)
+    }
+
     pub fn all_splits(
         &self,
     ) -> impl DoubleEndedIterator<Item = (SectionIndex, &ObjSection, u32, &ObjSplit)> {
@@ -163,6 +285,12 @@ impl IndexMut<SectionIndex> for ObjSections {
 }
 
 impl ObjSection {
+    /// Whether this is a GCC unwind table. These are kept as opaque read-only data, but
+    /// can't be divided between units, as their entries aren't delimited by symbols.
+    pub fn is_unwind_table(&self) -> bool {
+        matches!(self.name.as_str(), ".eh_frame" | ".gcc_except_table")
+    }
+
     pub fn data_range(&self, start: u32, end: u32) -> Result<&[u8]> {
         if end == 0 {
             ensure!(
@@ -184,7 +312,7 @@ impl ObjSection {
                 self.address + self.size
             );
         }
-        if self.kind == ObjSectionKind::Bss {
+        if self.kind.is_bss() {
             return Ok(&[]);
         }
         let start = (start as u64 - self.address) as usize;
@@ -195,6 +323,16 @@ impl ObjSection {
         })
     }
 
+    /// Returns `len` bytes of data starting at `address`, or `None` if the range is outside
+    /// of the section data. BSS sections have no data.
+    pub fn data_at(&self, address: u64, len: usize) -> Option<&[u8]> {
+        if self.kind.is_bss() {
+            return None;
+        }
+        let offset = address.checked_sub(self.address)? as usize;
+        self.data.get(offset..offset.checked_add(len)?)
+    }
+
     #[inline]
     pub fn symbol_data(&self, symbol: &ObjSymbol) -> Result<&[u8]> {
         if symbol.size == 0 {
@@ -242,6 +380,61 @@ pub fn section_kind_for_section(section_name: &str) -> Result<ObjSectionKind> {
         }
         ".bss" | ".sbss" | ".sbss2" => ObjSectionKind::Bss,
         ".data" | ".sdata" => ObjSectionKind::Data,
+        ".comment" => ObjSectionKind::Note,
+        name if name.starts_with(".note.") => ObjSectionKind::Note,
         name => bail!("Unknown section {name}"),
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::obj::fixtures::{test_section, test_section_sized};
+
+    #[test]
+    fn test_kind_helpers() {
+        let mut kinds = [
+            ObjSectionKind::Note,
+            ObjSectionKind::Bss,
+            ObjSectionKind::Data,
+            ObjSectionKind::ReadOnlyData,
+            ObjSectionKind::Code,
+        ];
+        kinds.sort_by_key(|k| k.canonical_order());
+        assert_eq!(kinds, [
+            ObjSectionKind::Code,
+            ObjSectionKind::ReadOnlyData,
+            ObjSectionKind::Data,
+            ObjSectionKind::Bss,
+            ObjSectionKind::Note,
+        ]);
+        assert_eq!(ObjSectionKind::Code.flags(), elf::SHF_ALLOC | elf::SHF_EXECINSTR);
+        assert_eq!(ObjSectionKind::Bss.flags(), elf::SHF_ALLOC | elf::SHF_WRITE);
+        assert_eq!(ObjSectionKind::ReadOnlyData.flags(), elf::SHF_ALLOC);
+        assert_eq!(ObjSectionKind::Note.flags(), 0);
+        assert!(kinds.iter().filter(|k| k.is_bss()).eq([&ObjSectionKind::Bss]));
+        assert!(!ObjSectionKind::Note.loadable() && ObjSectionKind::Bss.loadable());
+    }
+
+    #[test]
+    fn test_data_at() {
+        let section =
+            test_section(".data", ObjSectionKind::Data, 0x80004000, vec![0, 1, 2, 3, 4, 5, 6, 7]);
+        assert_eq!(section.data_at(0x80004000, 8), Some([0, 1, 2, 3, 4, 5, 6, 7].as_slice()));
+        assert_eq!(section.data_at(0x80004004, 2), Some([4, 5].as_slice()));
+        assert_eq!(section.data_at(0x80004008, 0), Some([].as_slice()));
+
+        // Out of range
+        assert_eq!(section.data_at(0x80003FFC, 4), None);
+        assert_eq!(section.data_at(0x80004006, 4), None);
+        assert_eq!(section.data_at(0x80004010, 1), None);
+        assert_eq!(section.data_at(0x80004000, usize::MAX), None);
+    }
+
+    #[test]
+    fn test_data_at_bss() {
+        let section = test_section_sized(".bss", ObjSectionKind::Bss, 0x80004000, 8);
+        assert_eq!(section.data_at(0x80004000, 0), None);
+        assert_eq!(section.data_at(0x80004000, 4), None);
+    }
+}
@@ -2,6 +2,8 @@ use std::{cmp::max, collections::BTreeMap, ops::RangeBounds};
 
 use anyhow::{anyhow, Result};
 use itertools::Itertools;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 
 use crate::{
     obj::{ObjInfo, ObjSection, SectionIndex},
@@ -10,6 +12,7 @@ use crate::{
 
 /// Marks a split point within a section.
 #[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct ObjSplit {
     pub unit: String,
     pub end: u32,
@@ -49,7 +52,8 @@ impl ObjSplit {
 }
 
 /// Splits within a section.
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct ObjSplits {
     splits: BTreeMap<u32, Vec<ObjSplit>>,
 }
@@ -51,6 +51,8 @@ flags! {
         NoExport,
         /// Symbol does not contain any relocations
         NoReloc,
+        /// Symbol name was synthesized for a nameless symbol
+        Autogenerated,
     }
 }
 
@@ -104,6 +106,9 @@ impl ObjSymbolFlagSet {
     #[inline]
     pub fn is_no_reloc(&self) -> bool { self.0.contains(ObjSymbolFlags::NoReloc) }
 
+    #[inline]
+    pub fn is_autogenerated(&self) -> bool { self.0.contains(ObjSymbolFlags::Autogenerated) }
+
     #[inline]
     pub fn set_scope(&mut self, scope: ObjSymbolScope) {
         match scope {
@@ -162,9 +167,12 @@ pub enum ObjSymbolKind {
     Function,
     Object,
     Section,
+    /// Thread-local storage object (`STT_TLS`).
+    Tls,
 }
 
 #[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum ObjDataKind {
     #[default]
     Unknown,
@@ -183,6 +191,7 @@ pub enum ObjDataKind {
 }
 
 #[derive(Debug, Clone, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct ObjSymbol {
     pub name: String,
     pub demangled_name: Option<String>,
@@ -210,6 +219,40 @@ pub struct ObjSymbols {
     symbols_by_section: Vec<BTreeMap<u32, Vec<SymbolIndex>>>,
 }
 
+// Lookup tables are derived from the symbol list
+impl PartialEq for ObjSymbols {
+    fn eq(&self, other: &Self) -> bool {
+        self.obj_kind == other.obj_kind && self.symbols == other.symbols
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for ObjSymbols {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where S: serde::Serializer {
+        use serde::ser::SerializeStruct;
+        let mut s = serializer.serialize_struct("ObjSymbols", 2)?;
+        s.serialize_field("obj_kind", &self.obj_kind)?;
+        s.serialize_field("symbols", &self.symbols)?;
+        s.end()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for ObjSymbols {
+    fn deserialize<D>(deserializer: D) -> Result<ObjSymbols, D::Error>
+    where D: serde::Deserializer<'de> {
+        // Lookup tables are rebuilt from the symbol list
+        #[derive(Deserialize)]
+        struct ObjSymbolsData {
+            obj_kind: ObjKind,
+            symbols: Vec<ObjSymbol>,
+        }
+        let data = ObjSymbolsData::deserialize(deserializer)?;
+        Ok(ObjSymbols::new(data.obj_kind, data.symbols))
+    }
+}
+
 impl ObjSymbols {
     pub fn new(obj_kind: ObjKind, symbols: Vec<ObjSymbol>) -> Self {
         let mut symbols_by_address = BTreeMap::<u32, Vec<SymbolIndex>>::new();
@@ -586,6 +629,10 @@ impl Index<SymbolIndex> for ObjSymbols {
 }
 
 impl ObjSymbol {
+    /// The name to show to users: the demangled name if available, otherwise the raw name.
+    #[inline]
+    pub fn display_name(&self) -> &str { self.demangled_name.as_deref().unwrap_or(&self.name) }
+
     /// Whether this symbol can be referenced by the given relocation kind.
     pub fn referenced_by(&self, reloc_kind: ObjRelocKind) -> bool {
         if self.flags.is_relocation_ignore() || self.flags.is_stripped() {
@@ -602,8 +649,10 @@ impl ObjSymbol {
 
         match self.kind {
             ObjSymbolKind::Unknown => true,
-            ObjSymbolKind::Function => !matches!(reloc_kind, ObjRelocKind::PpcEmbSda21),
-            ObjSymbolKind::Object => {
+            ObjSymbolKind::Function => {
+                !matches!(reloc_kind, ObjRelocKind::PpcEmbSda21 | ObjRelocKind::PpcSdaRel16)
+            }
+            ObjSymbolKind::Object | ObjSymbolKind::Tls => {
                 // !matches!(reloc_kind, ObjRelocKind::PpcRel14 | ObjRelocKind::PpcRel24)
                 true // SADX has bugged relocations that jump from .text to .bss, how awful
             }
@@ -628,7 +677,7 @@ pub fn best_match_for_reloc(
     }
     symbols.sort_by_key(|&(_, symbol)| {
         let mut rank = match symbol.kind {
-            ObjSymbolKind::Function | ObjSymbolKind::Object => {
+            ObjSymbolKind::Function | ObjSymbolKind::Object | ObjSymbolKind::Tls => {
                 // HACK: These are generally not referenced directly, so reduce their rank
                 if matches!(
                     symbol.name.as_str(),
@@ -643,7 +692,11 @@ pub fn best_match_for_reloc(
                     ObjRelocKind::Absolute
                     | ObjRelocKind::PpcRel24
                     | ObjRelocKind::PpcRel14
-                    | ObjRelocKind::PpcEmbSda21 => 2,
+                    | ObjRelocKind::PpcRel14BrTaken
+                    | ObjRelocKind::PpcRel14BrnTaken
+                    | ObjRelocKind::PpcEmbSda21
+                    | ObjRelocKind::PpcSdaRel16
+                    | ObjRelocKind::Other(_) => 2,
                 }
             }
             // Label
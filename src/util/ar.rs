@@ -0,0 +1,98 @@
+use std::io::Read;
+
+use anyhow::{anyhow, Context, Result};
+use typed_path::Utf8NativePath;
+
+use crate::{obj::ObjInfo, util::elf::process_elf_bytes, vfs::open_file};
+
+/// Archive members containing the symbol index, rather than an object.
+const SYMBOL_TABLE_NAMES: [&str; 3] = ["/", "__.SYMDEF", "__.SYMDEF SORTED"];
+
+/// Loads every ELF object in a static (`.a`) archive, returning pairs of
+/// member name and object.
+pub fn process_archive(path: &Utf8NativePath) -> Result<Vec<(String, ObjInfo)>> {
+    let mut file = open_file(path, true)?;
+    process_archive_bytes(file.map()?).with_context(|| format!("Failed to process {}", path))
+}
+
+/// Loads every ELF object in an in-memory static archive.
+pub fn process_archive_bytes(data: &[u8]) -> Result<Vec<(String, ObjInfo)>> {
+    let mut archive = ar::Archive::new(data);
+    let mut out = vec![];
+    while let Some(entry) = archive.next_entry() {
+        let mut entry = entry.map_err(|e| anyhow!("Failed to read archive entry: {:?}", e))?;
+        let name = String::from_utf8_lossy(entry.header().identifier()).to_string();
+        if SYMBOL_TABLE_NAMES.contains(&name.as_str()) {
+            continue;
+        }
+        let mut member_data = vec![0u8; entry.header().size() as usize];
+        entry.read_exact(&mut member_data)?;
+        let obj = process_elf_bytes(&member_data, &name)
+            .with_context(|| format!("Failed to process archive member '{}'", name))?;
+        out.push((name, obj));
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use object::{
+        write::{Object as WriteObject, Symbol as WriteSymbol, SymbolSection},
+        Architecture, BinaryFormat, Endianness, SectionKind, SymbolFlags, SymbolKind, SymbolScope,
+    };
+
+    use super::*;
+
+    fn build_elf(symbol_name: &str) -> Vec<u8> {
+        let mut obj = WriteObject::new(BinaryFormat::Elf, Architecture::PowerPc, Endianness::Big);
+        let text = obj.add_section(vec![], b".text".to_vec(), SectionKind::Text);
+        obj.append_section_data(text, &[0x4E, 0x80, 0x00, 0x20], 4);
+        obj.add_symbol(WriteSymbol {
+            name: symbol_name.as_bytes().to_vec(),
+            value: 0,
+            size: 4,
+            kind: SymbolKind::Text,
+            scope: SymbolScope::Dynamic,
+            weak: false,
+            section: SymbolSection::Section(text),
+            flags: SymbolFlags::None,
+        });
+        obj.write().unwrap()
+    }
+
+    #[test]
+    fn test_process_archive() {
+        let members = [("a.o", "func_a"), ("b.o", "func_b")];
+        let identifiers = members.iter().map(|(name, _)| name.as_bytes().to_vec()).collect();
+        let symbol_table = members
+            .iter()
+            .map(|(name, symbol)| (name.as_bytes().to_vec(), vec![symbol.as_bytes().to_vec()]))
+            .collect::<BTreeMap<_, _>>();
+        let mut builder = ar::GnuBuilder::new_with_symbol_table(
+            vec![],
+            true,
+            identifiers,
+            ar::GnuSymbolTableFormat::Size32,
+            symbol_table,
+        )
+        .unwrap();
+        for (name, symbol) in members {
+            let data = build_elf(symbol);
+            let header = ar::Header::new(name.as_bytes().to_vec(), data.len() as u64);
+            builder.append(&header, data.as_slice()).unwrap();
+        }
+        let data = builder.into_inner().unwrap();
+
+        let objects = process_archive_bytes(&data).unwrap();
+        assert_eq!(objects.len(), 2);
+        for ((name, obj), (expected_name, symbol)) in objects.iter().zip(members) {
+            assert_eq!(name, expected_name);
+            assert_eq!(obj.symbols.by_name(symbol).unwrap().map(|(_, s)| s.size), Some(4));
+            // Members without a file symbol are named after the member
+            assert_eq!(obj.name, expected_name);
+            assert_eq!(obj.link_order.len(), 1);
+        }
+    }
+}
@@ -11,9 +11,9 @@ use ppc750cl::{Argument, Ins, InsIter, Opcode};
 use crate::{
     obj::{
         ObjDataKind, ObjInfo, ObjReloc, ObjRelocKind, ObjSection, ObjSectionKind, ObjSymbol,
-        ObjSymbolKind, SymbolIndex,
+        ObjSymbolKind, SectionIndex, SymbolIndex,
     },
-    util::nested::NestedVec,
+    util::{nested::NestedVec, split::split_obj},
 };
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
@@ -111,6 +111,7 @@ where W: Write + ?Sized {
                             target_symbol: symbol_idx,
                             addend: 0,
                             module: None,
+                            pair: None,
                         });
                     }
                 }
@@ -200,7 +201,10 @@ where W: Write + ?Sized {
 
             write_section_header(w, section, subsection, current_address, section_end)?;
             match section.kind {
-                ObjSectionKind::Code | ObjSectionKind::Data | ObjSectionKind::ReadOnlyData => {
+                ObjSectionKind::Code
+                | ObjSectionKind::Data
+                | ObjSectionKind::ReadOnlyData
+                | ObjSectionKind::Note => {
                     write_data(
                         w,
                         &symbols,
@@ -235,6 +239,104 @@ where W: Write + ?Sized {
     Ok(())
 }
 
+/// Writes a data or read-only data section as plain GNU assembler data directives.
+/// Each symbol boundary is labeled, relocations are written as `.4byte symbol+addend`,
+/// and NUL-terminated ASCII strings are written as `.asciz`.
+pub fn dump_data_section(obj: &ObjInfo, section_index: SectionIndex) -> Result<String> {
+    let section = obj
+        .sections
+        .get(section_index)
+        .ok_or_else(|| anyhow!("Invalid section index {}", section_index))?;
+    ensure!(
+        matches!(section.kind, ObjSectionKind::Data | ObjSectionKind::ReadOnlyData),
+        "Section {} is not a data section",
+        section.name
+    );
+    let symbols = obj.symbols.iter().map(|(_, s)| s.clone()).collect_vec();
+    let mut labels = BTreeMap::<u32, Vec<SymbolIndex>>::new();
+    for (symbol_index, symbol) in obj.symbols.for_section(section_index) {
+        if symbol.kind != ObjSymbolKind::Section {
+            labels.nested_push(symbol.address as u32, symbol_index);
+        }
+    }
+
+    let mut w = Vec::<u8>::new();
+    writeln!(w, ".section {}", section.name)?;
+    let start = section.address as u32;
+    let end = (section.address + section.size) as u32;
+    let mut current_address = start;
+    while current_address < end {
+        if let Some(symbol_indexes) = labels.get(&current_address) {
+            for &symbol_index in symbol_indexes {
+                write_symbol_name(&mut w, &symbols[symbol_index as usize].name)?;
+                writeln!(w, ":")?;
+            }
+        }
+        if let Some(reloc) = section.relocations.at(current_address) {
+            ensure!(
+                reloc.kind == ObjRelocKind::Absolute,
+                "Unsupported data relocation type {:?} @ {:#010X}",
+                reloc.kind,
+                current_address
+            );
+            write!(w, "\t.4byte ")?;
+            write_reloc_symbol(&mut w, &symbols, reloc)?;
+            writeln!(w)?;
+            current_address += 4;
+            continue;
+        }
+        let until = [
+            labels.range(current_address + 1..end).next().map(|(&addr, _)| addr),
+            section.relocations.range(current_address + 1..end).next().map(|(addr, _)| addr),
+        ]
+        .into_iter()
+        .flatten()
+        .min()
+        .unwrap_or(end);
+        let mut data = section
+            .data_at(current_address as u64, (until - current_address) as usize)
+            .ok_or_else(|| {
+                anyhow!("Missing data @ {} {:#010X}-{:#010X}", section.name, current_address, until)
+            })?;
+        // Write any leading strings, followed by the remaining data
+        while let Some(len) = ascii_string_len(data) {
+            write!(w, "\t.asciz \"")?;
+            write_escaped_string(&mut w, &data[..len])?;
+            writeln!(w, "\"")?;
+            data = &data[len + 1..];
+        }
+        write_data_chunk(&mut w, data, ObjDataKind::Unknown)?;
+        current_address = until;
+    }
+    Ok(String::from_utf8(w)?)
+}
+
+/// Writes a complete assembler file for a single unit in the object's link order.
+/// The object is split using its configured splits, and the unit's sections, symbols
+/// and relocations are emitted as with [`write_asm`].
+pub fn write_unit_asm(obj: &ObjInfo, unit_name: &str) -> Result<String> {
+    let unit_index = obj
+        .link_order
+        .iter()
+        .position(|unit| unit.name == unit_name)
+        .ok_or_else(|| anyhow!("Unit '{}' not in link order", unit_name))?;
+    let mut split_objs = split_obj(obj, None)?;
+    let unit_obj = split_objs.swap_remove(unit_index);
+    let mut w = Vec::<u8>::new();
+    write_asm(&mut w, &unit_obj).with_context(|| format!("Failed to write {}", unit_name))?;
+    Ok(String::from_utf8(w)?)
+}
+
+/// Returns the length of a non-empty, NUL-terminated printable ASCII string at
+/// the start of `data`, excluding the terminator.
+fn ascii_string_len(data: &[u8]) -> Option<usize> {
+    let len = data.iter().position(|&b| b == 0)?;
+    if len == 0 || !data[..len].iter().all(|&b| b.is_ascii_graphic() || b.is_ascii_whitespace()) {
+        return None;
+    }
+    Some(len)
+}
+
 fn write_code_chunk<W>(
     w: &mut W,
     symbols: &[ObjSymbol],
@@ -248,13 +350,36 @@ where
     W: Write + ?Sized,
 {
     for (addr, ins) in InsIter::new(data, address) {
-        let reloc = relocations.get(&addr);
+        let reloc = relocations.get(&addr).map(|reloc| ObjReloc {
+            addend: paired_addend(addr, reloc, relocations),
+            ..reloc.clone()
+        });
         let file_offset = section.file_offset + (addr as u64 - section.address);
-        write_ins(w, symbols, addr, ins, reloc, file_offset, section.virtual_address)?;
+        write_ins(w, symbols, addr, ins, reloc.as_ref(), file_offset, section.virtual_address)?;
     }
     Ok(())
 }
 
+/// An `@ha`/`@h` relocation may only know the upper half of its addend (e.g. from a `.rel`
+/// section). If its paired `@l` relocation resolves to the same upper half, use the `@l`
+/// addend instead, so that both halves refer to the same address.
+fn paired_addend(addr: u32, reloc: &ObjReloc, relocations: &BTreeMap<u32, ObjReloc>) -> i64 {
+    let high: fn(i64) -> i64 = match reloc.kind {
+        ObjRelocKind::PpcAddr16Ha => |addend| (addend + 0x8000) >> 16,
+        ObjRelocKind::PpcAddr16Hi => |addend| addend >> 16,
+        _ => return reloc.addend,
+    };
+    reloc
+        .pair
+        .and_then(|offset| relocations.get(&((addr as i64 + offset as i64) as u32)))
+        .filter(|lo| {
+            lo.kind == ObjRelocKind::PpcAddr16Lo
+                && lo.target_symbol == reloc.target_symbol
+                && high(lo.addend) == high(reloc.addend)
+        })
+        .map_or(reloc.addend, |lo| lo.addend)
+}
+
 fn write_ins<W>(
     w: &mut W,
     symbols: &[ObjSymbol],
@@ -282,12 +407,16 @@ where
         // Zero out relocations
         ins.code = match reloc.kind {
             ObjRelocKind::Absolute => 0,
-            ObjRelocKind::PpcEmbSda21 => ins.code & !0x1FFFFF,
+            ObjRelocKind::PpcEmbSda21 => ins.code & !sda21_reloc_mask(&ins),
             ObjRelocKind::PpcRel24 => ins.code & !0x3FFFFFC,
-            ObjRelocKind::PpcRel14 => ins.code & !0xFFFC,
-            ObjRelocKind::PpcAddr16Hi | ObjRelocKind::PpcAddr16Ha | ObjRelocKind::PpcAddr16Lo => {
-                ins.code & !0xFFFF
-            }
+            ObjRelocKind::PpcRel14
+            | ObjRelocKind::PpcRel14BrTaken
+            | ObjRelocKind::PpcRel14BrnTaken => ins.code & !0xFFFC,
+            ObjRelocKind::PpcAddr16Hi
+            | ObjRelocKind::PpcAddr16Ha
+            | ObjRelocKind::PpcAddr16Lo
+            | ObjRelocKind::PpcSdaRel16 => ins.code & !0xFFFF,
+            ObjRelocKind::Other(r_type) => bail!("Unsupported relocation type {}", r_type),
         };
     }
 
@@ -300,6 +429,8 @@ where
         let sins = ins.simplified();
         write!(w, "{}", sins.mnemonic)?;
 
+        // Only the first immediate or offset argument carries the relocation
+        let mut reloc = reloc;
         let mut writing_offset = false;
         for (i, arg) in sins.args_iter().enumerate() {
             if !writing_offset {
@@ -311,14 +442,14 @@ where
             }
             match arg {
                 Argument::Uimm(_) | Argument::Simm(_) | Argument::BranchDest(_) => {
-                    if let Some(reloc) = reloc {
+                    if let Some(reloc) = reloc.take() {
                         write_reloc(w, symbols, reloc)?;
                     } else {
                         write!(w, "{arg}")?;
                     }
                 }
                 Argument::Offset(_) => {
-                    if let Some(reloc) = reloc {
+                    if let Some(reloc) = reloc.take() {
                         write_reloc(w, symbols, reloc)?;
                     } else {
                         write!(w, "{arg}")?;
@@ -341,11 +472,24 @@ where
     Ok(())
 }
 
+/// The bits of an instruction patched by an SDA21 relocation. Paired-single loads
+/// and stores only have a 12-bit displacement, followed by the W and I fields.
+fn sda21_reloc_mask(ins: &Ins) -> u32 {
+    match ins.op {
+        Opcode::PsqL | Opcode::PsqLu | Opcode::PsqSt | Opcode::PsqStu => 0x1F0FFF,
+        _ => 0x1FFFFF,
+    }
+}
+
 fn write_reloc<W>(w: &mut W, symbols: &[ObjSymbol], reloc: &ObjReloc) -> Result<()>
 where W: Write + ?Sized {
     write_reloc_symbol(w, symbols, reloc)?;
     match reloc.kind {
-        ObjRelocKind::Absolute | ObjRelocKind::PpcRel24 | ObjRelocKind::PpcRel14 => {
+        ObjRelocKind::Absolute
+        | ObjRelocKind::PpcRel24
+        | ObjRelocKind::PpcRel14
+        | ObjRelocKind::PpcRel14BrTaken
+        | ObjRelocKind::PpcRel14BrnTaken => {
             // pass
         }
         ObjRelocKind::PpcAddr16Hi => {
@@ -360,6 +504,10 @@ where W: Write + ?Sized {
         ObjRelocKind::PpcEmbSda21 => {
             write!(w, "@sda21")?;
         }
+        ObjRelocKind::PpcSdaRel16 => {
+            write!(w, "@sdarel")?;
+        }
+        ObjRelocKind::Other(r_type) => bail!("Unsupported relocation type {}", r_type),
     }
     Ok(())
 }
@@ -382,7 +530,7 @@ where
 
     let symbol_kind = match symbol.kind {
         ObjSymbolKind::Function => "fn",
-        ObjSymbolKind::Object => "obj",
+        ObjSymbolKind::Object | ObjSymbolKind::Tls => "obj",
         ObjSymbolKind::Unknown => "sym",
         ObjSymbolKind::Section => bail!("Attempted to write section symbol: {symbol:?}"),
     };
@@ -521,9 +669,10 @@ where
         let symbol_kind = if current_symbol_kind == ObjSymbolKind::Unknown {
             match section.kind {
                 ObjSectionKind::Code => ObjSymbolKind::Function,
-                ObjSectionKind::Data | ObjSectionKind::ReadOnlyData | ObjSectionKind::Bss => {
-                    ObjSymbolKind::Object
-                }
+                ObjSectionKind::Data
+                | ObjSectionKind::ReadOnlyData
+                | ObjSectionKind::Bss
+                | ObjSectionKind::Note => ObjSymbolKind::Object,
             }
         } else {
             current_symbol_kind
@@ -532,7 +681,7 @@ where
             if current_address == reloc_addr {
                 reloc = reloc_iter.next();
                 match symbol_kind {
-                    ObjSymbolKind::Object => {
+                    ObjSymbolKind::Object | ObjSymbolKind::Tls => {
                         current_address =
                             write_data_reloc(w, symbols, entries, reloc_addr, r, section_entries)?;
                         continue;
@@ -651,7 +800,14 @@ where W: Write + ?Sized {
     } else {
         write!(w, "\t.ascii \"")?;
     }
-    for &b in &data[..data.len() - if terminated { 1 } else { 0 }] {
+    write_escaped_string(w, &data[..data.len() - if terminated { 1 } else { 0 }])?;
+    writeln!(w, "\"")?;
+    Ok(())
+}
+
+fn write_escaped_string<W>(w: &mut W, data: &[u8]) -> Result<()>
+where W: Write + ?Sized {
+    for &b in data {
         match b as char {
             '\x08' => write!(w, "\\b")?,
             '\x09' => write!(w, "\\t")?,
@@ -664,7 +820,6 @@ where W: Write + ?Sized {
             _ => write!(w, "\\{:03o}", b)?,
         }
     }
-    writeln!(w, "\"")?;
     Ok(())
 }
 
@@ -990,3 +1145,168 @@ where W: Write + ?Sized {
 fn is_illegal_instruction(code: u32) -> bool {
     matches!(code, 0x43000000 /* bc 24, lt, 0x0 */ | 0xB8030000 /* lmw r0, 0(r3) */)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::obj::{
+        fixtures::{test_obj, test_reloc, test_section, test_symbol},
+        ObjKind, ObjSplit, ObjUnit,
+    };
+
+    #[test]
+    fn test_dump_data_section_pointer_table() {
+        let mut obj = test_obj(
+            ObjKind::Relocatable,
+            vec![
+                ObjSymbol { kind: ObjSymbolKind::Object, ..test_symbol("func", 0, 0, 8) },
+                ObjSymbol { kind: ObjSymbolKind::Object, ..test_symbol("table", 1, 0, 8) },
+            ],
+            vec![
+                test_section(".text", ObjSectionKind::Code, 0, vec![0; 8]),
+                test_section(".data", ObjSectionKind::Data, 0, vec![0; 8]),
+            ],
+        );
+        let reloc = test_reloc(ObjRelocKind::Absolute, 0);
+        obj.sections[1].relocations.insert(0, reloc.clone()).unwrap();
+        obj.sections[1].relocations.insert(4, ObjReloc { addend: 4, ..reloc }).unwrap();
+
+        let out = dump_data_section(&obj, 1).unwrap();
+        assert_eq!(out, ".section .data\ntable:\n\t.4byte func\n\t.4byte func+0x4\n");
+        assert!(dump_data_section(&obj, 0).is_err());
+    }
+
+    #[test]
+    fn test_dump_data_section_strings() {
+        let mut data = b"Hello\0wor\"ld\0".to_vec();
+        data.extend_from_slice(&[0, 0, 0, 0x12, 0x34, 0x56, 0x78]);
+        let obj = test_obj(
+            ObjKind::Relocatable,
+            vec![
+                ObjSymbol { kind: ObjSymbolKind::Object, ..test_symbol("str1", 0, 0, 6) },
+                ObjSymbol { kind: ObjSymbolKind::Object, ..test_symbol("str2", 0, 6, 7) },
+                ObjSymbol { kind: ObjSymbolKind::Object, ..test_symbol("value", 0, 0x10, 4) },
+            ],
+            vec![test_section(".rodata", ObjSectionKind::ReadOnlyData, 0, data)],
+        );
+
+        let out = dump_data_section(&obj, 0).unwrap();
+        assert_eq!(
+            out,
+            ".section .rodata\nstr1:\n\t.asciz \"Hello\"\nstr2:\n\t.asciz \"wor\\\"ld\"\n\t.byte \
+             0x00, 0x00, 0x00\nvalue:\n\t.4byte 0x12345678\n"
+        );
+    }
+
+    #[test]
+    fn test_write_unit_asm() {
+        let mut obj = test_obj(
+            ObjKind::Executable,
+            vec![
+                ObjSymbol {
+                    kind: ObjSymbolKind::Function,
+                    ..test_symbol("func", 0, 0x80003100, 8)
+                },
+                ObjSymbol { kind: ObjSymbolKind::Object, ..test_symbol("value", 1, 0x80004000, 4) },
+            ],
+            vec![
+                test_section(".text", ObjSectionKind::Code, 0x80003100, vec![
+                    0x60, 0x00, 0x00, 0x00, // nop
+                    0x4E, 0x80, 0x00, 0x20, // blr
+                ]),
+                test_section(".data", ObjSectionKind::Data, 0x80004000, vec![0; 4]),
+            ],
+        );
+        obj.link_order.push(ObjUnit {
+            name: "main.c".to_string(),
+            autogenerated: false,
+            comment_version: None,
+            order: None,
+        });
+        for (section_index, end) in [(0, 0x80003108), (1, 0x80004004)] {
+            let section = &mut obj.sections[section_index];
+            section.splits.push(section.address as u32, ObjSplit {
+                unit: "main.c".to_string(),
+                end,
+                align: None,
+                common: false,
+                autogenerated: false,
+                skip: false,
+                rename: None,
+            });
+        }
+        let reloc = test_reloc(ObjRelocKind::Absolute, 0);
+        obj.sections[1].relocations.insert(0x80004000, reloc).unwrap();
+
+        let out = write_unit_asm(&obj, "main.c").unwrap();
+        assert!(out.starts_with(".include \"macros.inc\"\n.file \"main.c\"\n"));
+        let text = out.find("\n.text\n").unwrap();
+        let data = out.find("\n.data\n").unwrap();
+        assert!(text < data);
+        assert!(out[text..data].contains(".fn func, global\n"));
+        assert!(out[text..data].contains("blr"));
+        assert!(out[data..].contains(".obj value, global\n"));
+        assert!(out[data..].contains(".4byte func\n"));
+        assert!(write_unit_asm(&obj, "missing.c").is_err());
+    }
+
+    #[test]
+    fn test_write_code_chunk_paired_addend() {
+        let symbols = vec![ObjSymbol {
+            kind: ObjSymbolKind::Object,
+            ..test_symbol("value", 0, 0x80004000, 8)
+        }];
+        let section = test_section(".text", ObjSectionKind::Code, 0x80003100, vec![
+            0x3C, 0x60, 0x00, 0x00, // lis r3, 0
+            0x38, 0x63, 0x00, 0x00, // addi r3, r3, 0
+            0x3C, 0x80, 0x00, 0x00, // lis r4, 0
+        ]);
+        let reloc = |kind: ObjRelocKind, addend: i64, pair: Option<i32>| ObjReloc {
+            addend,
+            pair,
+            ..test_reloc(kind, 0)
+        };
+        let relocations = BTreeMap::from([
+            // Only the upper half of the @ha addend is known
+            (0x80003100, reloc(ObjRelocKind::PpcAddr16Ha, 0x10000, Some(4))),
+            (0x80003104, reloc(ObjRelocKind::PpcAddr16Lo, 0x10004, Some(-4))),
+            (0x80003108, reloc(ObjRelocKind::PpcAddr16Ha, 0x10000, None)),
+        ]);
+        let mut out = Vec::<u8>::new();
+        write_code_chunk(
+            &mut out,
+            &symbols,
+            &BTreeMap::new(),
+            &relocations,
+            &section,
+            0x80003100,
+            &section.data,
+        )
+        .unwrap();
+        let out = String::from_utf8(out).unwrap();
+        let lines = out.lines().map(|l| l.split_once('\t').unwrap().1).collect::<Vec<_>>();
+        assert_eq!(lines, [
+            "lis r3, value+0x10004@ha",
+            "addi r3, r3, value+0x10004@l",
+            "lis r4, value+0x10000@ha",
+        ]);
+    }
+
+    #[test]
+    fn test_write_ins_psq_sda21() {
+        let symbols = vec![ObjSymbol {
+            kind: ObjSymbolKind::Object,
+            ..test_symbol("value", 0, 0x80004000, 8)
+        }];
+        let reloc = test_reloc(ObjRelocKind::PpcEmbSda21, 0);
+        // psq_l f1, 0x10(r2), 1, qr2
+        let ins = Ins::new(0xE022A010);
+        assert_eq!(ins.op, Opcode::PsqL);
+        let mut out = Vec::<u8>::new();
+        write_ins(&mut out, &symbols, 0x80003100, ins, Some(&reloc), 0, None).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        let (_, ins_str) = out.split_once('\t').unwrap();
+        assert!(ins_str.starts_with("psq_l f1, value@sda21(r0), 1, "), "{}", ins_str);
+        assert_eq!(ins_str.matches("@sda21").count(), 1);
+    }
+}
@@ -28,7 +28,7 @@ pub fn bin2c(symbol: &ObjSymbol, section: &ObjSection, data: &[u8]) -> String {
         output.push_str("const ");
     }
     output.push_str("unsigned char ");
-    output.push_str(symbol.demangled_name.as_deref().unwrap_or(symbol.name.as_str()));
+    output.push_str(symbol.display_name());
     output.push_str(&format!("[] ATTRIBUTE_ALIGN({}) = {{", symbol.align.unwrap_or(4)));
     for (i, byte) in data.iter().enumerate() {
         if i % 16 == 0 {
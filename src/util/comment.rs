@@ -36,6 +36,9 @@ const MAGIC: &[u8] = "CodeWarrior".as_bytes();
 const HEADER_SIZE: u8 = 0x2C;
 const PADDING: &[u8] = &[0u8; 0x16];
 
+/// Whether `.comment` section data begins with a Metrowerks header.
+pub fn is_mw_comment(data: &[u8]) -> bool { data.starts_with(MAGIC) }
+
 impl FromReader for MWComment {
     type Args = ();
 
@@ -259,7 +262,7 @@ impl CommentSym {
                     match symbol.kind {
                         ObjSymbolKind::Unknown => 0,
                         ObjSymbolKind::Function => 4,
-                        ObjSymbolKind::Object => {
+                        ObjSymbolKind::Object | ObjSymbolKind::Tls => {
                             if symbol.address & 3 == 0 {
                                 4
                             } else {
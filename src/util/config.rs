@@ -52,21 +52,30 @@ pub fn apply_symbols_file(
     Ok(if fs::metadata(path).is_ok_and(|m| m.is_file()) {
         let mut file = open_file(path, true)?;
         let cached = FileReadInfo::new(file.as_mut())?;
-        for result in file.lines() {
-            let line = match result {
-                Ok(line) => line,
-                Err(e) => bail!("Failed to process symbols file: {e:?}"),
-            };
-            if let Some(symbol) = parse_symbol_line(&line, obj)? {
-                obj.add_symbol(symbol, true)?;
-            }
-        }
+        apply_symbols(file.as_mut(), obj)?;
         Some(cached)
     } else {
         None
     })
 }
 
+/// Applies `name = [section:]address; // attributes` lines to the object, replacing
+/// auto-generated symbols at the same address. Symbols at addresses outside of any
+/// section are skipped with a warning.
+pub fn apply_symbols<R>(r: &mut R, obj: &mut ObjInfo) -> Result<()>
+where R: BufRead + ?Sized {
+    for result in r.lines() {
+        let line = match result {
+            Ok(line) => line,
+            Err(e) => bail!("Failed to process symbols file: {e:?}"),
+        };
+        if let Some(symbol) = parse_symbol_line(&line, obj)? {
+            obj.add_symbol(symbol, true)?;
+        }
+    }
+    Ok(())
+}
+
 pub fn parse_symbol_line(line: &str, obj: &mut ObjInfo) -> Result<Option<ObjSymbol>> {
     static SYMBOL_LINE: Lazy<Regex> = Lazy::new(|| {
         Regex::new(
@@ -79,19 +88,32 @@ pub fn parse_symbol_line(line: &str, obj: &mut ObjInfo) -> Result<Option<ObjSymb
     if let Some(captures) = SYMBOL_LINE.captures(line) {
         let name = captures["name"].to_string();
         let addr = parse_u32(&captures["addr"])?;
-        let section_name = captures["section"].to_string();
-        let section = if section_name == "ABS" {
+        let section_name = captures.name("section").map(|m| m.as_str());
+        let known_section = match section_name {
+            Some(section_name) => obj.sections.by_name(section_name)?.map(|(index, _)| index),
+            None => None,
+        };
+        let section = if section_name == Some("ABS") {
             None
-        } else if let Some((section_index, _)) = obj.sections.by_name(&section_name)? {
+        } else if let Some(section_index) = known_section {
             Some(section_index)
         } else if obj.kind == ObjKind::Executable {
-            let (section_index, section) = obj.sections.at_address_mut(addr)?;
-            if !section.section_known {
-                section.rename(section_name)?;
+            let Some((section_index, section)) =
+                obj.sections.iter_mut().find(|(_, s)| s.contains(addr))
+            else {
+                warn!("Skipping symbol {} @ {:#010X}: not within any section", name, addr);
+                return Ok(None);
+            };
+            if let Some(section_name) = section_name {
+                if !section.section_known {
+                    section.rename(section_name.to_string())?;
+                }
             }
             Some(section_index)
-        } else {
+        } else if let Some(section_name) = section_name {
             bail!("Section {} not found", section_name)
+        } else {
+            bail!("Symbol {} requires a section in relocatable objects", name)
         };
         let demangled_name = demangle(&name, &DemangleOptions::default());
         let mut symbol =
@@ -100,8 +122,8 @@ pub fn parse_symbol_line(line: &str, obj: &mut ObjInfo) -> Result<Option<ObjSymb
         if symbol.name.starts_with("..") {
             symbol.flags.0 |= ObjSymbolFlags::Exported;
         }
-        let attrs = captures["attrs"].split(' ');
-        for attr in attrs {
+        let attrs = captures.name("attrs").map_or("", |m| m.as_str());
+        for attr in attrs.split_whitespace() {
             if let Some((name, value)) = attr.split_once(':') {
                 match name {
                     "type" => {
@@ -189,7 +211,8 @@ pub fn is_skip_symbol(symbol: &ObjSymbol) -> bool {
 }
 
 pub fn is_auto_symbol(symbol: &ObjSymbol) -> bool {
-    symbol.name.starts_with("lbl_")
+    symbol.flags.is_autogenerated()
+        || symbol.name.starts_with("lbl_")
         || symbol.name.starts_with("fn_")
         || symbol.name.starts_with("jumptable_")
         || symbol.name.starts_with("gap_")
@@ -315,6 +338,7 @@ fn symbol_kind_to_str(kind: ObjSymbolKind) -> &'static str {
         ObjSymbolKind::Function => "function",
         ObjSymbolKind::Object => "object",
         ObjSymbolKind::Section => "section",
+        ObjSymbolKind::Tls => "tls",
     }
 }
 
@@ -344,6 +368,7 @@ fn symbol_kind_from_str(s: &str) -> Option<ObjSymbolKind> {
         "function" => Some(ObjSymbolKind::Function),
         "object" => Some(ObjSymbolKind::Object),
         "section" => Some(ObjSymbolKind::Section),
+        "tls" => Some(ObjSymbolKind::Tls),
         _ => None,
     }
 }
@@ -398,6 +423,7 @@ fn section_kind_from_str(s: &str) -> Option<ObjSectionKind> {
         "data" => Some(ObjSectionKind::Data),
         "rodata" => Some(ObjSectionKind::ReadOnlyData),
         "bss" => Some(ObjSectionKind::Bss),
+        "note" => Some(ObjSectionKind::Note),
         _ => None,
     }
 }
@@ -409,6 +435,7 @@ fn section_kind_to_str(kind: ObjSectionKind) -> &'static str {
         ObjSectionKind::Data => "data",
         ObjSectionKind::ReadOnlyData => "rodata",
         ObjSectionKind::Bss => "bss",
+        ObjSectionKind::Note => "note",
     }
 }
 
@@ -898,3 +925,104 @@ impl serde::Serialize for SectionAddressRef {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+    use crate::obj::{
+        fixtures::{test_obj, test_section_sized},
+        ObjSection,
+    };
+
+    /// Sections named by the config being applied.
+    fn unknown_sections() -> Vec<ObjSection> {
+        [
+            (".section0", ObjSectionKind::Code, 0x80003100),
+            (".section1", ObjSectionKind::Data, 0x80004000),
+        ]
+        .map(|(name, kind, address)| ObjSection {
+            section_known: false,
+            ..test_section_sized(name, kind, address, 0x100)
+        })
+        .into()
+    }
+
+    #[test]
+    fn test_apply_symbols() {
+        let mut obj = test_obj(
+            ObjKind::Executable,
+            vec![ObjSymbol {
+                name: "fn_80003100".to_string(),
+                address: 0x80003100,
+                section: Some(0),
+                kind: ObjSymbolKind::Function,
+                ..Default::default()
+            }],
+            unknown_sections(),
+        );
+        let symbols = "\
+            main = .text:0x80003100; // type:function size:0x10\n\
+            // comment\n\
+            value = 0x80004008; // type:object size:0x4 scope:local\n\
+            missing = 0x90000000;\n";
+        apply_symbols(&mut Cursor::new(symbols), &mut obj).unwrap();
+
+        assert_eq!(obj.symbols.count(), 2);
+        assert_eq!(obj.sections[0].name, ".text");
+        let (_, main) = obj.symbols.by_name("main").unwrap().unwrap();
+        assert_eq!((main.section, main.address, main.size), (Some(0), 0x80003100, 0x10));
+        assert!(obj.symbols.by_name("fn_80003100").unwrap().is_none());
+        let (_, value) = obj.symbols.by_name("value").unwrap().unwrap();
+        assert_eq!((value.section, value.kind, value.size), (Some(1), ObjSymbolKind::Object, 4));
+        assert!(value.flags.is_local());
+        assert!(obj.symbols.by_name("missing").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_splits_round_trip() {
+        let new_obj = || test_obj(ObjKind::Executable, vec![], unknown_sections());
+        let split = |unit: &str, end: u32| ObjSplit {
+            unit: unit.to_string(),
+            end,
+            align: None,
+            common: false,
+            autogenerated: false,
+            skip: false,
+            rename: None,
+        };
+        let mut obj = new_obj();
+        obj.sections[0].rename(".text".to_string()).unwrap();
+        obj.sections[1].rename(".data".to_string()).unwrap();
+        for name in ["a.c", "b.c"] {
+            obj.link_order.push(ObjUnit {
+                name: name.to_string(),
+                autogenerated: false,
+                comment_version: None,
+                order: None,
+            });
+        }
+        obj.sections[0].splits.push(0x80003100, split("a.c", 0x80003180));
+        obj.sections[0].splits.push(0x80003180, split("b.c", 0x80003200));
+        obj.sections[1].splits.push(0x80004000, ObjSplit {
+            rename: Some(".data$1".to_string()),
+            ..split("a.c", 0x80004010)
+        });
+
+        let mut out = Vec::new();
+        write_splits(&mut out, &obj, true).unwrap();
+        let mut read_obj = new_obj();
+        apply_splits(&mut Cursor::new(&out), &mut read_obj).unwrap();
+
+        assert_eq!(read_obj.link_order, obj.link_order);
+        for (section_index, section) in obj.sections.iter() {
+            let read_section = &read_obj.sections[section_index];
+            assert_eq!((&read_section.name, read_section.kind), (&section.name, section.kind));
+            assert_eq!(
+                read_section.splits.iter().collect::<Vec<_>>(),
+                section.splits.iter().collect::<Vec<_>>()
+            );
+        }
+    }
+}
@@ -19,7 +19,7 @@ use objdiff_core::{
 };
 use object::RelocationFlags;
 
-use crate::obj::{ObjInfo, ObjReloc, ObjSection, ObjSymbol};
+use crate::obj::{ObjInfo, ObjReloc, ObjRelocKind, ObjSection, ObjSymbol};
 
 /// Processes code for a PPC function using objdiff-core.
 /// Returns [ProcessCodeResult] for other objdiff-core functions to accept.
@@ -106,6 +106,84 @@ pub fn calc_diff_ranges(
     ranges
 }
 
+/// A range of differing bytes, as offsets from the start of the section.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DiffSpan {
+    pub start: u32,
+    pub end: u32,
+}
+
+/// Compares the data of two sections, returning the byte ranges that differ.
+/// Bits patched by equivalent relocations (same offset, kind and addend) are ignored, since
+/// their values depend on final addresses. Relocations present in only one section, or that
+/// differ in kind or addend, cause the relocated word to be reported as a difference.
+pub fn diff_sections(a: &ObjSection, b: &ObjSection) -> Vec<DiffSpan> {
+    let len = a.data.len().max(b.data.len());
+    let mut differs = vec![false; len];
+    for (offset, byte) in differs.iter_mut().enumerate() {
+        *byte = a.data.get(offset) != b.data.get(offset);
+    }
+
+    // Gather relocations by section offset, so that sections at different addresses compare
+    let a_relocs = a.relocations.iter().map(|(addr, r)| ((addr as u64 - a.address) as usize, r));
+    let b_relocs = b.relocations.iter().map(|(addr, r)| ((addr as u64 - b.address) as usize, r));
+    for item in a_relocs.merge_join_by(b_relocs, |(a, _), (b, _)| a.cmp(b)) {
+        let (offset, mask) = match item {
+            itertools::EitherOrBoth::Both((offset, a_reloc), (_, b_reloc))
+                if a_reloc.kind == b_reloc.kind && a_reloc.addend == b_reloc.addend =>
+            {
+                (offset, reloc_patch_mask(a_reloc.kind))
+            }
+            item => {
+                let (offset, _) = item.reduce(|a, _| a);
+                (offset, 0)
+            }
+        };
+        for (i, mask_byte) in mask.to_be_bytes().into_iter().enumerate() {
+            let Some(byte_differs) = differs.get_mut(offset + i) else {
+                break;
+            };
+            if mask_byte == 0xFF {
+                // Fully patched by the relocation
+                *byte_differs = false;
+            } else if mask_byte == 0 {
+                // Mismatched relocation
+                *byte_differs = true;
+            } else {
+                let a_byte = a.data.get(offset + i).copied().unwrap_or_default();
+                let b_byte = b.data.get(offset + i).copied().unwrap_or_default();
+                *byte_differs = (a_byte ^ b_byte) & !mask_byte != 0;
+            }
+        }
+    }
+
+    let mut spans = Vec::<DiffSpan>::new();
+    for (offset, _) in differs.iter().enumerate().filter(|(_, &d)| d) {
+        let offset = offset as u32;
+        match spans.last_mut() {
+            Some(span) if span.end == offset => span.end += 1,
+            _ => spans.push(DiffSpan { start: offset, end: offset + 1 }),
+        }
+    }
+    spans
+}
+
+/// The bits of the relocated word patched by a relocation.
+fn reloc_patch_mask(kind: ObjRelocKind) -> u32 {
+    match kind {
+        ObjRelocKind::Absolute | ObjRelocKind::Other(_) => 0xFFFFFFFF,
+        ObjRelocKind::PpcAddr16Hi
+        | ObjRelocKind::PpcAddr16Ha
+        | ObjRelocKind::PpcAddr16Lo
+        | ObjRelocKind::PpcSdaRel16 => 0x0000FFFF,
+        ObjRelocKind::PpcRel24 => 0x03FFFFFC,
+        ObjRelocKind::PpcRel14 | ObjRelocKind::PpcRel14BrTaken | ObjRelocKind::PpcRel14BrnTaken => {
+            0x0000FFFC
+        }
+        ObjRelocKind::PpcEmbSda21 => 0x001FFFFF,
+    }
+}
+
 pub fn print_diff(
     left: &ObjSymbolDiff,
     right: &ObjSymbolDiff,
@@ -232,8 +310,7 @@ fn print_line(ins_diff: &ObjInsDiff, base_addr: u64) -> Vec<Span> {
                 }
             }
             DiffText::Symbol(sym) => {
-                let name = sym.demangled_name.as_ref().unwrap_or(&sym.name);
-                label_text = name.clone();
+                label_text = sym.display_name().to_string();
                 base_color = Color::White;
             }
             DiffText::Spacing(n) => {
@@ -310,6 +387,10 @@ fn to_objdiff_symbol(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::obj::{
+        fixtures::{test_reloc, test_section},
+        ObjSectionKind,
+    };
 
     macro_rules! ins_diff {
         ($kind:expr) => {
@@ -388,4 +469,44 @@ mod tests {
         ];
         assert_eq!(calc_diff_ranges(&diff, &diff, 3), vec![0..7, 9..15]);
     }
+
+    fn text_with_relocs(data: Vec<u8>, relocs: &[(u32, ObjRelocKind, i64)]) -> ObjSection {
+        let mut section = test_section(".text", ObjSectionKind::Code, 0x80003100, data);
+        for &(addr, kind, addend) in relocs {
+            section.relocations.insert(addr, ObjReloc { addend, ..test_reloc(kind, 0) }).unwrap();
+        }
+        section
+    }
+
+    #[test]
+    fn test_diff_sections() {
+        let relocs =
+            [(0x80003100, ObjRelocKind::PpcRel24, 0), (0x80003104, ObjRelocKind::Absolute, 4)];
+        let a = text_with_relocs(
+            vec![0x48, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00, 0x60, 0x00, 0x00, 0x00],
+            &relocs,
+        );
+        let b = text_with_relocs(
+            vec![0x4B, 0xFF, 0xFF, 0xF1, 0x80, 0x00, 0x31, 0x04, 0x60, 0x00, 0x00, 0x00],
+            &relocs,
+        );
+        assert_eq!(diff_sections(&a, &b), vec![]);
+
+        // Real change outside of relocations
+        let mut c = b.clone();
+        c.data[9] = 0x01;
+        assert_eq!(diff_sections(&a, &c), vec![DiffSpan { start: 9, end: 10 }]);
+
+        // Non-relocated bits of a relocated word
+        let mut c = b.clone();
+        c.data[0] = 0x40;
+        assert_eq!(diff_sections(&a, &c), vec![DiffSpan { start: 0, end: 1 }]);
+
+        // Mismatched relocation addend
+        let c = text_with_relocs(b.data.to_vec(), &[
+            relocs[0],
+            (0x80003104, ObjRelocKind::Absolute, 8),
+        ]);
+        assert_eq!(diff_sections(&a, &c), vec![DiffSpan { start: 4, end: 8 }]);
+    }
 }
@@ -0,0 +1,108 @@
+use std::io::{Read, Seek, SeekFrom};
+
+use anyhow::{ensure, Context, Result};
+use typed_path::Utf8NativePath;
+
+use crate::{
+    util::{
+        dol::DolHeader,
+        reader::{Endian, FromReader},
+    },
+    vfs::open_file,
+};
+
+const GCM_MAGIC: u32 = 0xC2339F3D;
+const GCM_MAGIC_OFFSET: u64 = 0x1C;
+/// Offset of the main.dol file offset in the disc header.
+const GCM_DOL_OFFSET: u64 = 0x420;
+const TGC_MAGIC: u32 = 0xAE0F38A2;
+/// Offset of the TGC header size, which is where the embedded GCM begins.
+const TGC_HEADER_SIZE_OFFSET: u64 = 0x8;
+/// Offset of the DOL's file offset in the TGC header.
+const TGC_DOL_OFFSET: u64 = 0x1C;
+
+/// Extracts `main.dol` from an uncompressed GameCube disc image (GCM/ISO), or a TGC
+/// containing one. Wii and compressed disc images are not supported; use the VFS for those.
+pub fn extract_dol(iso_path: &Utf8NativePath) -> Result<Vec<u8>> {
+    let mut file = open_file(iso_path, false)?;
+    read_gcm_dol(file.as_mut()).with_context(|| format!("Failed to extract DOL from {}", iso_path))
+}
+
+pub fn read_gcm_dol<R>(reader: &mut R) -> Result<Vec<u8>>
+where R: Read + Seek + ?Sized {
+    // A TGC wraps a GCM after its own header. The embedded disc header's DOL offset is a
+    // virtual offset, so the TGC header's DOL offset (relative to the TGC file) is used instead.
+    reader.seek(SeekFrom::Start(0))?;
+    let tgc = if u32::from_reader(reader, Endian::Big)? == TGC_MAGIC {
+        reader.seek(SeekFrom::Start(TGC_HEADER_SIZE_OFFSET))?;
+        let header_size = u32::from_reader(reader, Endian::Big)? as u64;
+        reader.seek(SeekFrom::Start(TGC_DOL_OFFSET))?;
+        Some((header_size, u32::from_reader(reader, Endian::Big)? as u64))
+    } else {
+        None
+    };
+    let base = tgc.map_or(0, |(header_size, _)| header_size);
+
+    reader.seek(SeekFrom::Start(base + GCM_MAGIC_OFFSET))?;
+    let magic = u32::from_reader(reader, Endian::Big)?;
+    ensure!(magic == GCM_MAGIC, "Not a GameCube disc image (magic {:#010X})", magic);
+    let dol_offset = match tgc {
+        Some((_, dol_offset)) => dol_offset,
+        None => {
+            reader.seek(SeekFrom::Start(GCM_DOL_OFFSET))?;
+            u32::from_reader(reader, Endian::Big)? as u64
+        }
+    };
+
+    // The DOL size isn't stored in the disc header, so calculate it from the section table
+    reader.seek(SeekFrom::Start(dol_offset))?;
+    let header = DolHeader::from_reader(reader, Endian::Big).context("Reading DOL header")?;
+    let dol_size = header
+        .text_offs
+        .iter()
+        .zip(&header.text_sizes)
+        .chain(header.data_offs.iter().zip(&header.data_sizes))
+        .map(|(&offset, &size)| offset as u64 + size as u64)
+        .fold(DolHeader::STATIC_SIZE as u64, u64::max);
+
+    let mut data = vec![0u8; dol_size as usize];
+    reader.seek(SeekFrom::Start(dol_offset))?;
+    reader.read_exact(&mut data).context("DOL extends past the end of the disc image")?;
+    Ok(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+
+    #[test]
+    fn test_read_gcm_dol() {
+        let mut disc = vec![0u8; 0x1200];
+        disc[0x1C..0x20].copy_from_slice(&GCM_MAGIC.to_be_bytes());
+        disc[0x420..0x424].copy_from_slice(&0x1000u32.to_be_bytes());
+        // DOL header: text section 0 at file offset 0x100, size 0x20
+        disc[0x1000..0x1004].copy_from_slice(&0x100u32.to_be_bytes());
+        disc[0x1090..0x1094].copy_from_slice(&0x20u32.to_be_bytes());
+        disc[0x1100..0x1120].fill(0x60);
+
+        let dol = read_gcm_dol(&mut Cursor::new(&disc)).unwrap();
+        assert_eq!(dol, disc[0x1000..0x1120]);
+
+        // Wrapped in a TGC, the DOL is located by the TGC header rather than the
+        // embedded disc header, which holds a virtual offset
+        let mut tgc = vec![0u8; 0x8000];
+        tgc[0..4].copy_from_slice(&TGC_MAGIC.to_be_bytes());
+        tgc[8..12].copy_from_slice(&0x8000u32.to_be_bytes());
+        tgc[0x1C..0x20].copy_from_slice(&0x9000u32.to_be_bytes());
+        tgc.extend_from_slice(&disc);
+        tgc[0x8420..0x8424].copy_from_slice(&0x1E000000u32.to_be_bytes());
+        assert_eq!(read_gcm_dol(&mut Cursor::new(&tgc)).unwrap(), dol);
+
+        disc.truncate(0x1110);
+        assert!(read_gcm_dol(&mut Cursor::new(&disc)).is_err());
+        disc[0x1C] = 0;
+        assert!(read_gcm_dol(&mut Cursor::new(&disc)).is_err());
+    }
+}
@@ -179,6 +179,44 @@ fn read_u32(buf: &[u8], dol: &dyn DolLike, addr: u32) -> Result<u32> {
     Ok(u32::from_be_bytes(dol.virtual_data_at(buf, addr, 4)?.try_into()?))
 }
 
+/// Verifies that every loaded section lies within the file, and that loaded sections
+/// don't overlap BSS. Truncated files would otherwise fail later with confusing errors.
+fn validate_sections(dol: &dyn DolLike, file_len: usize) -> Result<()> {
+    for section in dol.sections().iter().filter(|s| s.kind != DolSectionKind::Bss) {
+        let file_end = section.file_offset as u64 + section.data_size as u64;
+        ensure!(
+            file_end <= file_len as u64,
+            "{:?} section @ {:#010X} file range {:#X}..{:#X} exceeds file size {:#X}",
+            section.kind,
+            section.address,
+            section.file_offset,
+            file_end,
+            file_len
+        );
+    }
+    for bss in dol.sections().iter().filter(|s| s.kind == DolSectionKind::Bss) {
+        let bss_end = bss.address as u64 + bss.size as u64;
+        for section in dol.sections().iter().filter(|s| s.kind != DolSectionKind::Bss) {
+            let start = section.address as u64;
+            let end = start + section.size as u64;
+            if start >= bss_end || end <= bss.address as u64 {
+                continue;
+            }
+            // A DOL's unified BSS range also covers .sdata and .sdata2
+            ensure!(
+                dol.has_unified_bss() && start >= bss.address as u64 && end <= bss_end,
+                "{:?} section @ {:#010X}-{:#010X} overlaps BSS @ {:#010X}-{:#010X}",
+                section.kind,
+                start,
+                end,
+                bss.address,
+                bss_end
+            );
+        }
+    }
+    Ok(())
+}
+
 pub fn process_dol(buf: &[u8], name: &str) -> Result<ObjInfo> {
     let mut reader = Cursor::new(buf);
     let dol: Box<dyn DolLike> = if buf.len() > 4 && *array_ref!(buf, 0, 4) == ALF_MAGIC {
@@ -186,6 +224,7 @@ pub fn process_dol(buf: &[u8], name: &str) -> Result<ObjInfo> {
     } else {
         Box::new(DolFile::from_reader(&mut reader, Endian::Big)?)
     };
+    validate_sections(dol.as_ref(), buf.len())?;
 
     // Locate _rom_copy_info
     let first_rom_section = dol
@@ -427,7 +466,7 @@ pub fn process_dol(buf: &[u8], name: &str) -> Result<ObjInfo> {
             kind,
             address: dol_section.address as u64,
             size: size as u64,
-            data: data.to_vec(),
+            data: data.into(),
             align: 0,
             elf_index: 0,
             relocations: Default::default(),
@@ -457,7 +496,7 @@ pub fn process_dol(buf: &[u8], name: &str) -> Result<ObjInfo> {
                 kind: ObjSectionKind::Bss,
                 address: addr as u64,
                 size: size as u64,
-                data: vec![],
+                data: vec![].into(),
                 align: 0,
                 elf_index: 0,
                 relocations: Default::default(),
@@ -477,7 +516,7 @@ pub fn process_dol(buf: &[u8], name: &str) -> Result<ObjInfo> {
                 kind: ObjSectionKind::Bss,
                 address: bss_section.address as u64,
                 size: bss_section.size as u64,
-                data: vec![],
+                data: vec![].into(),
                 align: 0,
                 elf_index: 0,
                 relocations: Default::default(),
@@ -504,7 +543,7 @@ pub fn process_dol(buf: &[u8], name: &str) -> Result<ObjInfo> {
                         kind: ObjSectionKind::Bss,
                         address: bss_sections[0].0 as u64,
                         size: bss_sections[0].1 as u64,
-                        data: vec![],
+                        data: vec![].into(),
                         align: 0,
                         elf_index: 0,
                         relocations: Default::default(),
@@ -518,7 +557,7 @@ pub fn process_dol(buf: &[u8], name: &str) -> Result<ObjInfo> {
                         kind: ObjSectionKind::Bss,
                         address: bss_sections[1].0 as u64,
                         size: bss_sections[1].1 as u64,
-                        data: vec![],
+                        data: vec![].into(),
                         align: 0,
                         elf_index: 0,
                         relocations: Default::default(),
@@ -750,7 +789,7 @@ pub fn process_dol(buf: &[u8], name: &str) -> Result<ObjInfo> {
             let sda_base = obj.sda_base.unwrap();
             obj.add_symbol(
                 ObjSymbol {
-                    name: "_SDA2_BASE_".to_string(),
+                    name: SDA2_BASE_SYMBOL.to_string(),
                     address: sda2_base as u64,
                     size_known: true,
                     flags: ObjSymbolFlagSet(ObjSymbolFlags::Global.into()),
@@ -760,7 +799,7 @@ pub fn process_dol(buf: &[u8], name: &str) -> Result<ObjInfo> {
             )?;
             obj.add_symbol(
                 ObjSymbol {
-                    name: "_SDA_BASE_".to_string(),
+                    name: SDA_BASE_SYMBOL.to_string(),
                     address: sda_base as u64,
                     size_known: true,
                     flags: ObjSymbolFlagSet(ObjSymbolFlags::Global.into()),
@@ -847,3 +886,47 @@ fn validate_eti_init_info(
     }
     Ok(false)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Builds a DOL with a single text section at file offset 0x100.
+    fn build_dol(text_size: u32, bss_addr: u32, bss_size: u32) -> Vec<u8> {
+        let mut buf = vec![0u8; 0x100];
+        buf[0x00..0x04].copy_from_slice(&0x100u32.to_be_bytes()); // text_offs[0]
+        buf[0x48..0x4C].copy_from_slice(&0x80003100u32.to_be_bytes()); // text_addrs[0]
+        buf[0x90..0x94].copy_from_slice(&text_size.to_be_bytes()); // text_sizes[0]
+        buf[0xD8..0xDC].copy_from_slice(&bss_addr.to_be_bytes());
+        buf[0xDC..0xE0].copy_from_slice(&bss_size.to_be_bytes());
+        buf[0xE0..0xE4].copy_from_slice(&0x80003100u32.to_be_bytes()); // entry_point
+        buf
+    }
+
+    #[test]
+    fn test_truncated_dol() {
+        let mut buf = build_dol(0x100, 0x80004000, 0x100);
+        buf.resize(0x180, 0);
+        let err = process_dol(&buf, "test").unwrap_err().to_string();
+        assert_eq!(
+            err,
+            "Text section @ 0x80003100 file range 0x100..0x200 exceeds file size 0x180"
+        );
+
+        let dol = DolFile::from_reader(&mut Cursor::new(&buf), Endian::Big).unwrap();
+        buf.resize(0x200, 0);
+        assert!(validate_sections(&dol, buf.len()).is_ok());
+    }
+
+    #[test]
+    fn test_bss_overlap() {
+        let mut buf = build_dol(0x100, 0x80003180, 0x100);
+        buf.resize(0x200, 0);
+        let dol = DolFile::from_reader(&mut Cursor::new(&buf), Endian::Big).unwrap();
+        let err = validate_sections(&dol, buf.len()).unwrap_err().to_string();
+        assert_eq!(
+            err,
+            "Text section @ 0x80003100-0x80003200 overlaps BSS @ 0x80003180-0x80003280"
+        );
+    }
+}
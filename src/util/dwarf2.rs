@@ -0,0 +1,335 @@
+use std::{
+    collections::BTreeMap,
+    io::{BufRead, Seek, SeekFrom},
+};
+
+use anyhow::{anyhow, ensure, Result};
+
+use crate::util::reader::{Endian, FromReader};
+
+const DW_LNS_COPY: u8 = 1;
+const DW_LNS_ADVANCE_PC: u8 = 2;
+const DW_LNS_ADVANCE_LINE: u8 = 3;
+const DW_LNS_SET_FILE: u8 = 4;
+const DW_LNS_SET_COLUMN: u8 = 5;
+const DW_LNS_NEGATE_STMT: u8 = 6;
+const DW_LNS_SET_BASIC_BLOCK: u8 = 7;
+const DW_LNS_CONST_ADD_PC: u8 = 8;
+const DW_LNS_FIXED_ADVANCE_PC: u8 = 9;
+
+const DW_LNE_END_SEQUENCE: u8 = 1;
+const DW_LNE_SET_ADDRESS: u8 = 2;
+const DW_LNE_DEFINE_FILE: u8 = 3;
+
+/// Address to source line mapping, read from a DWARF 2 `.debug_line` section.
+#[derive(Debug, Clone, Default)]
+pub struct LineTable {
+    pub files: Vec<String>,
+    /// Start address -> (file index, line). `None` marks the end of a sequence.
+    pub rows: BTreeMap<u32, Option<(usize, u32)>>,
+}
+
+impl LineTable {
+    /// Returns the file name and line number for the given address, if any.
+    pub fn lookup(&self, address: u32) -> Option<(&str, u32)> {
+        let (_, &row) = self.rows.range(..=address).next_back()?;
+        let (file, line) = row?;
+        Some((self.files.get(file)?.as_str(), line))
+    }
+}
+
+struct LineProgramHeader {
+    end: u64,
+    program_start: u64,
+    min_inst_length: u8,
+    line_base: i8,
+    line_range: u8,
+    opcode_base: u8,
+    standard_opcode_lengths: Vec<u8>,
+    include_directories: Vec<String>,
+}
+
+#[derive(Copy, Clone)]
+struct LineState {
+    address: u32,
+    file: u32,
+    line: u32,
+}
+
+impl Default for LineState {
+    fn default() -> Self { Self { address: 0, file: 1, line: 1 } }
+}
+
+pub fn read_debug_line_section<R>(reader: &mut R, e: Endian) -> Result<LineTable>
+where R: BufRead + Seek + ?Sized {
+    let len = {
+        let old_pos = reader.stream_position()?;
+        let len = reader.seek(SeekFrom::End(0))?;
+        reader.seek(SeekFrom::Start(old_pos))?;
+        len
+    };
+
+    let mut table = LineTable::default();
+    loop {
+        let position = reader.stream_position()?;
+        if position >= len {
+            break;
+        }
+        read_line_program(reader, e, &mut table)?;
+    }
+    Ok(table)
+}
+
+fn read_line_program<R>(reader: &mut R, e: Endian, table: &mut LineTable) -> Result<()>
+where R: BufRead + Seek + ?Sized {
+    let header = read_line_program_header(reader, e)?;
+    // File indices are 1-based and local to each line program
+    let mut files = Vec::new();
+    loop {
+        let name = read_string(reader)?;
+        if name.is_empty() {
+            break;
+        }
+        files.push(table.files.len());
+        table.files.push(read_file_entry(reader, &header, name)?);
+    }
+
+    reader.seek(SeekFrom::Start(header.program_start))?;
+    let mut state = LineState::default();
+    while reader.stream_position()? < header.end {
+        let opcode = u8::from_reader(reader, e)?;
+        if opcode >= header.opcode_base {
+            let adjusted = opcode - header.opcode_base;
+            state.address = state.address.wrapping_add(
+                (adjusted / header.line_range) as u32 * header.min_inst_length as u32,
+            );
+            state.line = state.line.wrapping_add_signed(
+                header.line_base as i32 + (adjusted % header.line_range) as i32,
+            );
+            emit_row(table, &files, &state);
+            continue;
+        }
+        match opcode {
+            0 => {
+                let length = read_uleb128(reader)?;
+                ensure!(length > 0, "Invalid extended opcode length");
+                let start = reader.stream_position()?;
+                match u8::from_reader(reader, e)? {
+                    DW_LNE_END_SEQUENCE => {
+                        table.rows.insert(state.address, None);
+                        state = LineState::default();
+                    }
+                    DW_LNE_SET_ADDRESS => state.address = u32::from_reader(reader, e)?,
+                    DW_LNE_DEFINE_FILE => {
+                        let name = read_string(reader)?;
+                        files.push(table.files.len());
+                        table.files.push(read_file_entry(reader, &header, name)?);
+                    }
+                    _ => {}
+                }
+                let next = start
+                    .checked_add(length)
+                    .ok_or_else(|| anyhow!("Invalid extended opcode length {:#X}", length))?;
+                reader.seek(SeekFrom::Start(next))?;
+            }
+            DW_LNS_COPY => emit_row(table, &files, &state),
+            DW_LNS_ADVANCE_PC => {
+                let advance =
+                    (read_uleb128(reader)? as u32).wrapping_mul(header.min_inst_length as u32);
+                state.address = state.address.wrapping_add(advance);
+            }
+            DW_LNS_ADVANCE_LINE => {
+                state.line = state.line.wrapping_add_signed(read_sleb128(reader)? as i32)
+            }
+            DW_LNS_SET_FILE => state.file = read_uleb128(reader)? as u32,
+            DW_LNS_SET_COLUMN => {
+                read_uleb128(reader)?;
+            }
+            DW_LNS_NEGATE_STMT | DW_LNS_SET_BASIC_BLOCK => {}
+            DW_LNS_CONST_ADD_PC => {
+                state.address = state.address.wrapping_add(
+                    ((255 - header.opcode_base) / header.line_range) as u32
+                        * header.min_inst_length as u32,
+                )
+            }
+            DW_LNS_FIXED_ADVANCE_PC => {
+                state.address = state.address.wrapping_add(u16::from_reader(reader, e)? as u32)
+            }
+            _ => {
+                // Unknown standard opcode, skip its operands
+                for _ in 0..header.standard_opcode_lengths[opcode as usize - 1] {
+                    read_uleb128(reader)?;
+                }
+            }
+        }
+    }
+    reader.seek(SeekFrom::Start(header.end))?;
+    Ok(())
+}
+
+fn emit_row(table: &mut LineTable, files: &[usize], state: &LineState) {
+    let file = state.file.checked_sub(1).and_then(|i| files.get(i as usize).copied());
+    table.rows.insert(state.address, file.map(|file| (file, state.line)));
+}
+
+fn read_line_program_header<R>(reader: &mut R, e: Endian) -> Result<LineProgramHeader>
+where R: BufRead + Seek + ?Sized {
+    let unit_length = u32::from_reader(reader, e)?;
+    let end = reader.stream_position()? + unit_length as u64;
+    let version = u16::from_reader(reader, e)?;
+    ensure!(version == 2, "Unsupported .debug_line version {}", version);
+    let header_length = u32::from_reader(reader, e)?;
+    let program_start = reader.stream_position()? + header_length as u64;
+    let min_inst_length = u8::from_reader(reader, e)?;
+    let _default_is_stmt = u8::from_reader(reader, e)?;
+    let line_base = i8::from_reader(reader, e)?;
+    let line_range = u8::from_reader(reader, e)?;
+    ensure!(line_range != 0, "Invalid .debug_line line range");
+    let opcode_base = u8::from_reader(reader, e)?;
+    ensure!(opcode_base != 0, "Invalid .debug_line opcode base");
+    let mut standard_opcode_lengths = vec![0u8; opcode_base as usize - 1];
+    reader.read_exact(&mut standard_opcode_lengths)?;
+    let mut include_directories = Vec::new();
+    loop {
+        let dir = read_string(reader)?;
+        if dir.is_empty() {
+            break;
+        }
+        include_directories.push(dir);
+    }
+    Ok(LineProgramHeader {
+        end,
+        program_start,
+        min_inst_length,
+        line_base,
+        line_range,
+        opcode_base,
+        standard_opcode_lengths,
+        include_directories,
+    })
+}
+
+/// Reads the remainder of a file entry and resolves its include directory.
+fn read_file_entry<R>(reader: &mut R, header: &LineProgramHeader, name: String) -> Result<String>
+where R: BufRead + ?Sized {
+    let dir = read_uleb128(reader)?;
+    let _mtime = read_uleb128(reader)?;
+    let _length = read_uleb128(reader)?;
+    Ok(match dir.checked_sub(1).and_then(|i| header.include_directories.get(i as usize)) {
+        Some(dir) => format!("{}/{}", dir, name),
+        None => name,
+    })
+}
+
+fn read_string<R>(reader: &mut R) -> Result<String>
+where R: BufRead + ?Sized {
+    let mut buf = Vec::new();
+    reader.read_until(0, &mut buf)?;
+    ensure!(buf.pop() == Some(0), "Unterminated string");
+    Ok(String::from_utf8_lossy(&buf).into_owned())
+}
+
+fn read_uleb128<R>(reader: &mut R) -> Result<u64>
+where R: BufRead + ?Sized {
+    let mut result = 0u64;
+    let mut shift = 0;
+    loop {
+        let mut byte = [0u8; 1];
+        reader.read_exact(&mut byte)?;
+        ensure!(shift < 64, "ULEB128 value too large");
+        result |= ((byte[0] & 0x7F) as u64) << shift;
+        shift += 7;
+        if byte[0] & 0x80 == 0 {
+            return Ok(result);
+        }
+    }
+}
+
+fn read_sleb128<R>(reader: &mut R) -> Result<i64>
+where R: BufRead + ?Sized {
+    let mut result = 0i64;
+    let mut shift = 0;
+    loop {
+        let mut byte = [0u8; 1];
+        reader.read_exact(&mut byte)?;
+        ensure!(shift < 64, "SLEB128 value too large");
+        result |= ((byte[0] & 0x7F) as i64) << shift;
+        shift += 7;
+        if byte[0] & 0x80 == 0 {
+            if shift < 64 && byte[0] & 0x40 != 0 {
+                result |= -1 << shift;
+            }
+            return Ok(result);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+
+    #[test]
+    fn test_read_debug_line_section() {
+        let mut program = vec![
+            1,    // min_inst_length
+            1,    // default_is_stmt
+            0xFB, // line_base (-5)
+            14,   // line_range
+            10,   // opcode_base
+            0, 1, 1, 1, 1, 0, 0, 0, 1, // standard_opcode_lengths
+            b's', b'r', b'c', 0, 0, // include_directories
+            b'a', b'.', b'c', 0, 1, 0, 0, // file 1
+            b'b', b'.', b'h', 0, 0, 0, 0, // file 2
+            0, // end of file names
+        ];
+        let header_length = program.len() as u32;
+        // set_address 0x80003100
+        program.extend_from_slice(&[0, 5, DW_LNE_SET_ADDRESS, 0x80, 0x00, 0x31, 0x00]);
+        // line 10
+        program.extend_from_slice(&[DW_LNS_ADVANCE_LINE, 9, DW_LNS_COPY]);
+        // special opcode: address += 4, line += 1
+        program.push(72);
+        // file 2, address += 8, line -= 2
+        program.extend_from_slice(&[DW_LNS_SET_FILE, 2, DW_LNS_ADVANCE_PC, 8]);
+        program.extend_from_slice(&[DW_LNS_ADVANCE_LINE, 0x7E, DW_LNS_COPY]);
+        // address += 4, end_sequence
+        program.extend_from_slice(&[DW_LNS_ADVANCE_PC, 4, 0, 1, DW_LNE_END_SEQUENCE]);
+
+        let mut data = vec![];
+        let unit_length = 2 + 4 + program.len() as u32;
+        data.extend_from_slice(&unit_length.to_be_bytes());
+        data.extend_from_slice(&2u16.to_be_bytes());
+        data.extend_from_slice(&header_length.to_be_bytes());
+        data.extend_from_slice(&program);
+
+        let table = read_debug_line_section(&mut Cursor::new(data), Endian::Big).unwrap();
+        assert_eq!(table.files, vec!["src/a.c".to_string(), "b.h".to_string()]);
+        assert_eq!(table.lookup(0x800030FC), None);
+        assert_eq!(table.lookup(0x80003100), Some(("src/a.c", 10)));
+        assert_eq!(table.lookup(0x80003102), Some(("src/a.c", 10)));
+        assert_eq!(table.lookup(0x80003104), Some(("src/a.c", 11)));
+        assert_eq!(table.lookup(0x8000310C), Some(("b.h", 9)));
+        assert_eq!(table.lookup(0x80003110), None);
+    }
+
+    #[test]
+    fn test_address_overflow() {
+        let mut program = vec![4, 1, 0xFB, 14, 10, 0, 1, 1, 1, 1, 0, 0, 0, 1, 0, 0];
+        let header_length = program.len() as u32;
+        program.extend_from_slice(&[0, 5, DW_LNE_SET_ADDRESS, 0xFF, 0xFF, 0xFF, 0xFC]);
+        // Advances past the end of the address space wrap around
+        program.extend_from_slice(&[DW_LNS_ADVANCE_PC, 0xFF, 0xFF, 0xFF, 0xFF, 0x0F]);
+        program.extend_from_slice(&[DW_LNS_FIXED_ADVANCE_PC, 0xFF, 0xFF, DW_LNS_COPY]);
+
+        let mut data = vec![];
+        let unit_length = 2 + 4 + program.len() as u32;
+        data.extend_from_slice(&unit_length.to_be_bytes());
+        data.extend_from_slice(&2u16.to_be_bytes());
+        data.extend_from_slice(&header_length.to_be_bytes());
+        data.extend_from_slice(&program);
+        let table = read_debug_line_section(&mut Cursor::new(data), Endian::Big).unwrap();
+        assert_eq!(table.rows.keys().copied().collect::<Vec<_>>(), vec![0xFFF7]);
+    }
+}
@@ -1,24 +1,26 @@
 use std::{
-    collections::{hash_map, HashMap},
+    borrow::Cow,
+    collections::{hash_map, BTreeMap, HashMap},
     io::Cursor,
     num::NonZeroU64,
     path::Path,
 };
 
 use anyhow::{anyhow, bail, ensure, Context, Result};
-use cwdemangle::demangle;
+use cwdemangle::{demangle, DemangleOptions};
 use flagset::Flags;
 use indexmap::IndexMap;
 use objdiff_core::obj::split_meta::{SplitMeta, SHT_SPLITMETA, SPLITMETA_SECTION};
 use object::{
     elf,
-    elf::{SHF_ALLOC, SHF_EXECINSTR, SHF_WRITE, SHT_LOUSER, SHT_NOBITS, SHT_PROGBITS},
+    elf::{SHT_LOUSER, SHT_NOBITS, SHT_NOTE, SHT_PROGBITS},
     write::{
         elf::{ProgramHeader, Rel, SectionHeader, SectionIndex, SymbolIndex, Writer},
         StringId,
     },
-    Architecture, Endianness, Object, ObjectKind, ObjectSection, ObjectSymbol, Relocation,
-    RelocationFlags, RelocationTarget, SectionKind, Symbol, SymbolKind, SymbolScope, SymbolSection,
+    Architecture, CompressionFormat, Endianness, Object, ObjectKind, ObjectSection, ObjectSymbol,
+    Relocation, RelocationFlags, RelocationTarget, SectionKind, Symbol, SymbolKind, SymbolScope,
+    SymbolSection,
 };
 use typed_path::Utf8NativePath;
 
@@ -27,10 +29,12 @@ use crate::{
     obj::{
         ObjArchitecture, ObjInfo, ObjKind, ObjReloc, ObjRelocKind, ObjSection, ObjSectionKind,
         ObjSplit, ObjSymbol, ObjSymbolFlagSet, ObjSymbolFlags, ObjSymbolKind, ObjUnit,
-        SectionIndex as ObjSectionIndex, SymbolIndex as ObjSymbolIndex,
+        SectionIndex as ObjSectionIndex, SymbolIndex as ObjSymbolIndex, SDA2_BASE_SYMBOL,
+        SDA_BASE_SYMBOL,
     },
     util::{
-        comment::{CommentSym, MWComment},
+        comment::{is_mw_comment, CommentSym, MWComment},
+        dwarf2::read_debug_line_section,
         reader::{Endian, FromReader, ToWriter},
     },
     vfs::open_file,
@@ -40,16 +44,135 @@ pub const SHT_MWCATS: u32 = SHT_LOUSER + 0x4A2A82C2;
 
 enum BoundaryState {
     /// Looking for a file symbol, any section symbols are queued
-    LookForFile(Vec<(u64, String)>),
+    LookForFile(Vec<(Option<u64>, String)>),
     /// Looking for section symbols
     LookForSections(String),
     /// Done with files and sections
     FilesEnded,
 }
 
+/// Options for [`process_elf_with_options`].
+#[derive(Debug, Clone)]
+pub struct ProcessElfOptions {
+    /// Treat nameless local symbols as stripped, and resolve relocations against them to
+    /// the containing section symbol (plus the symbol's offset as addend) instead of failing.
+    /// Section symbols are created as needed.
+    pub lenient_relocations: bool,
+    /// Rename sections sharing a name with an earlier section (`.text`, `.text.1`, ...)
+    /// so that section names are unique.
+    pub rename_duplicate_sections: bool,
+    /// Leave [`ObjSection::data`] empty instead of copying section contents, for analyses
+    /// that only need headers, symbols and relocations. The object is marked with
+    /// [`ObjInfo::data_stripped`]; use [`load_section_data`] to read the data of individual
+    /// sections on demand from their [`ObjSection::file_offset`]. Compressed sections are
+    /// still copied, as they have no contiguous range in the file.
+    pub skip_section_data: bool,
+    /// Options used to demangle symbol names.
+    pub demangle_options: DemangleOptions,
+    /// Name nameless local symbols after their address (`lbl_80001234` in code sections,
+    /// `data_80001234` elsewhere) instead of failing. Synthesized names are flagged with
+    /// [`ObjSymbolFlags::Autogenerated`].
+    pub synthesize_symbol_names: bool,
+    /// Skip relocations of unhandled types instead of failing. Skipped relocations are
+    /// counted per ELF type in [`ObjInfo::skipped_relocations`].
+    pub skip_unknown_relocations: bool,
+    /// Read relocations into [`ObjSection::relocations`]. Disable for symbol-only scans;
+    /// sections are then left without relocations. Enabled by default.
+    pub parse_relocations: bool,
+}
+
+impl Default for ProcessElfOptions {
+    fn default() -> Self {
+        Self {
+            lenient_relocations: false,
+            rename_duplicate_sections: false,
+            skip_section_data: false,
+            demangle_options: Default::default(),
+            synthesize_symbol_names: false,
+            skip_unknown_relocations: false,
+            parse_relocations: true,
+        }
+    }
+}
+
+/// Loading phases reported to the progress callback of [`process_elf_with_options`].
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum ProgressEvent {
+    /// Section headers and data have been read. Contains the number of sections loaded.
+    SectionsParsed(usize),
+    /// The symbol table has been read. Contains the number of symbols loaded.
+    SymbolsParsed(usize),
+    /// Relocations have been read. Contains the number of relocations loaded.
+    RelocationsParsed(usize),
+}
+
 pub fn process_elf(path: &Utf8NativePath) -> Result<ObjInfo> {
+    process_elf_with_options(path, &ProcessElfOptions::default(), None)
+}
+
+/// Loads an ELF object, optionally reporting progress through `progress` as each
+/// loading phase completes.
+pub fn process_elf_with_options(
+    path: &Utf8NativePath,
+    options: &ProcessElfOptions,
+    progress: Option<&mut dyn FnMut(ProgressEvent)>,
+) -> Result<ObjInfo> {
     let mut file = open_file(path, true)?;
-    let obj_file = object::read::File::parse(file.map()?)?;
+    let mut obj = parse_elf(file.map()?, options, progress)?;
+    add_default_unit(&mut obj, path.file_name().unwrap_or_default());
+    Ok(obj)
+}
+
+/// Parses an ELF object from an in-memory buffer. `name` is used as the unit name
+/// for objects without a file symbol, as with [`process_elf`].
+pub fn process_elf_bytes(data: &[u8], name: &str) -> Result<ObjInfo> {
+    let mut obj = parse_elf(data, &ProcessElfOptions::default(), None)?;
+    add_default_unit(&mut obj, name);
+    Ok(obj)
+}
+
+/// Objects assembled by MWCC from `.s` files may have no file symbol at all, leaving
+/// them without a name. Name such objects after the input file and assign every section
+/// to a single unit so that they remain visible to the splitter.
+fn add_default_unit(obj: &mut ObjInfo, name: &str) {
+    if obj.kind != ObjKind::Relocatable || !obj.name.is_empty() || name.is_empty() {
+        return;
+    }
+    log::debug!("No file symbol found, using unit name '{}'", name);
+    obj.name = name.to_string();
+    obj.link_order = vec![ObjUnit {
+        name: name.to_string(),
+        autogenerated: true,
+        comment_version: None,
+        order: None,
+    }];
+    for (_, section) in obj.sections.iter_mut() {
+        if section.kind == ObjSectionKind::Note {
+            continue;
+        }
+        section.splits.push(section.address as u32, ObjSplit {
+            unit: name.to_string(),
+            end: (section.address + section.size) as u32,
+            align: None,
+            common: false,
+            autogenerated: true,
+            skip: false,
+            rename: None,
+        });
+    }
+}
+
+fn parse_elf(
+    data: &[u8],
+    options: &ProcessElfOptions,
+    mut progress: Option<&mut dyn FnMut(ProgressEvent)>,
+) -> Result<ObjInfo> {
+    let mut report = |event| {
+        if let Some(progress) = progress.as_mut() {
+            progress(event);
+        }
+    };
+    let obj_file = object::read::File::parse(data)?;
     let architecture = match obj_file.architecture() {
         Architecture::PowerPc => ObjArchitecture::PowerPc,
         arch => bail!("Unexpected architecture: {arch:?}"),
@@ -67,11 +190,17 @@ pub fn process_elf(path: &Utf8NativePath) -> Result<ObjInfo> {
     let mut db_stack_addr: Option<u32> = None;
     let mut arena_lo: Option<u32> = None;
     let mut arena_hi: Option<u32> = None;
+    let mut ctors_address: Option<u32> = None;
+    let mut dtors_address: Option<u32> = None;
+    let mut init_cpp_exceptions_reference: Option<u32> = None;
     let mut sda_base: Option<u32> = None;
     let mut sda2_base: Option<u32> = None;
 
     let mut sections: Vec<ObjSection> = vec![];
     let mut section_indexes: Vec<Option<usize>> = vec![None /* ELF null section */];
+    // End of the previous loaded section in the file, used to order BSS sections
+    let mut last_file_end = 0;
+    let mut section_name_to_index = HashMap::<String, usize>::new(); // for renaming duplicate names
     for section in obj_file.sections() {
         if section.size() == 0 {
             section_indexes.push(None);
@@ -83,32 +212,67 @@ pub fn process_elf(path: &Utf8NativePath) -> Result<ObjInfo> {
             SectionKind::Data => ObjSectionKind::Data,
             SectionKind::ReadOnlyData => ObjSectionKind::ReadOnlyData,
             SectionKind::UninitializedData => ObjSectionKind::Bss,
-            // SectionKind::Other if section_name == ".comment" => ObjSectionKind::Comment,
+            SectionKind::Note
+                if section_name.starts_with(".note.") && section_name != SPLITMETA_SECTION =>
+            {
+                ObjSectionKind::Note
+            }
+            // Non-Metrowerks producer strings (e.g. GCC) are preserved as-is
+            _ if section_name == ".comment" && !is_mw_comment(&section.uncompressed_data()?) => {
+                ObjSectionKind::Note
+            }
+            // Unwind tables may not be allocated, but their contents and relocations
+            // (to landing pads) are kept as opaque data so they round-trip
+            _ if matches!(section_name, ".eh_frame" | ".gcc_except_table") => {
+                ObjSectionKind::ReadOnlyData
+            }
             _ => {
                 section_indexes.push(None);
                 continue;
             }
         };
+        // BSS sections don't occupy space in the file, so use the offset where the
+        // section would begin to keep file offsets monotonic.
+        let file_offset = match section.file_range() {
+            Some((offset, _)) => offset,
+            None => last_file_end,
+        };
+        last_file_end = file_offset + section.size();
+        let mut name = section_name.to_string();
+        if options.rename_duplicate_sections {
+            while sections.iter().any(|s| s.name == name) {
+                let index = section_name_to_index.entry(section_name.to_string()).or_insert(0);
+                *index += 1;
+                name = format!("{}.{}", section_name, index);
+            }
+        }
         section_indexes.push(Some(sections.len()));
         sections.push(ObjSection {
-            name: section_name.to_string(),
+            name,
             kind: section_kind,
             address: section.address(),
             size: section.size(),
-            data: section.uncompressed_data()?.to_vec(),
+            data: if options.skip_section_data
+                && section.compressed_file_range()?.format == CompressionFormat::None
+            {
+                Default::default()
+            } else {
+                section.uncompressed_data()?.into_owned().into()
+            },
             align: section.align(),
             elf_index: section.index().0 as ObjSectionIndex,
             relocations: Default::default(),
             virtual_address: None, // Loaded from section symbol
-            file_offset: section.file_range().map(|(v, _)| v).unwrap_or_default(),
+            file_offset,
             section_known: true,
             splits: Default::default(),
         });
     }
+    report(ProgressEvent::SectionsParsed(sections.len()));
 
     let mw_comment = if let Some(comment_section) = obj_file.section_by_name(".comment") {
         let data = comment_section.uncompressed_data()?;
-        if data.is_empty() {
+        if !is_mw_comment(&data) {
             None
         } else {
             let mut reader = Cursor::new(&*data);
@@ -150,23 +314,70 @@ pub fn process_elf(path: &Utf8NativePath) -> Result<ObjInfo> {
         None
     };
 
+    // Addresses in relocatable objects are unrelocated, so only load line info for executables
+    let line_table = match obj_file.section_by_name(".debug_line") {
+        Some(line_section) if kind == ObjKind::Executable => {
+            // Line info is optional, so an unsupported or malformed table doesn't fail the load
+            let data = line_section.uncompressed_data()?;
+            match read_debug_line_section(&mut Cursor::new(&*data), Endian::Big) {
+                Ok(table) => {
+                    log::debug!("Loaded .debug_line section with {} rows", table.rows.len());
+                    Some(table)
+                }
+                Err(e) => {
+                    log::warn!("Ignoring .debug_line section: {:#}", e);
+                    None
+                }
+            }
+        }
+        _ => None,
+    };
+
+    // GNU symbol versioning is not supported, so version information is dropped
+    let versioned_symbols = versioned_dynamic_symbols(&obj_file)?;
+    if !versioned_symbols.is_empty() {
+        log::warn!(
+            "Ignoring .gnu.version information for {} symbol(s): {}",
+            versioned_symbols.len(),
+            versioned_symbols
+                .iter()
+                .map(|(name, version)| format!("{}@{}", name, version))
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+    }
+
     let mut symbols: Vec<ObjSymbol> = vec![];
     let mut symbol_indexes: Vec<Option<ObjSymbolIndex>> = vec![None /* ELF null symbol */];
-    let mut section_starts = IndexMap::<String, Vec<(u64, String)>>::new();
+    // File name -> section start addresses, or `None` if not yet known
+    let mut section_starts = IndexMap::<String, Vec<(Option<u64>, String)>>::new();
     let mut name_to_index = HashMap::<String, usize>::new(); // for resolving duplicate names
     let mut boundary_state = BoundaryState::LookForFile(Default::default());
 
-    for symbol in obj_file.symbols() {
+    // Stripped executables may only have a dynamic symbol table
+    let elf_symbols =
+        if obj_file.symbol_table().is_none() && obj_file.dynamic_symbol_table().is_some() {
+            log::debug!("No .symtab found, reading symbols from .dynsym");
+            obj_file.dynamic_symbols()
+        } else {
+            obj_file.symbols()
+        };
+    for symbol in elf_symbols {
         // Locate linker-generated symbols
-        let symbol_name = symbol.name()?;
-        match symbol_name {
+        let symbol_name = symbol_name(&symbol)?;
+        match symbol_name.as_ref() {
             "_stack_addr" => stack_address = Some(symbol.address() as u32),
             "_stack_end" => stack_end = Some(symbol.address() as u32),
             "_db_stack_addr" => db_stack_addr = Some(symbol.address() as u32),
             "__ArenaLo" => arena_lo = Some(symbol.address() as u32),
             "__ArenaHi" => arena_hi = Some(symbol.address() as u32),
-            "_SDA_BASE_" => sda_base = Some(symbol.address() as u32),
-            "_SDA2_BASE_" => sda2_base = Some(symbol.address() as u32),
+            "_ctors" => ctors_address = Some(symbol.address() as u32),
+            "_dtors" => dtors_address = Some(symbol.address() as u32),
+            "__init_cpp_exceptions_reference" => {
+                init_cpp_exceptions_reference = Some(symbol.address() as u32)
+            }
+            SDA_BASE_SYMBOL => sda_base = Some(symbol.address() as u32),
+            SDA2_BASE_SYMBOL => sda2_base = Some(symbol.address() as u32),
             _ => {}
         };
 
@@ -246,16 +457,21 @@ pub fn process_elf(path: &Utf8NativePath) -> Result<ObjInfo> {
 
                 let section = obj_file.section_by_index(section_index)?;
                 let section_name = section.name()?.to_string();
+                // Section symbols in linked objects may have address 0, rather than the
+                // section address. In that case, the address is determined from the first
+                // symbol within the section.
+                let section_range = section.address()..section.address() + section.size();
+                let address = Some(symbol.address()).filter(|a| section_range.contains(a));
                 match &mut boundary_state {
                     BoundaryState::LookForFile(queue) => {
-                        queue.push((symbol.address(), section_name));
+                        queue.push((address, section_name));
                     }
                     BoundaryState::LookForSections(file_name) => {
                         if section_indexes[section_index.0].is_some() {
                             let sections = section_starts
                                 .get_mut(file_name)
                                 .ok_or_else(|| anyhow!("Failed to create entry"))?;
-                            sections.push((symbol.address(), section_name));
+                            sections.push((address, section_name));
                         }
                     }
                     BoundaryState::FilesEnded => {
@@ -283,15 +499,15 @@ pub fn process_elf(path: &Utf8NativePath) -> Result<ObjInfo> {
                             let section_name = section.name()?;
                             if let Some((addr, _)) = sections
                                 .iter_mut()
-                                .find(|(addr, name)| *addr == 0 && name == section_name)
+                                .find(|(addr, name)| addr.is_none() && name == section_name)
                             {
-                                // If the section symbol had address 0, determine address
-                                // from first symbol within that section.
-                                *addr = symbol.address();
+                                // If the section symbol's address is unknown, determine
+                                // address from first symbol within that section.
+                                *addr = Some(symbol.address());
                             } else if !sections.iter().any(|(_, name)| name == section_name) {
                                 // Otherwise, if there was no section symbol, assume this
                                 // symbol indicates the section address.
-                                sections.push((symbol.address(), section_name.to_string()));
+                                sections.push((Some(symbol.address()), section_name.to_string()));
                             }
                         }
                     }
@@ -309,10 +525,75 @@ pub fn process_elf(path: &Utf8NativePath) -> Result<ObjInfo> {
             symbol_indexes.push(None);
             continue;
         }
+        if options.lenient_relocations
+            && !options.synthesize_symbol_names
+            && symbol.is_local()
+            && symbol.kind() != SymbolKind::Section
+            && symbol_name.is_empty()
+        {
+            // Stripped local, relocations against it target the section symbol instead
+            symbol_indexes.push(None);
+            continue;
+        }
         symbol_indexes.push(Some(symbols.len() as ObjSymbolIndex));
         let align = mw_comment.as_ref().map(|(_, vec)| vec[symbol.index().0].align);
-        symbols.push(to_obj_symbol(&obj_file, &symbol, &section_indexes, align)?);
+        symbols.push(to_obj_symbol(&obj_file, &symbol, &section_indexes, align, options)?);
     }
+    report(ProgressEvent::SymbolsParsed(symbols.len()));
+
+    // Locate (or create) section symbols to use as targets for relocations against
+    // stripped symbols. Sections without stripped targets don't need one.
+    let section_symbols = if options.lenient_relocations {
+        let mut needs_symbol = vec![false; section_indexes.len()];
+        for section in obj_file.sections() {
+            for (_, reloc) in section.relocations() {
+                let RelocationTarget::Symbol(idx) = reloc.target() else {
+                    continue;
+                };
+                if symbol_indexes.get(idx.0).is_some_and(|i| i.is_some()) {
+                    continue;
+                }
+                if let Some(section_index) =
+                    obj_file.symbol_by_index(idx).ok().and_then(|s| s.section_index())
+                {
+                    needs_symbol[section_index.0] = true;
+                }
+            }
+        }
+        let mut section_symbols: Vec<Option<ObjSymbolIndex>> = vec![None; section_indexes.len()];
+        for (symbol_index, symbol) in symbols.iter().enumerate() {
+            if symbol.kind != ObjSymbolKind::Section {
+                continue;
+            }
+            if let Some(elf_index) = symbol.section.and_then(|section_index| {
+                section_indexes.iter().position(|&idx| idx == Some(section_index as usize))
+            }) {
+                section_symbols[elf_index].get_or_insert(symbol_index as ObjSymbolIndex);
+            }
+        }
+        for (elf_index, section_index) in section_indexes.iter().enumerate() {
+            let Some(section_index) = *section_index else {
+                continue;
+            };
+            if section_symbols[elf_index].is_some() || !needs_symbol[elf_index] {
+                continue;
+            }
+            let section = &sections[section_index];
+            section_symbols[elf_index] = Some(symbols.len() as ObjSymbolIndex);
+            symbols.push(ObjSymbol {
+                name: section.name.clone(),
+                address: section.address,
+                section: Some(section_index as ObjSectionIndex),
+                size_known: true,
+                flags: ObjSymbolFlagSet(ObjSymbolFlags::Local.into()),
+                kind: ObjSymbolKind::Section,
+                ..Default::default()
+            });
+        }
+        Some(section_symbols)
+    } else {
+        None
+    };
 
     let mut link_order = Vec::<ObjUnit>::new();
     if kind == ObjKind::Executable {
@@ -328,7 +609,15 @@ pub fn process_elf(path: &Utf8NativePath) -> Result<ObjInfo> {
 
         // Create a map of address -> file splits
         for (file_name, section_addrs) in section_starts {
-            for (address, _) in section_addrs {
+            for (address, section_name) in section_addrs {
+                let Some(address) = address else {
+                    log::warn!(
+                        "Failed to determine address of section {} in file {}",
+                        section_name,
+                        file_name
+                    );
+                    continue;
+                };
                 let Some(section) = sections.iter_mut().find(|s| s.contains(address as u32)) else {
                     log::warn!(
                         "Failed to find section containing address {:#010X} in file {}",
@@ -352,26 +641,70 @@ pub fn process_elf(path: &Utf8NativePath) -> Result<ObjInfo> {
         // TODO rebuild common symbols
     }
 
-    for section in obj_file.sections() {
-        let out_section =
-            match section_indexes[section.index().0].and_then(|idx| sections.get_mut(idx)) {
-                Some(s) => s,
-                None => continue,
-            };
-        // Generate relocations
-        for (address, reloc) in section.relocations() {
-            let Some(reloc) =
-                to_obj_reloc(&obj_file, &symbol_indexes, &out_section.data, address, reloc)?
-            else {
-                continue;
-            };
-            out_section.relocations.insert(address as u32, reloc)?;
+    let mut relocation_count = 0;
+    let mut skipped_relocations = BTreeMap::<u32, usize>::new();
+    if options.parse_relocations {
+        for section in obj_file.sections() {
+            let out_section =
+                match section_indexes[section.index().0].and_then(|idx| sections.get_mut(idx)) {
+                    Some(s) => s,
+                    None => continue,
+                };
+            // Generate relocations
+            let section_data = section.uncompressed_data()?;
+            for (address, reloc) in section.relocations() {
+                let reloc_kind = to_obj_reloc_kind(reloc.flags())?;
+                if let ObjRelocKind::Other(r_type) = reloc_kind {
+                    if options.skip_unknown_relocations {
+                        *skipped_relocations.entry(r_type).or_default() += 1;
+                        continue;
+                    }
+                    let r_offset = (address & !3) + 4 - reloc_kind.width() as u64;
+                    ensure!(
+                        address == r_offset,
+                        "Relocation of type {} at {:#010X} isn't at the expected offset {:#010X} \
+                        (try skipping unknown relocations)",
+                        r_type,
+                        address,
+                        r_offset
+                    );
+                }
+                let Some(reloc) = to_obj_reloc(
+                    &obj_file,
+                    &symbol_indexes,
+                    section_symbols.as_deref(),
+                    &section_data,
+                    address,
+                    reloc_kind,
+                    reloc,
+                )?
+                else {
+                    continue;
+                };
+                out_section.relocations.insert(address as u32, reloc)?;
+                relocation_count += 1;
+            }
+            out_section.relocations.pair_hi_lo();
         }
     }
+    report(ProgressEvent::RelocationsParsed(relocation_count));
+    if !skipped_relocations.is_empty() {
+        log::warn!(
+            "Skipped relocations of unhandled type: {}",
+            skipped_relocations
+                .iter()
+                .map(|(r_type, count)| format!("{} ({}x)", r_type, count))
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+    }
 
     let mut obj = ObjInfo::new(kind, architecture, obj_name, symbols, sections);
-    obj.entry = NonZeroU64::new(obj_file.entry()).map(|n| n.get());
+    if kind == ObjKind::Executable {
+        obj.entry = NonZeroU64::new(obj_file.entry()).map(|n| n.get());
+    }
     obj.mw_comment = mw_comment.map(|(header, _)| header);
+    obj.line_table = line_table;
     obj.split_meta = split_meta;
     obj.sda2_base = sda2_base;
     obj.sda_base = sda_base;
@@ -380,11 +713,86 @@ pub fn process_elf(path: &Utf8NativePath) -> Result<ObjInfo> {
     obj.db_stack_addr = db_stack_addr;
     obj.arena_lo = arena_lo;
     obj.arena_hi = arena_hi;
+    obj.ctors_address = ctors_address;
+    obj.dtors_address = dtors_address;
+    obj.init_cpp_exceptions_reference = init_cpp_exceptions_reference;
+    obj.skipped_relocations = skipped_relocations;
     obj.link_order = link_order;
+    obj.data_stripped = options.skip_section_data;
     Ok(obj)
 }
 
+/// Reads the data of a section of an object loaded with
+/// [`ProcessElfOptions::skip_section_data`] from the original ELF file, which is not parsed
+/// again: the bytes are taken from the section's recorded file offset. Once every section
+/// has its data, [`ObjInfo::data_stripped`] is cleared so the object can be written out.
+pub fn load_section_data(
+    data: &[u8],
+    obj: &mut ObjInfo,
+    section_index: ObjSectionIndex,
+) -> Result<()> {
+    let section = obj
+        .sections
+        .get_mut(section_index)
+        .ok_or_else(|| anyhow!("Invalid section index {}", section_index))?;
+    if !section.kind.is_bss() && section.data.len() as u64 != section.size {
+        let start = section.file_offset;
+        let end = start + section.size;
+        let bytes = data.get(start as usize..end as usize).ok_or_else(|| {
+            anyhow!(
+                "Section {} data {:#X}..{:#X} is out of bounds ({:#X})",
+                section.name,
+                start,
+                end,
+                data.len()
+            )
+        })?;
+        section.data = bytes.to_vec().into();
+    }
+    obj.data_stripped =
+        obj.sections.iter().any(|(_, s)| !s.kind.is_bss() && s.data.len() as u64 != s.size);
+    Ok(())
+}
+
+/// Options for [`write_elf_with_options`].
+#[derive(Debug, Clone, Default)]
+pub struct WriteElfOptions {
+    /// Omit local symbols. Relocations against them are rewritten to target the containing
+    /// section symbol, which requires a relocatable object.
+    pub strip_locals: bool,
+    /// Word used to pad between a code section and the following section, such as
+    /// `0x60000000` (nop). Repeated in big-endian order, aligned to the file offset.
+    pub code_fill: u32,
+    /// Word used to pad between a data section and the following section.
+    pub data_fill: u32,
+    /// Place section data at each section's original `file_offset`, padding as needed, to
+    /// minimize differences against the input file. Offsets must be increasing.
+    pub preserve_file_offsets: bool,
+}
+
 pub fn write_elf(obj: &ObjInfo, export_all: bool) -> Result<Vec<u8>> {
+    write_elf_with_options(obj, export_all, &WriteElfOptions::default())
+}
+
+pub fn write_elf_with_options(
+    obj: &ObjInfo,
+    export_all: bool,
+    options: &WriteElfOptions,
+) -> Result<Vec<u8>> {
+    write_elf_impl(obj, export_all, options, elf::SHN_LORESERVE.into())
+}
+
+/// Writes the object, emitting `.symtab_shndx` once any section index reaches
+/// `xindex_threshold` (`SHN_LORESERVE`, lowered in tests).
+fn write_elf_impl(
+    obj: &ObjInfo,
+    export_all: bool,
+    options: &WriteElfOptions,
+    xindex_threshold: u32,
+) -> Result<Vec<u8>> {
+    ensure!(!obj.data_stripped, "Can't write object {} with stripped section data", obj.name);
+    obj.validate_relocations()?;
+
     let mut out_data = Vec::new();
     let mut writer = Writer::new(Endianness::Big, false, &mut out_data);
 
@@ -490,7 +898,7 @@ pub fn write_elf(obj: &ObjInfo, export_all: bool) -> Result<Vec<u8>> {
 
     let mut out_symbols: Vec<OutSymbol> = Vec::with_capacity(obj.symbols.count() as usize);
     let mut symbol_map = vec![None; obj.symbols.count() as usize];
-    let mut section_symbol_offset = 0;
+    let mut section_symbols = vec![None; obj.sections.len() as usize];
     let mut num_local = 0;
 
     // Add file symbol
@@ -529,7 +937,6 @@ pub fn write_elf(obj: &ObjInfo, export_all: bool) -> Result<Vec<u8>> {
         {
             virtual_addresses.push(0);
         }
-        section_symbol_offset += 1;
     }
 
     // Add section symbols for relocatable objects
@@ -548,6 +955,7 @@ pub fn write_elf(obj: &ObjInfo, export_all: bool) -> Result<Vec<u8>> {
             };
             num_local = writer.symbol_count();
             out_symbols.push(OutSymbol { index, sym });
+            section_symbols[section_index as usize] = Some(index.0);
             if let Some((comment_data, _)) = &mut comment_data {
                 CommentSym { align: section.align as u32, vis_flags: 0, active_flags: 0 }
                     .to_writer_static(comment_data, Endian::Big)?;
@@ -560,23 +968,30 @@ pub fn write_elf(obj: &ObjInfo, export_all: bool) -> Result<Vec<u8>> {
         }
     }
 
-    // Add symbols, starting with local symbols
+    // Add symbols, starting with local symbols. ELF requires every local symbol to precede
+    // the globals, so partition by the binding that will actually be written.
     for (symbol_index, symbol) in obj
         .symbols
         .iter()
-        .filter(|&(_, s)| s.flags.is_local())
-        .chain(obj.symbols.iter().filter(|&(_, s)| !s.flags.is_local()))
+        .filter(|&(_, s)| symbol_bind(s) == elf::STB_LOCAL)
+        .chain(obj.symbols.iter().filter(|&(_, s)| symbol_bind(s) != elf::STB_LOCAL))
     {
         if obj.kind == ObjKind::Relocatable && symbol.kind == ObjSymbolKind::Section {
             // We wrote section symbols above, so skip them here
             let section_index =
                 symbol.section.ok_or_else(|| anyhow!("section symbol without section index"))?;
-            symbol_map[symbol_index as usize] =
-                Some(section_symbol_offset as ObjSectionIndex + section_index);
+            symbol_map[symbol_index as usize] = section_symbols[section_index as usize];
+            continue;
+        }
+        if options.strip_locals && symbol.flags.is_local() {
+            // Relocations against stripped symbols are rewritten below
             continue;
         }
 
-        let section = symbol.section.and_then(|idx| out_sections.get(idx as usize));
+        // Common symbols have no section, and carry their alignment in st_value
+        let is_common = symbol.flags.is_common();
+        let section =
+            symbol.section.filter(|_| !is_common).and_then(|idx| out_sections.get(idx as usize));
         let section_index = section.map(|s| s.index);
         let index = writer.reserve_symbol_index(section_index);
         let name_index = if symbol.name.is_empty() {
@@ -593,27 +1008,24 @@ pub fn write_elf(obj: &ObjInfo, export_all: bool) -> Result<Vec<u8>> {
                     ObjSymbolKind::Function => elf::STT_FUNC,
                     ObjSymbolKind::Object => elf::STT_OBJECT,
                     ObjSymbolKind::Section => elf::STT_SECTION,
+                    ObjSymbolKind::Tls => elf::STT_TLS,
                 };
-                let st_bind = if symbol.flags.is_weak() {
-                    elf::STB_WEAK
-                } else if symbol.flags.is_local() {
-                    elf::STB_LOCAL
-                } else {
-                    elf::STB_GLOBAL
-                };
-                (st_bind << 4) + st_type
+                (symbol_bind(symbol) << 4) + st_type
             },
             st_other: if symbol.flags.is_hidden() { elf::STV_HIDDEN } else { elf::STV_DEFAULT },
-            st_shndx: if section_index.is_some() {
-                0
-            } else if symbol.flags.is_common() {
+            st_shndx: if is_common {
                 elf::SHN_COMMON
+            } else if section_index.is_some() {
+                0
             } else if symbol.address != 0 {
                 elf::SHN_ABS
             } else {
                 elf::SHN_UNDEF
             },
-            st_value: symbol.address,
+            st_value: match symbol.align {
+                Some(align) if is_common => align as u64,
+                _ => symbol.address,
+            },
             st_size: symbol.size,
         };
         if sym.st_info >> 4 == elf::STB_LOCAL {
@@ -635,18 +1047,45 @@ pub fn write_elf(obj: &ObjInfo, export_all: bool) -> Result<Vec<u8>> {
         }
     }
 
+    // Section indices that don't fit in st_shndx are stored in .symtab_shndx instead
+    if out_sections.iter().any(|s| s.index.0 >= xindex_threshold) {
+        writer.require_symtab_shndx();
+    }
+    if writer.symtab_shndx_needed() {
+        writer.reserve_symtab_shndx_section_index();
+    }
+
     writer.reserve_file_header();
 
     if obj.kind == ObjKind::Executable {
-        writer.reserve_program_headers(obj.sections.len());
+        writer.reserve_program_headers(
+            obj.sections.iter().filter(|(_, s)| has_program_header(s)).count(),
+        );
     }
 
-    for ((_, section), out_section) in obj.sections.iter().zip(&mut out_sections) {
-        if section.kind == ObjSectionKind::Bss {
-            continue;
-        }
+    // Section data is laid out by kind, unless the original file offsets are preserved
+    let mut data_order =
+        obj.sections.iter().filter(|(_, s)| !s.kind.is_bss()).map(|(i, _)| i).collect::<Vec<_>>();
+    if !options.preserve_file_offsets {
+        data_order.sort_by_key(|&i| obj.sections[i].kind.canonical_order());
+    }
+
+    for &section_index in &data_order {
+        let section = &obj.sections[section_index];
+        let out_section = &mut out_sections[section_index as usize];
         ensure!(section.data.len() as u64 == section.size);
-        if section.size == 0 {
+        if options.preserve_file_offsets {
+            let offset = section.file_offset as usize;
+            ensure!(
+                offset >= writer.reserved_len(),
+                "Section {} file offset {:#X} precedes the end of previous data ({:#X})",
+                section.name,
+                offset,
+                writer.reserved_len()
+            );
+            writer.reserve_until(offset);
+            out_section.offset = writer.reserve(section.data.len(), 1);
+        } else if section.size == 0 {
             // Bug in Writer::reserve doesn't align when len is 0
             let offset = (writer.reserved_len() + 31) & !31;
             writer.reserve_until(offset);
@@ -664,6 +1103,7 @@ pub fn write_elf(obj: &ObjInfo, export_all: bool) -> Result<Vec<u8>> {
     }
 
     writer.reserve_symtab();
+    writer.reserve_symtab_shndx();
     writer.reserve_strtab();
     writer.reserve_shstrtab();
 
@@ -681,6 +1121,16 @@ pub fn write_elf(obj: &ObjInfo, export_all: bool) -> Result<Vec<u8>> {
 
     writer.reserve_section_headers();
 
+    // Relocatable objects have no entry point
+    let e_entry = match obj.kind {
+        ObjKind::Executable => obj.entry.unwrap_or(0),
+        ObjKind::Relocatable => {
+            if let Some(entry) = obj.entry {
+                log::warn!("Ignoring entry point {:#010X} for relocatable object", entry);
+            }
+            0
+        }
+    };
     writer.write_file_header(&object::write::elf::FileHeader {
         os_abi: elf::ELFOSABI_SYSV,
         abi_version: 0,
@@ -689,38 +1139,44 @@ pub fn write_elf(obj: &ObjInfo, export_all: bool) -> Result<Vec<u8>> {
             ObjKind::Relocatable => elf::ET_REL,
         },
         e_machine: elf::EM_PPC,
-        e_entry: obj.entry.unwrap_or(0),
+        e_entry,
         e_flags: elf::EF_PPC_EMB,
     })?;
 
     if obj.kind == ObjKind::Executable {
         writer.write_align_program_headers();
         for ((_, section), out_section) in obj.sections.iter().zip(&out_sections) {
+            if !has_program_header(section) {
+                continue;
+            }
             writer.write_program_header(&ProgramHeader {
-                p_type: elf::PT_LOAD,
+                p_type: if section.kind.loadable() { elf::PT_LOAD } else { elf::PT_NOTE },
                 p_flags: match section.kind {
                     ObjSectionKind::Code => elf::PF_R | elf::PF_X,
                     ObjSectionKind::Data | ObjSectionKind::Bss => elf::PF_R | elf::PF_W,
-                    ObjSectionKind::ReadOnlyData => elf::PF_R,
+                    ObjSectionKind::ReadOnlyData | ObjSectionKind::Note => elf::PF_R,
                 },
                 p_offset: out_section.offset as u64,
                 p_vaddr: section.address,
                 p_paddr: 0,
-                p_filesz: match section.kind {
-                    ObjSectionKind::Bss => 0,
-                    _ => section.size,
-                },
+                p_filesz: if section.kind.is_bss() { 0 } else { section.size },
                 p_memsz: section.size,
                 p_align: 32,
             });
         }
     }
 
-    for ((_, section), out_section) in obj.sections.iter().zip(&out_sections) {
-        if section.kind == ObjSectionKind::Bss {
-            continue;
-        }
-        writer.write_align(32);
+    let mut prev_kind = None;
+    for &section_index in &data_order {
+        let section = &obj.sections[section_index];
+        let out_section = &out_sections[section_index as usize];
+        let fill = match prev_kind {
+            Some(ObjSectionKind::Code) => options.code_fill,
+            Some(_) => options.data_fill,
+            None => 0,
+        };
+        write_fill(&mut writer, out_section.offset, fill);
+        prev_kind = Some(section.kind);
         ensure!(writer.len() == out_section.offset);
         if obj.kind == ObjKind::Relocatable {
             write_relocatable_section_data(&mut writer, section)?;
@@ -735,11 +1191,29 @@ pub fn write_elf(obj: &ObjInfo, export_all: bool) -> Result<Vec<u8>> {
         }
         writer.write_align_relocation();
         ensure!(writer.len() == out_section.rela_offset);
+        let mut relocations = Vec::with_capacity(section.relocations.len());
         for (addr, reloc) in section.relocations.iter() {
             let (r_offset, r_type) = reloc.to_elf(addr);
-            let r_sym = symbol_map[reloc.target_symbol as usize]
-                .ok_or_else(|| anyhow!("Relocation against stripped symbol"))?;
-            writer.write_relocation(true, &Rel { r_offset, r_sym, r_type, r_addend: reloc.addend });
+            let (r_sym, r_addend) = match symbol_map[reloc.target_symbol as usize] {
+                Some(r_sym) => (r_sym, reloc.addend),
+                None => {
+                    // Target the containing section symbol instead
+                    let symbol = &obj.symbols[reloc.target_symbol];
+                    let Some((r_sym, section)) = symbol
+                        .section
+                        .filter(|_| options.strip_locals)
+                        .and_then(|idx| Some((section_symbols[idx as usize]?, &obj.sections[idx])))
+                    else {
+                        bail!("Relocation against stripped symbol {}", symbol.name);
+                    };
+                    (r_sym, symbol.address as i64 - section.address as i64 + reloc.addend)
+                }
+            };
+            relocations.push(Rel { r_offset, r_sym, r_type, r_addend });
+        }
+        sort_relocations(&mut relocations);
+        for rel in &relocations {
+            writer.write_relocation(true, rel);
         }
     }
 
@@ -747,6 +1221,7 @@ pub fn write_elf(obj: &ObjInfo, export_all: bool) -> Result<Vec<u8>> {
     for out_symbol in &out_symbols {
         writer.write_symbol(&out_symbol.sym);
     }
+    writer.write_symtab_shndx();
 
     writer.write_strtab();
     writer.write_shstrtab();
@@ -779,12 +1254,10 @@ pub fn write_elf(obj: &ObjInfo, export_all: bool) -> Result<Vec<u8>> {
                     SHT_PROGBITS
                 }
                 ObjSectionKind::Bss => SHT_NOBITS,
+                ObjSectionKind::Note if section.name == ".comment" => SHT_PROGBITS,
+                ObjSectionKind::Note => SHT_NOTE,
             },
-            sh_flags: match section.kind {
-                ObjSectionKind::Code => SHF_ALLOC | SHF_EXECINSTR,
-                ObjSectionKind::Data | ObjSectionKind::Bss => SHF_ALLOC | SHF_WRITE,
-                ObjSectionKind::ReadOnlyData => SHF_ALLOC,
-            } as u64,
+            sh_flags: section.kind.flags() as u64,
             sh_addr: section.address,
             sh_offset: out_section.offset as u64,
             sh_size: section.size,
@@ -853,6 +1326,8 @@ pub fn write_elf(obj: &ObjInfo, export_all: bool) -> Result<Vec<u8>> {
         });
     }
 
+    writer.write_symtab_shndx_section_header();
+
     ensure!(writer.reserved_len() == writer.len());
     Ok(out_data)
 }
@@ -862,6 +1337,7 @@ fn to_obj_symbol(
     symbol: &Symbol<'_, '_>,
     section_indexes: &[Option<usize>],
     align: Option<u32>,
+    options: &ProcessElfOptions,
 ) -> Result<ObjSymbol> {
     let section = match symbol.section_index() {
         Some(idx) => Some(obj_file.section_by_index(idx)?),
@@ -869,13 +1345,23 @@ fn to_obj_symbol(
     };
     let name = match symbol.kind() {
         SymbolKind::Section => match &section {
-            Some(section) => section.name()?,
+            Some(section) => Cow::Borrowed(section.name()?),
             _ => bail!("Section symbol without section"),
         },
-        _ => symbol.name()?,
+        _ => symbol_name(symbol)?,
     };
-    ensure!(!name.is_empty(), "Empty symbol name");
     let mut flags = ObjSymbolFlagSet(ObjSymbolFlags::none());
+    let name = match &section {
+        Some(section)
+            if name.is_empty() && symbol.is_local() && options.synthesize_symbol_names =>
+        {
+            flags = ObjSymbolFlagSet(flags.0 | ObjSymbolFlags::Autogenerated);
+            let prefix = if section.kind() == SectionKind::Text { "lbl" } else { "data" };
+            Cow::Owned(format!("{}_{:08X}", prefix, symbol.address()))
+        }
+        _ => name,
+    };
+    ensure!(!name.is_empty(), "Empty symbol name");
     if symbol.is_global() {
         flags = ObjSymbolFlagSet(flags.0 | ObjSymbolFlags::Global);
     }
@@ -893,8 +1379,12 @@ fn to_obj_symbol(
     }
     let section_idx = section.as_ref().and_then(|section| section_indexes[section.index().0]);
     Ok(ObjSymbol {
-        name: name.to_string(),
-        demangled_name: demangle(name, &Default::default()),
+        // Names decoded from Shift-JIS aren't mangled by CodeWarrior, so skip demangling
+        demangled_name: match &name {
+            Cow::Borrowed(name) => demangle(name, &options.demangle_options),
+            Cow::Owned(_) => None,
+        },
+        name: name.into_owned(),
         address: symbol.address(),
         section: section_idx.map(|s| s as ObjSectionIndex),
         size: symbol.size(),
@@ -905,13 +1395,52 @@ fn to_obj_symbol(
             SymbolKind::Data => ObjSymbolKind::Object,
             SymbolKind::Unknown | SymbolKind::Label => ObjSymbolKind::Unknown,
             SymbolKind::Section => ObjSymbolKind::Section,
+            SymbolKind::Tls => ObjSymbolKind::Tls,
             _ => bail!("Unsupported symbol kind: {:?}", symbol),
         },
-        align,
+        // Common symbols carry their alignment in st_value
+        align: align
+            .or_else(|| symbol.is_common().then_some(symbol.address() as u32))
+            .or_else(|| section.as_ref().and_then(|section| infer_symbol_align(symbol, section))),
         ..Default::default()
     })
 }
 
+/// Infers the alignment of a data symbol from its offset within its section, limited to
+/// the alignment of the section itself.
+fn infer_symbol_align(symbol: &Symbol<'_, '_>, section: &object::Section<'_, '_>) -> Option<u32> {
+    if symbol.kind() != SymbolKind::Data {
+        return None;
+    }
+    let offset = symbol.address().checked_sub(section.address())?;
+    let section_align = section.align().max(1);
+    let align =
+        if offset == 0 { section_align } else { (1 << offset.trailing_zeros()).min(section_align) };
+    Some(align as u32)
+}
+
+/// Returns a symbol's name. Names that aren't valid UTF-8 (such as file symbols from
+/// Japanese CodeWarrior builds) are decoded as Shift-JIS when the `shift_jis` feature
+/// is enabled.
+fn symbol_name<'data>(symbol: &Symbol<'data, '_>) -> Result<Cow<'data, str>> {
+    let bytes = symbol.name_bytes()?;
+    match std::str::from_utf8(bytes) {
+        Ok(name) => Ok(Cow::Borrowed(name)),
+        Err(e) => decode_shift_jis(bytes).map(Cow::Owned).ok_or_else(|| {
+            anyhow!("Invalid symbol name '{}': {}", String::from_utf8_lossy(bytes), e)
+        }),
+    }
+}
+
+#[cfg(feature = "shift_jis")]
+fn decode_shift_jis(bytes: &[u8]) -> Option<String> {
+    let (name, had_errors) = encoding_rs::SHIFT_JIS.decode_without_bom_handling(bytes);
+    (!had_errors).then(|| name.into_owned())
+}
+
+#[cfg(not(feature = "shift_jis"))]
+fn decode_shift_jis(_bytes: &[u8]) -> Option<String> { None }
+
 pub fn to_obj_reloc_kind(flags: RelocationFlags) -> Result<ObjRelocKind> {
     Ok(match flags {
         RelocationFlags::Elf { r_type } => match r_type {
@@ -921,21 +1450,66 @@ pub fn to_obj_reloc_kind(flags: RelocationFlags) -> Result<ObjRelocKind> {
             elf::R_PPC_ADDR16_HA => ObjRelocKind::PpcAddr16Ha,
             elf::R_PPC_REL24 => ObjRelocKind::PpcRel24,
             elf::R_PPC_REL14 => ObjRelocKind::PpcRel14,
+            elf::R_PPC_REL14_BRTAKEN => ObjRelocKind::PpcRel14BrTaken,
+            elf::R_PPC_REL14_BRNTAKEN => ObjRelocKind::PpcRel14BrnTaken,
             elf::R_PPC_EMB_SDA21 => ObjRelocKind::PpcEmbSda21,
-            kind => bail!("Unhandled ELF relocation type: {kind}"),
+            elf::R_PPC_SDAREL16 => ObjRelocKind::PpcSdaRel16,
+            r_type => ObjRelocKind::Other(r_type),
         },
         flags => bail!("Unhandled relocation type: {:?}", flags),
     })
 }
 
+/// Resolves a relocation against a stripped symbol to the section symbol of the
+/// section containing it, returning the symbol's offset from the section start.
+fn stripped_reloc_target(
+    obj_file: &object::File<'_>,
+    section_symbols: &[Option<ObjSymbolIndex>],
+    symbol: &Symbol<'_, '_>,
+) -> Result<(ObjSymbolIndex, i64)> {
+    let section_index = symbol
+        .section_index()
+        .ok_or_else(|| anyhow!("Relocation against stripped symbol without section: {symbol:?}"))?;
+    let target_symbol =
+        section_symbols.get(section_index.0).and_then(|&idx| idx).ok_or_else(|| {
+            anyhow!("Relocation against stripped symbol in stripped section: {symbol:?}")
+        })?;
+    let section = obj_file.section_by_index(section_index)?;
+    Ok((target_symbol, symbol.address() as i64 - section.address() as i64))
+}
+
+/// Returns the names and version indices of dynamic symbols that have a version
+/// assigned in the `.gnu.version` section. Local and global (unversioned) entries
+/// are not included.
+fn versioned_dynamic_symbols(obj_file: &object::File<'_>) -> Result<Vec<(String, u16)>> {
+    let Some(versym_section) = obj_file.section_by_name(".gnu.version") else {
+        return Ok(vec![]);
+    };
+    let data = versym_section.data()?;
+    let mut out = vec![];
+    for symbol in obj_file.dynamic_symbols() {
+        let offset = symbol.index().0 * 2;
+        let versym = data
+            .get(offset..offset + 2)
+            .map(|b| u16::from_be_bytes([b[0], b[1]]))
+            .ok_or_else(|| anyhow!(".gnu.version section too small for symbol {:?}", symbol))?;
+        let version = versym & elf::VERSYM_VERSION;
+        if version > elf::VER_NDX_GLOBAL {
+            out.push((symbol.name()?.to_string(), version));
+        }
+    }
+    Ok(out)
+}
+
 fn to_obj_reloc(
     obj_file: &object::File<'_>,
     symbol_indexes: &[Option<ObjSymbolIndex>],
+    section_symbols: Option<&[Option<ObjSymbolIndex>]>,
     section_data: &[u8],
     address: u64,
+    reloc_kind: ObjRelocKind,
     reloc: Relocation,
 ) -> Result<Option<ObjReloc>> {
-    let reloc_kind = to_obj_reloc_kind(reloc.flags())?;
     let symbol = match reloc.target() {
         RelocationTarget::Symbol(idx) => {
             obj_file.symbol_by_index(idx).context("Failed to locate relocation target symbol")?
@@ -948,30 +1522,124 @@ fn to_obj_reloc(
             bail!("Unhandled relocation target: {:?} (address: {:#010X})", reloc.target(), address)
         }
     };
-    let target_symbol = symbol_indexes[symbol.index().0]
-        .ok_or_else(|| anyhow!("Relocation against stripped symbol: {symbol:?}"))?;
-    let addend = match symbol.kind() {
-        SymbolKind::Text | SymbolKind::Data | SymbolKind::Unknown | SymbolKind::Label => {
-            Ok(reloc.addend())
-        }
-        SymbolKind::Section => {
+    let target_symbol = match (symbol_indexes[symbol.index().0], section_symbols) {
+        (Some(target_symbol), _) => target_symbol,
+        (None, Some(section_symbols)) => {
+            let (target_symbol, offset) =
+                stripped_reloc_target(obj_file, section_symbols, &symbol)?;
             let addend = if reloc.has_implicit_addend() {
-                let addend = u32::from_be_bytes(
-                    section_data[address as usize..address as usize + 4].try_into()?,
-                ) as i64;
-                match reloc_kind {
-                    ObjRelocKind::Absolute => addend,
-                    _ => bail!("Unsupported implicit relocation type {reloc_kind:?}"),
-                }
+                implicit_addend(reloc_kind, section_data, address)?
             } else {
                 reloc.addend()
             };
+            log::debug!(
+                "Relocation at {:#010X} against stripped symbol {}, using section symbol + {:#X}",
+                address,
+                symbol.name().unwrap_or_default(),
+                offset
+            );
+            return Ok(Some(ObjReloc {
+                kind: reloc_kind,
+                target_symbol,
+                addend: offset + addend,
+                module: None,
+                pair: None,
+            }));
+        }
+        (None, None) => bail!("Relocation against stripped symbol: {symbol:?}"),
+    };
+    let addend = if reloc.has_implicit_addend() {
+        implicit_addend(reloc_kind, section_data, address)?
+    } else {
+        reloc.addend()
+    };
+    let addend = match symbol.kind() {
+        SymbolKind::Text
+        | SymbolKind::Data
+        | SymbolKind::Tls
+        | SymbolKind::Unknown
+        | SymbolKind::Label => Ok(addend),
+        SymbolKind::Section => {
             ensure!(addend >= 0, "Negative addend in section reloc: {addend}");
             Ok(addend)
         }
         _ => Err(anyhow!("Unhandled relocation symbol type {:?}", symbol.kind())),
     }?;
-    Ok(Some(ObjReloc { kind: reloc_kind, target_symbol, addend, module: None }))
+    Ok(Some(ObjReloc { kind: reloc_kind, target_symbol, addend, module: None, pair: None }))
+}
+
+/// Decodes the addend stored in the relocated field, for relocations from `.rel`
+/// sections. Only the upper half of `@h`/`@ha` addends can be recovered.
+fn implicit_addend(kind: ObjRelocKind, section_data: &[u8], address: u64) -> Result<i64> {
+    let start = address as usize;
+    let bytes = section_data.get(start..start + kind.width() as usize).ok_or_else(|| {
+        anyhow!("Relocation {:?} at {:#010X} is outside of section data", kind, address)
+    })?;
+    Ok(match kind {
+        ObjRelocKind::Absolute => u32::from_be_bytes(bytes.try_into()?) as i64,
+        ObjRelocKind::PpcAddr16Lo | ObjRelocKind::PpcSdaRel16 => {
+            i16::from_be_bytes(bytes.try_into()?) as i64
+        }
+        ObjRelocKind::PpcAddr16Hi | ObjRelocKind::PpcAddr16Ha => {
+            (i16::from_be_bytes(bytes.try_into()?) as i64) << 16
+        }
+        ObjRelocKind::PpcRel24 => {
+            let ins = u32::from_be_bytes(bytes.try_into()?);
+            (((ins & 0x3FFFFFC) << 6) as i32 >> 6) as i64
+        }
+        ObjRelocKind::PpcRel14 | ObjRelocKind::PpcRel14BrTaken | ObjRelocKind::PpcRel14BrnTaken => {
+            let ins = u32::from_be_bytes(bytes.try_into()?);
+            (ins & 0xFFFC) as i16 as i64
+        }
+        ObjRelocKind::PpcEmbSda21 => {
+            let ins = u32::from_be_bytes(bytes.try_into()?);
+            ins as i16 as i64
+        }
+        ObjRelocKind::Other(r_type) => {
+            bail!("Can't decode implicit addend of relocation type {} at {:#010X}", r_type, address)
+        }
+    })
+}
+
+/// ELF binding for a symbol. Weak symbols are never local, regardless of their scope.
+fn symbol_bind(symbol: &ObjSymbol) -> u8 {
+    if symbol.flags.is_weak() {
+        elf::STB_WEAK
+    } else if symbol.flags.is_local() {
+        elf::STB_LOCAL
+    } else {
+        elf::STB_GLOBAL
+    }
+}
+
+/// Zero-size sections don't occupy any memory, and `.comment` isn't loaded,
+/// so neither gets a segment.
+fn has_program_header(section: &ObjSection) -> bool {
+    section.size > 0 && !(section.kind == ObjSectionKind::Note && section.name == ".comment")
+}
+
+/// Sorts relocations by their final offset, since to_elf can move r_offset past the
+/// following relocation's. Entries stored at exact offsets by
+/// [`ObjRelocations::replace`](crate::obj::ObjRelocations::replace) can share an offset;
+/// those are ordered canonically, otherwise by address.
+fn sort_relocations(relocations: &mut [Rel]) {
+    relocations.sort_by_key(|rel| (rel.r_offset, same_offset_order(rel.r_type)));
+}
+
+/// Orders relocations sharing an offset, placing `@ha`/`@h` before `@l` as linkers expect.
+fn same_offset_order(r_type: u32) -> u8 {
+    match r_type {
+        elf::R_PPC_ADDR16_HA | elf::R_PPC_ADDR16_HI => 0,
+        elf::R_PPC_ADDR16_LO => 1,
+        _ => 2,
+    }
+}
+
+/// Pads the output up to `offset` with a repeating big-endian word.
+fn write_fill(w: &mut Writer, offset: usize, fill: u32) {
+    let fill = fill.to_be_bytes();
+    let padding = (w.len()..offset).map(|pos| fill[pos % 4]).collect::<Vec<_>>();
+    w.write(&padding);
 }
 
 /// Writes section data while zeroing out relocations.
@@ -979,25 +1647,22 @@ fn write_relocatable_section_data(w: &mut Writer, section: &ObjSection) -> Resul
     ensure!(section.address == 0);
     let mut current_address = 0;
     for (addr, reloc) in section.relocations.iter() {
+        let mask = match reloc.kind {
+            ObjRelocKind::Absolute => 0xFFFFFFFF,
+            ObjRelocKind::PpcAddr16Hi
+            | ObjRelocKind::PpcAddr16Ha
+            | ObjRelocKind::PpcAddr16Lo
+            | ObjRelocKind::PpcSdaRel16 => 0xFFFF,
+            ObjRelocKind::PpcRel24 => 0x3FFFFFC,
+            ObjRelocKind::PpcRel14
+            | ObjRelocKind::PpcRel14BrTaken
+            | ObjRelocKind::PpcRel14BrnTaken => 0xFFFC,
+            ObjRelocKind::PpcEmbSda21 => 0x1FFFFF,
+            // The patched field is unknown, so leave the data as-is
+            ObjRelocKind::Other(_) => continue,
+        };
         w.write(&section.data[current_address..addr as usize]);
-        let mut ins = u32::from_be_bytes(*array_ref!(section.data, addr as usize, 4));
-        match reloc.kind {
-            ObjRelocKind::Absolute => {
-                ins = 0;
-            }
-            ObjRelocKind::PpcAddr16Hi | ObjRelocKind::PpcAddr16Ha | ObjRelocKind::PpcAddr16Lo => {
-                ins &= !0xFFFF;
-            }
-            ObjRelocKind::PpcRel24 => {
-                ins &= !0x3FFFFFC;
-            }
-            ObjRelocKind::PpcRel14 => {
-                ins &= !0xFFFC;
-            }
-            ObjRelocKind::PpcEmbSda21 => {
-                ins &= !0x1FFFFF;
-            }
-        }
+        let ins = u32::from_be_bytes(*array_ref!(section.data, addr as usize, 4)) & !mask;
         w.write(&ins.to_be_bytes());
         current_address = addr as usize + 4;
     }
@@ -1005,3 +1670,1289 @@ fn write_relocatable_section_data(w: &mut Writer, section: &ObjSection) -> Resul
     w.write(&section.data[current_address..]);
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use object::{
+        write::{
+            Object as WriteObject, Relocation as WriteRelocation, SectionId, Symbol as WriteSymbol,
+            SymbolId,
+        },
+        BinaryFormat, ObjectSegment, SymbolFlags,
+    };
+
+    use super::*;
+    use crate::obj::fixtures::{test_obj, test_reloc, test_section, test_symbol};
+
+    /// Starts a relocatable object with a `.text` section containing `text`.
+    fn new_elf(text: &[u8]) -> (WriteObject<'static>, SectionId) {
+        let mut obj = WriteObject::new(BinaryFormat::Elf, Architecture::PowerPc, Endianness::Big);
+        let section = obj.add_section(vec![], b".text".to_vec(), SectionKind::Text);
+        obj.append_section_data(section, text, 4);
+        (obj, section)
+    }
+
+    /// Adds a symbol to an object built with [`new_elf`]. `None` makes it undefined.
+    fn add_elf_symbol(
+        obj: &mut WriteObject,
+        name: &[u8],
+        section: Option<SectionId>,
+        value: u64,
+        size: u64,
+        kind: SymbolKind,
+        scope: SymbolScope,
+    ) -> SymbolId {
+        obj.add_symbol(WriteSymbol {
+            name: name.to_vec(),
+            value,
+            size,
+            kind,
+            scope,
+            weak: false,
+            section: match section {
+                Some(section) => object::write::SymbolSection::Section(section),
+                None => object::write::SymbolSection::Undefined,
+            },
+            flags: SymbolFlags::None,
+        })
+    }
+
+    /// Adds a relocation with an explicit addend to an object built with [`new_elf`].
+    fn add_elf_reloc(
+        obj: &mut WriteObject,
+        section: SectionId,
+        offset: u64,
+        symbol: SymbolId,
+        addend: i64,
+        r_type: u32,
+    ) {
+        obj.add_relocation(section, WriteRelocation {
+            offset,
+            symbol,
+            addend,
+            flags: RelocationFlags::Elf { r_type },
+        })
+        .unwrap();
+    }
+
+    /// Builds a relocatable object with a `.text` section containing a local symbol at
+    /// offset 8, and an absolute relocation against it at offset 0.
+    fn build_reloc_elf() -> Vec<u8> {
+        let (mut obj, text) = new_elf(&[0u8; 16]);
+        obj.section_symbol(text);
+        let local = add_elf_symbol(
+            &mut obj,
+            b"stripped_local",
+            Some(text),
+            8,
+            4,
+            SymbolKind::Data,
+            SymbolScope::Compilation,
+        );
+        add_elf_reloc(&mut obj, text, 0, local, 0, elf::R_PPC_ADDR32);
+        obj.write().unwrap()
+    }
+
+    #[test]
+    fn test_stripped_local_reloc() {
+        let (mut obj, text) = new_elf(&[0u8; 16]);
+        let data = obj.add_section(vec![], b".data".to_vec(), SectionKind::Data);
+        obj.append_section_data(data, &[0u8; 4], 4);
+        let local = add_elf_symbol(
+            &mut obj,
+            b"",
+            Some(text),
+            8,
+            4,
+            SymbolKind::Data,
+            SymbolScope::Compilation,
+        );
+        add_elf_reloc(&mut obj, text, 0, local, 0, elf::R_PPC_ADDR32);
+        let data = obj.write().unwrap();
+        assert!(parse_elf(&data, &ProcessElfOptions::default(), None).is_err());
+
+        let options = ProcessElfOptions { lenient_relocations: true, ..Default::default() };
+        let obj = parse_elf(&data, &options, None).unwrap();
+        let reloc = obj.sections[0].relocations.at(0).unwrap();
+        let target = &obj.symbols[reloc.target_symbol];
+        assert_eq!(target.kind, ObjSymbolKind::Section);
+        assert_eq!(target.name, ".text");
+        assert_eq!(reloc.addend, 8);
+        // No section symbol is created for .data, which has no stripped targets
+        assert_eq!(obj.symbols.iter().filter(|(_, s)| s.kind == ObjSymbolKind::Section).count(), 1);
+    }
+
+    #[test]
+    fn test_bss_file_offset() {
+        let (mut obj, _) = new_elf(&[0u8; 16]);
+        let bss = obj.add_section(vec![], b".bss".to_vec(), SectionKind::UninitializedData);
+        obj.append_section_bss(bss, 0x20, 8);
+        let sbss = obj.add_section(vec![], b".sbss".to_vec(), SectionKind::UninitializedData);
+        obj.append_section_bss(sbss, 0x10, 8);
+        let data = obj.write().unwrap();
+
+        let obj = parse_elf(&data, &ProcessElfOptions::default(), None).unwrap();
+        let (_, text) = obj.sections.by_name(".text").unwrap().unwrap();
+        let (_, bss) = obj.sections.by_name(".bss").unwrap().unwrap();
+        let (_, sbss) = obj.sections.by_name(".sbss").unwrap().unwrap();
+        assert_eq!(bss.file_offset, text.file_offset + text.size);
+        assert_eq!(sbss.file_offset, bss.file_offset + bss.size);
+    }
+
+    #[test]
+    fn test_common_symbol() {
+        let mut obj = parse_elf(&build_reloc_elf(), &ProcessElfOptions::default(), None).unwrap();
+        obj.add_symbol(
+            ObjSymbol {
+                name: "common_sym".to_string(),
+                size: 0x20,
+                size_known: true,
+                flags: ObjSymbolFlagSet(ObjSymbolFlags::Global | ObjSymbolFlags::Common),
+                kind: ObjSymbolKind::Object,
+                align: Some(8),
+                ..Default::default()
+            },
+            false,
+        )
+        .unwrap();
+        let data = write_elf(&obj, false).unwrap();
+
+        let obj_file = object::read::File::parse(&*data).unwrap();
+        let symbol = obj_file.symbol_by_name("common_sym").unwrap();
+        assert!(symbol.is_common());
+        assert_eq!(symbol.address(), 8);
+        assert_eq!(symbol.size(), 0x20);
+
+        let obj = parse_elf(&data, &ProcessElfOptions::default(), None).unwrap();
+        let (_, symbol) = obj.symbols.by_name("common_sym").unwrap().unwrap();
+        assert!(symbol.flags.is_common());
+        assert_eq!(symbol.section, None);
+        assert_eq!(symbol.align, Some(8));
+        assert_eq!(symbol.size, 0x20);
+    }
+
+    #[test]
+    fn test_skip_section_data() {
+        let data = build_reloc_elf();
+        let mut obj = parse_elf(
+            &data,
+            &ProcessElfOptions { skip_section_data: true, ..Default::default() },
+            None,
+        )
+        .unwrap();
+        assert!(obj.data_stripped);
+        let text = &obj.sections[0];
+        assert_eq!(text.size, 16);
+        assert_eq!(text.data.capacity(), 0);
+        assert_eq!(text.relocations.len(), 1);
+
+        load_section_data(&data, &mut obj, 0).unwrap();
+        assert_eq!(obj.sections[0].data, vec![0u8; 16]);
+        assert!(!obj.data_stripped);
+    }
+
+    #[test]
+    fn test_load_section_data_matches_eager() {
+        let obj = test_obj(ObjKind::Relocatable, vec![], vec![
+            test_section(".text", ObjSectionKind::Code, 0, vec![0x4E, 0x80, 0x00, 0x20]),
+            test_section(".data", ObjSectionKind::Data, 0, (0..24).collect()),
+            test_section(".bss", ObjSectionKind::Bss, 0, vec![0; 8]),
+        ]);
+        let data = write_elf(&obj, false).unwrap();
+        let eager = parse_elf(&data, &ProcessElfOptions::default(), None).unwrap();
+        let options = ProcessElfOptions { skip_section_data: true, ..Default::default() };
+        let mut lazy = parse_elf(&data, &options, None).unwrap();
+        for (section_index, section) in eager.sections.iter() {
+            assert!(section.kind.is_bss() || lazy.sections[section_index].data.is_empty());
+            load_section_data(&data, &mut lazy, section_index).unwrap();
+            assert_eq!(lazy.sections[section_index].data, section.data, "{}", section.name);
+        }
+        assert!(!lazy.data_stripped);
+
+        // Offsets past the end of the file are rejected
+        let mut lazy = parse_elf(&data, &options, None).unwrap();
+        lazy.sections[1].file_offset = data.len() as u64;
+        assert!(load_section_data(&data, &mut lazy, 1).is_err());
+    }
+
+    #[test]
+    fn test_write_skipped_section_data() {
+        let data = build_reloc_elf();
+        let options = ProcessElfOptions { skip_section_data: true, ..Default::default() };
+        let mut obj = parse_elf(&data, &options, None).unwrap();
+        let err = write_elf(&obj, false).unwrap_err();
+        assert!(err.to_string().contains("with stripped section data"), "{err}");
+
+        load_section_data(&data, &mut obj, 0).unwrap();
+        let out = write_elf(&obj, false).unwrap();
+        let obj_file = object::read::File::parse(&*out).unwrap();
+        assert_eq!(obj_file.section_by_name(".text").unwrap().data().unwrap(), [0u8; 16]);
+    }
+
+    #[test]
+    fn test_skip_relocations() {
+        let data = build_reloc_elf();
+        let obj = parse_elf(
+            &data,
+            &ProcessElfOptions { parse_relocations: false, ..Default::default() },
+            None,
+        )
+        .unwrap();
+        assert!(obj.sections.iter().all(|(_, s)| s.relocations.is_empty()));
+        assert!(obj.symbols.by_name("stripped_local").unwrap().is_some());
+
+        // The written object has no relocation sections
+        let out = write_elf(&obj, false).unwrap();
+        let obj_file = object::read::File::parse(&*out).unwrap();
+        assert!(obj_file.sections().all(|s| s.relocations().next().is_none()));
+    }
+
+    #[test]
+    fn test_rename_duplicate_sections() {
+        let (mut obj, _) = new_elf(&[0u8; 16]);
+        let text2 = obj.add_section(vec![], b".text".to_vec(), SectionKind::Text);
+        obj.append_section_data(text2, &[0u8; 8], 4);
+        let data = obj.write().unwrap();
+
+        let obj = parse_elf(&data, &ProcessElfOptions::default(), None).unwrap();
+        assert_eq!(obj.sections[0].name, ".text");
+        assert_eq!(obj.sections[1].name, ".text");
+
+        let obj = parse_elf(
+            &data,
+            &ProcessElfOptions { rename_duplicate_sections: true, ..Default::default() },
+            None,
+        )
+        .unwrap();
+        let (_, text) = obj.sections.by_name(".text").unwrap().unwrap();
+        let (_, text_1) = obj.sections.by_name(".text.1").unwrap().unwrap();
+        assert_eq!(text.size, 16);
+        assert_eq!(text_1.size, 8);
+        assert_ne!(text.elf_index, text_1.elf_index);
+    }
+
+    #[test]
+    fn test_relocatable_entry() {
+        let mut obj = parse_elf(&build_reloc_elf(), &ProcessElfOptions::default(), None).unwrap();
+        assert_eq!(obj.kind, ObjKind::Relocatable);
+        assert_eq!(obj.entry, None);
+
+        obj.entry = Some(0x80003100);
+        let data = write_elf(&obj, false).unwrap();
+        let obj_file = object::read::File::parse(&*data).unwrap();
+        assert_eq!(obj_file.kind(), ObjectKind::Relocatable);
+        assert_eq!(obj_file.entry(), 0);
+    }
+
+    #[test]
+    fn test_versioned_dynamic_symbols() {
+        let mut data = vec![];
+        let mut writer = Writer::new(Endianness::Big, false, &mut data);
+        writer.reserve_file_header();
+        let unversioned = writer.add_dynamic_string(b"unversioned");
+        let versioned = writer.add_dynamic_string(b"versioned");
+        writer.reserve_null_dynamic_symbol_index();
+        writer.reserve_dynamic_symbol_index();
+        writer.reserve_dynamic_symbol_index();
+        writer.reserve_dynsym_section_index();
+        writer.reserve_dynstr_section_index();
+        writer.reserve_gnu_versym_section_index();
+        writer.reserve_shstrtab_section_index();
+        writer.reserve_dynsym();
+        writer.reserve_dynstr();
+        writer.reserve_gnu_versym();
+        writer.reserve_shstrtab();
+        writer.reserve_section_headers();
+
+        writer
+            .write_file_header(&object::write::elf::FileHeader {
+                os_abi: elf::ELFOSABI_SYSV,
+                abi_version: 0,
+                e_type: elf::ET_EXEC,
+                e_machine: elf::EM_PPC,
+                e_entry: 0,
+                e_flags: 0,
+            })
+            .unwrap();
+        writer.write_null_dynamic_symbol();
+        for (name, address) in [(unversioned, 0x80003100), (versioned, 0x80003110)] {
+            writer.write_dynamic_symbol(&object::write::elf::Sym {
+                name: Some(name),
+                section: None,
+                st_info: (elf::STB_GLOBAL << 4) + elf::STT_FUNC,
+                st_other: elf::STV_DEFAULT,
+                st_shndx: elf::SHN_ABS,
+                st_value: address,
+                st_size: 0x10,
+            });
+        }
+        writer.write_dynstr();
+        writer.write_null_gnu_versym();
+        writer.write_gnu_versym(elf::VER_NDX_GLOBAL);
+        writer.write_gnu_versym(2);
+        writer.write_shstrtab();
+        writer.write_null_section_header();
+        writer.write_dynsym_section_header(0, 1);
+        writer.write_dynstr_section_header(0);
+        writer.write_gnu_versym_section_header(0);
+        writer.write_shstrtab_section_header();
+
+        let obj_file = object::read::File::parse(&*data).unwrap();
+        assert_eq!(versioned_dynamic_symbols(&obj_file).unwrap(), vec![(
+            "versioned".to_string(),
+            2
+        )]);
+    }
+
+    #[test]
+    fn test_dynsym_only() {
+        let mut data = vec![];
+        let mut writer = Writer::new(Endianness::Big, false, &mut data);
+        writer.reserve_file_header();
+        let text_name = writer.add_section_name(b".text");
+        let text_index = writer.reserve_section_index();
+        let func = writer.add_dynamic_string(b"func");
+        writer.reserve_null_dynamic_symbol_index();
+        writer.reserve_dynamic_symbol_index();
+        writer.reserve_dynsym_section_index();
+        writer.reserve_dynstr_section_index();
+        writer.reserve_shstrtab_section_index();
+        let text_offset = writer.reserve(8, 4);
+        writer.reserve_dynsym();
+        writer.reserve_dynstr();
+        writer.reserve_shstrtab();
+        writer.reserve_section_headers();
+
+        writer
+            .write_file_header(&object::write::elf::FileHeader {
+                os_abi: elf::ELFOSABI_SYSV,
+                abi_version: 0,
+                e_type: elf::ET_EXEC,
+                e_machine: elf::EM_PPC,
+                e_entry: 0x80003100,
+                e_flags: 0,
+            })
+            .unwrap();
+        writer.write_align(4);
+        writer.write(&[0x38, 0x60, 0x00, 0x00, 0x4E, 0x80, 0x00, 0x20]);
+        writer.write_null_dynamic_symbol();
+        writer.write_dynamic_symbol(&object::write::elf::Sym {
+            name: Some(func),
+            section: Some(text_index),
+            st_info: (elf::STB_GLOBAL << 4) + elf::STT_FUNC,
+            st_other: elf::STV_DEFAULT,
+            st_shndx: 0,
+            st_value: 0x80003100,
+            st_size: 8,
+        });
+        writer.write_dynstr();
+        writer.write_shstrtab();
+        writer.write_null_section_header();
+        writer.write_section_header(&SectionHeader {
+            name: Some(text_name),
+            sh_type: SHT_PROGBITS,
+            sh_flags: ObjSectionKind::Code.flags() as u64,
+            sh_addr: 0x80003100,
+            sh_offset: text_offset as u64,
+            sh_size: 8,
+            sh_link: 0,
+            sh_info: 0,
+            sh_addralign: 4,
+            sh_entsize: 0,
+        });
+        writer.write_dynsym_section_header(0, 1);
+        writer.write_dynstr_section_header(0);
+        writer.write_shstrtab_section_header();
+
+        let obj = process_elf_bytes(&data, "").unwrap();
+        let (_, func) = obj.symbols.by_name("func").unwrap().unwrap();
+        assert_eq!(func.kind, ObjSymbolKind::Function);
+        assert_eq!((func.section, func.address, func.size), (Some(0), 0x80003100, 8));
+    }
+
+    #[test]
+    fn test_skip_unknown_relocations() {
+        let (mut obj, text) = new_elf(&[0u8; 16]);
+        let target = add_elf_symbol(
+            &mut obj,
+            b"target",
+            Some(text),
+            0,
+            4,
+            SymbolKind::Text,
+            SymbolScope::Dynamic,
+        );
+        let relocations = [
+            (0, elf::R_PPC_ADDR32),
+            (6, elf::R_PPC_ADDR16),
+            (0xA, elf::R_PPC_ADDR16),
+            (0xC, elf::R_PPC_REL32),
+        ];
+        for (offset, r_type) in relocations {
+            add_elf_reloc(&mut obj, text, offset, target, 0, r_type);
+        }
+        let data = obj.write().unwrap();
+
+        let obj = process_elf_bytes(&data, "").unwrap();
+        let (_, text) = obj.sections.by_name(".text").unwrap().unwrap();
+        assert_eq!(text.relocations.len(), 4);
+        assert_eq!(text.relocations.at(0xC).unwrap().kind, ObjRelocKind::Other(elf::R_PPC_REL32));
+
+        let options = ProcessElfOptions { skip_unknown_relocations: true, ..Default::default() };
+        let obj = parse_elf(&data, &options, None).unwrap();
+        let (_, text) = obj.sections.by_name(".text").unwrap().unwrap();
+        assert_eq!(text.relocations.len(), 1);
+        assert_eq!(text.relocations.at(0).unwrap().kind, ObjRelocKind::Absolute);
+        assert_eq!(
+            obj.skipped_relocations,
+            BTreeMap::from([(elf::R_PPC_ADDR16, 2), (elf::R_PPC_REL32, 1)])
+        );
+    }
+
+    #[test]
+    fn test_other_relocation_round_trip() {
+        let (mut obj, text) = new_elf(&[0x4E, 0x80, 0x00, 0x20, 0x80, 0x62, 0x00, 0x00]);
+        let target = add_elf_symbol(
+            &mut obj,
+            b"target",
+            None,
+            0,
+            0,
+            SymbolKind::Unknown,
+            SymbolScope::Dynamic,
+        );
+        // A halfword relocation in the last word of the section
+        add_elf_reloc(&mut obj, text, 6, target, 4, elf::R_PPC_TOC16);
+        let obj = process_elf_bytes(&obj.write().unwrap(), "").unwrap();
+        let (_, text) = obj.sections.by_name(".text").unwrap().unwrap();
+        assert_eq!(text.relocations.at(4).unwrap().kind, ObjRelocKind::Other(elf::R_PPC_TOC16));
+        obj.validate_relocations().unwrap();
+
+        let data = write_elf(&obj, false).unwrap();
+        let obj_file = object::read::File::parse(&*data).unwrap();
+        let text = obj_file.section_by_name(".text").unwrap();
+        assert_eq!(text.data().unwrap(), [0x4E, 0x80, 0x00, 0x20, 0x80, 0x62, 0x00, 0x00]);
+        let relocations = text.relocations().collect::<Vec<_>>();
+        assert_eq!(relocations.len(), 1);
+        let (address, reloc) = &relocations[0];
+        assert_eq!(*address, 6);
+        assert_eq!(reloc.flags(), RelocationFlags::Elf { r_type: elf::R_PPC_TOC16 });
+        assert_eq!(reloc.addend(), 4);
+    }
+
+    #[test]
+    fn test_unwind_table_round_trip() {
+        let (mut obj, text) = new_elf(&[0x4E, 0x80, 0x00, 0x20]);
+        let text_symbol = obj.section_symbol(text);
+        let eh_frame = obj.add_section(vec![], b".eh_frame".to_vec(), SectionKind::Other);
+        obj.append_section_data(eh_frame, &[0x00, 0x00, 0x00, 0x10, 0xFF, 0xFF, 0xFF, 0xF0], 4);
+        add_elf_reloc(&mut obj, eh_frame, 4, text_symbol, 0, elf::R_PPC_REL32);
+        let data = obj.write().unwrap();
+
+        let obj = process_elf_bytes(&data, "").unwrap();
+        let (_, section) = obj.sections.by_name(".eh_frame").unwrap().unwrap();
+        assert_eq!(section.kind, ObjSectionKind::ReadOnlyData);
+        assert_eq!(section.relocations.at(4).unwrap().kind, ObjRelocKind::Other(elf::R_PPC_REL32));
+
+        let data = write_elf(&obj, false).unwrap();
+        let obj_file = object::read::File::parse(&*data).unwrap();
+        let eh_frame = obj_file.section_by_name(".eh_frame").unwrap();
+        assert_eq!(eh_frame.data().unwrap(), [0x00, 0x00, 0x00, 0x10, 0xFF, 0xFF, 0xFF, 0xF0]);
+        let relocations = eh_frame.relocations().collect::<Vec<_>>();
+        assert_eq!(relocations.len(), 1);
+        let (address, reloc) = &relocations[0];
+        assert_eq!(*address, 4);
+        assert_eq!(reloc.flags(), RelocationFlags::Elf { r_type: elf::R_PPC_REL32 });
+        let RelocationTarget::Symbol(idx) = reloc.target() else { panic!() };
+        let target = obj_file.symbol_by_index(idx).unwrap();
+        assert_eq!(target.section_index(), obj_file.section_by_name(".text").map(|s| s.index()));
+    }
+
+    #[test]
+    fn test_infer_symbol_align() {
+        let mut obj = WriteObject::new(BinaryFormat::Elf, Architecture::PowerPc, Endianness::Big);
+        let data = obj.add_section(vec![], b".data".to_vec(), SectionKind::Data);
+        obj.append_section_data(data, &[0u8; 0x20], 16);
+        for (name, value) in [("first", 0x0), ("aligned", 0x10), ("word", 0x14), ("byte", 0x19)] {
+            add_elf_symbol(
+                &mut obj,
+                name.as_bytes(),
+                Some(data),
+                value,
+                1,
+                SymbolKind::Data,
+                SymbolScope::Dynamic,
+            );
+        }
+        let obj = process_elf_bytes(&obj.write().unwrap(), "").unwrap();
+        let align = |name: &str| obj.symbols.by_name(name).unwrap().unwrap().1.align;
+        assert_eq!(align("first"), Some(16));
+        assert_eq!(align("aligned"), Some(16));
+        assert_eq!(align("word"), Some(4));
+        assert_eq!(align("byte"), Some(1));
+    }
+
+    #[test]
+    fn test_process_elf_bytes() {
+        let data: Vec<u8> = build_reloc_elf();
+        let obj = process_elf_bytes(&data, "").unwrap();
+        assert_eq!(obj.kind, ObjKind::Relocatable);
+        let (_, text) = obj.sections.by_name(".text").unwrap().unwrap();
+        assert_eq!(text.data, vec![0u8; 16]);
+        assert_eq!(text.relocations.len(), 1);
+        assert!(process_elf_bytes(&data[..0x10], "").is_err());
+    }
+
+    #[test]
+    fn test_shift_jis_symbol_names() {
+        // "テスト" in Shift-JIS
+        let name = b"\x83\x65\x83\x58\x83\x67";
+        let (mut obj, text) = new_elf(&[0u8; 4]);
+        obj.add_file_symbol([name.as_slice(), b".c"].concat());
+        add_elf_symbol(
+            &mut obj,
+            &[name.as_slice(), b"__Fv"].concat(),
+            Some(text),
+            0,
+            4,
+            SymbolKind::Text,
+            SymbolScope::Dynamic,
+        );
+        let data = obj.write().unwrap();
+
+        let result = parse_elf(&data, &ProcessElfOptions::default(), None);
+        if cfg!(feature = "shift_jis") {
+            let obj = result.unwrap();
+            assert_eq!(obj.name, "テスト.c");
+            let (_, symbol) = obj.symbols.by_name("テスト__Fv").unwrap().unwrap();
+            assert_eq!(symbol.demangled_name, None);
+        } else {
+            assert!(result.is_err());
+        }
+    }
+
+    #[test]
+    fn test_demangle_options() {
+        let (mut obj, text) = new_elf(&[0x4E, 0x80, 0x00, 0x20]);
+        add_elf_symbol(
+            &mut obj,
+            b"func__Fv",
+            Some(text),
+            0,
+            4,
+            SymbolKind::Text,
+            SymbolScope::Dynamic,
+        );
+        let data = obj.write().unwrap();
+
+        let demangled_name = |options: &ProcessElfOptions| {
+            let obj = parse_elf(&data, options, None).unwrap();
+            let (_, symbol) = obj.symbols.by_name("func__Fv").unwrap().unwrap();
+            symbol.demangled_name.clone()
+        };
+        assert_eq!(demangled_name(&ProcessElfOptions::default()).as_deref(), Some("func()"));
+        let options = ProcessElfOptions {
+            demangle_options: DemangleOptions {
+                omit_empty_parameters: false,
+                mw_extensions: false,
+            },
+            ..Default::default()
+        };
+        assert_eq!(demangled_name(&options).as_deref(), Some("func(void)"));
+    }
+
+    #[test]
+    fn test_default_unit() {
+        let (mut obj, text) = new_elf(&[0x4E, 0x80, 0x00, 0x20]);
+        let data = obj.add_section(vec![], b".data".to_vec(), SectionKind::Data);
+        obj.append_section_data(data, &[0; 8], 4);
+        add_elf_symbol(&mut obj, b"func", Some(text), 0, 4, SymbolKind::Text, SymbolScope::Dynamic);
+        let data = obj.write().unwrap();
+
+        let mut obj = parse_elf(&data, &ProcessElfOptions::default(), None).unwrap();
+        assert!(obj.name.is_empty());
+        add_default_unit(&mut obj, "boot.s");
+        assert_eq!(obj.name, "boot.s");
+        assert_eq!(obj.link_order.iter().map(|u| u.name.as_str()).collect::<Vec<_>>(), vec![
+            "boot.s"
+        ]);
+        for (_, section) in obj.sections.iter() {
+            let (addr, split) = section.splits.for_address(0).unwrap();
+            assert_eq!((addr, split.end, split.unit.as_str()), (0, section.size as u32, "boot.s"));
+        }
+
+        // Objects with a file symbol are left alone
+        let (mut obj, _) = new_elf(&[0x4E, 0x80, 0x00, 0x20]);
+        obj.add_file_symbol(b"main.c".to_vec());
+        let obj = process_elf_bytes(&obj.write().unwrap(), "boot.s").unwrap();
+        assert_eq!(obj.name, "main.c");
+        assert!(obj.link_order.is_empty());
+    }
+
+    #[test]
+    fn test_section_start_at_zero() {
+        let (mut obj, text) = new_elf(&[0u8; 16]);
+        obj.add_file_symbol(b"a.c".to_vec());
+        obj.section_symbol(text);
+        add_elf_symbol(
+            &mut obj,
+            b"func",
+            Some(text),
+            8,
+            8,
+            SymbolKind::Text,
+            SymbolScope::Compilation,
+        );
+        let mut data = obj.write().unwrap();
+        // Mark as executable, leaving .text at address 0
+        data[16..18].copy_from_slice(&elf::ET_EXEC.to_be_bytes());
+
+        let obj = parse_elf(&data, &ProcessElfOptions::default(), None).unwrap();
+        assert_eq!(obj.kind, ObjKind::Executable);
+        let (_, text) = obj.sections.by_name(".text").unwrap().unwrap();
+        let splits = text.splits.iter().map(|(addr, split)| (addr, split.unit.as_str()));
+        assert_eq!(splits.collect::<Vec<_>>(), vec![(0, "a.c")]);
+    }
+
+    #[test]
+    fn test_sdarel16_round_trip() {
+        // lwz r3, value@sdarel(r13)
+        let (mut obj, text) = new_elf(&[0x80, 0x6D, 0x00, 0x00, 0x4E, 0x80, 0x00, 0x20]);
+        let sdata = obj.add_section(vec![], b".sdata".to_vec(), SectionKind::Data);
+        obj.append_section_data(sdata, &[0u8; 8], 4);
+        let value = add_elf_symbol(
+            &mut obj,
+            b"value",
+            Some(sdata),
+            4,
+            4,
+            SymbolKind::Data,
+            SymbolScope::Dynamic,
+        );
+        add_elf_reloc(&mut obj, text, 2, value, 0, elf::R_PPC_SDAREL16);
+        let data = obj.write().unwrap();
+
+        let read = |data: &[u8]| {
+            let obj = parse_elf(data, &ProcessElfOptions::default(), None).unwrap();
+            let (_, text) = obj.sections.by_name(".text").unwrap().unwrap();
+            let reloc = text.relocations.at(2).unwrap();
+            assert_eq!(reloc.kind, ObjRelocKind::PpcSdaRel16);
+            assert_eq!(reloc.to_elf(0), (2, elf::R_PPC_SDAREL16));
+            assert_eq!(obj.symbols[reloc.target_symbol].name, "value");
+            obj
+        };
+        let obj = read(&data);
+        read(&write_elf(&obj, false).unwrap());
+    }
+
+    #[test]
+    fn test_strip_locals() {
+        let obj = parse_elf(&build_reloc_elf(), &ProcessElfOptions::default(), None).unwrap();
+        let options = WriteElfOptions { strip_locals: true, ..Default::default() };
+        let data = write_elf_with_options(&obj, false, &options).unwrap();
+
+        let obj = parse_elf(&data, &ProcessElfOptions::default(), None).unwrap();
+        assert!(obj.symbols.by_name("stripped_local").unwrap().is_none());
+        let (_, text) = obj.sections.by_name(".text").unwrap().unwrap();
+        let reloc = text.relocations.at(0).unwrap();
+        let target = &obj.symbols[reloc.target_symbol];
+        assert_eq!(target.kind, ObjSymbolKind::Section);
+        assert_eq!(target.name, ".text");
+        assert_eq!(reloc.addend, 8);
+    }
+
+    #[test]
+    fn test_undefined_reloc_target() {
+        let (mut obj, text) = new_elf(&[0u8; 8]);
+        add_elf_symbol(
+            &mut obj,
+            b"local",
+            Some(text),
+            0,
+            4,
+            SymbolKind::Text,
+            SymbolScope::Compilation,
+        );
+        let import = add_elf_symbol(
+            &mut obj,
+            b"import",
+            None,
+            0,
+            0,
+            SymbolKind::Unknown,
+            SymbolScope::Dynamic,
+        );
+        add_elf_reloc(&mut obj, text, 4, import, 0, elf::R_PPC_REL24);
+        let obj = parse_elf(&obj.write().unwrap(), &ProcessElfOptions::default(), None).unwrap();
+
+        for strip_locals in [false, true] {
+            let options = WriteElfOptions { strip_locals, ..Default::default() };
+            let data = write_elf_with_options(&obj, false, &options).unwrap();
+            let obj_file = object::read::File::parse(&*data).unwrap();
+            let (_, reloc) =
+                obj_file.section_by_name(".text").unwrap().relocations().next().unwrap();
+            let RelocationTarget::Symbol(idx) = reloc.target() else { panic!() };
+            let symbol = obj_file.symbol_by_index(idx).unwrap();
+            assert_eq!(symbol.name(), Ok("import"));
+            assert!(symbol.is_undefined());
+
+            let obj = parse_elf(&data, &ProcessElfOptions::default(), None).unwrap();
+            let (_, text) = obj.sections.by_name(".text").unwrap().unwrap();
+            let target = &obj.symbols[text.relocations.at(4).unwrap().target_symbol];
+            assert_eq!(target.name, "import");
+            assert_eq!(target.section, None);
+        }
+    }
+
+    #[test]
+    fn test_same_offset_relocation_order() {
+        let rel = |r_offset: u64, r_type: u32| Rel { r_offset, r_sym: 1, r_type, r_addend: 0 };
+        let mut relocations = vec![
+            rel(4, elf::R_PPC_REL24),
+            rel(2, elf::R_PPC_ADDR16_LO),
+            rel(2, elf::R_PPC_ADDR16_HA),
+            rel(0, elf::R_PPC_ADDR32),
+        ];
+        sort_relocations(&mut relocations);
+        assert_eq!(relocations.iter().map(|rel| (rel.r_offset, rel.r_type)).collect::<Vec<_>>(), [
+            (0, elf::R_PPC_ADDR32),
+            (2, elf::R_PPC_ADDR16_HA),
+            (2, elf::R_PPC_ADDR16_LO),
+            (4, elf::R_PPC_REL24),
+        ]);
+    }
+
+    #[test]
+    fn test_locals_precede_globals() {
+        let symbol = |name: &str, address: u64, flags: ObjSymbolFlagSet| ObjSymbol {
+            flags,
+            kind: ObjSymbolKind::Object,
+            ..test_symbol(name, 0, address, 4)
+        };
+        let local = ObjSymbolFlagSet(ObjSymbolFlags::Local.into());
+        let mut obj = test_obj(
+            ObjKind::Relocatable,
+            vec![
+                symbol("global_a", 0, ObjSymbolFlagSet(ObjSymbolFlags::Global.into())),
+                symbol("local_b", 4, local),
+                symbol("weak_c", 8, ObjSymbolFlagSet(ObjSymbolFlags::Local | ObjSymbolFlags::Weak)),
+                symbol("local_d", 12, local),
+            ],
+            vec![ObjSection {
+                elf_index: 1,
+                ..test_section(".data", ObjSectionKind::Data, 0, vec![0; 16])
+            }],
+        );
+        let reloc = test_reloc(ObjRelocKind::Absolute, 3);
+        obj.sections[0].relocations.insert(0, reloc).unwrap();
+        let data = write_elf(&obj, false).unwrap();
+
+        let elf_file = object::read::elf::ElfFile32::<object::BigEndian>::parse(&*data).unwrap();
+        let (_, symtab) =
+            elf_file.elf_section_table().section_by_name(object::BigEndian, b".symtab").unwrap();
+        let num_local =
+            object::read::elf::SectionHeader::sh_info(symtab, object::BigEndian) as usize;
+        let obj_file = object::read::File::parse(&*data).unwrap();
+        for symbol in obj_file.symbols() {
+            assert_eq!(symbol.is_local(), symbol.index().0 < num_local, "{:?}", symbol.name());
+        }
+        // Null, file, section, local_b and local_d symbols
+        assert_eq!(num_local, 5);
+
+        let (_, reloc) = obj_file.section_by_name(".data").unwrap().relocations().next().unwrap();
+        let RelocationTarget::Symbol(idx) = reloc.target() else { panic!() };
+        assert_eq!(obj_file.symbol_by_index(idx).unwrap().name(), Ok("local_d"));
+    }
+
+    #[test]
+    fn test_tls_symbol_round_trip() {
+        let obj = test_obj(
+            ObjKind::Relocatable,
+            vec![ObjSymbol {
+                name: "tls_value".to_string(),
+                address: 0,
+                section: Some(0),
+                size: 4,
+                size_known: true,
+                flags: ObjSymbolFlagSet(ObjSymbolFlags::Global.into()),
+                kind: ObjSymbolKind::Tls,
+                ..Default::default()
+            }],
+            vec![ObjSection {
+                elf_index: 1,
+                ..test_section(".tdata", ObjSectionKind::Data, 0, vec![0; 4])
+            }],
+        );
+        let data = write_elf(&obj, false).unwrap();
+        let obj_file = object::read::File::parse(&*data).unwrap();
+        let symbol = obj_file.symbol_by_name("tls_value").unwrap();
+        assert_eq!(symbol.kind(), SymbolKind::Tls);
+
+        let obj = parse_elf(&data, &ProcessElfOptions::default(), None).unwrap();
+        let (_, symbol) = obj.symbols.by_name("tls_value").unwrap().unwrap();
+        assert_eq!(symbol.kind, ObjSymbolKind::Tls);
+        assert_eq!(symbol.size, 4);
+    }
+
+    #[test]
+    fn test_code_fill() {
+        let obj = test_obj(ObjKind::Relocatable, vec![], vec![
+            test_section(".init", ObjSectionKind::Code, 0, vec![0x4E, 0x80, 0x00, 0x20]),
+            test_section(".text", ObjSectionKind::Code, 0, vec![0x4E, 0x80, 0x00, 0x20]),
+            test_section(".data", ObjSectionKind::Data, 0, vec![0xFF; 4]),
+        ]);
+        let options = WriteElfOptions { code_fill: 0x60000000, ..Default::default() };
+        let data = write_elf_with_options(&obj, false, &options).unwrap();
+
+        let obj_file = object::read::File::parse(&*data).unwrap();
+        let file_range = |name: &str| obj_file.section_by_name(name).unwrap().file_range().unwrap();
+        for (name, next) in [(".init", ".text"), (".text", ".data")] {
+            let (offset, size) = file_range(name);
+            let (next_offset, _) = file_range(next);
+            let padding = &data[(offset + size) as usize..next_offset as usize];
+            assert!(!padding.is_empty());
+            assert!(padding.chunks(4).all(|word| word == [0x60, 0x00, 0x00, 0x00]));
+        }
+    }
+
+    #[test]
+    fn test_section_data_kind_order() {
+        let obj = test_obj(ObjKind::Relocatable, vec![], vec![
+            test_section(".data", ObjSectionKind::Data, 0, vec![0xFF; 4]),
+            test_section(".text", ObjSectionKind::Code, 0, vec![0x4E, 0x80, 0x00, 0x20]),
+            test_section(".rodata", ObjSectionKind::ReadOnlyData, 0, vec![0xFF; 4]),
+        ]);
+        let data = write_elf(&obj, false).unwrap();
+        let obj_file = object::read::File::parse(&*data).unwrap();
+        // Section indices keep the object's order
+        let names =
+            obj_file.sections().skip(1).take(3).map(|s| s.name().unwrap()).collect::<Vec<_>>();
+        assert_eq!(names, [".data", ".text", ".rodata"]);
+        // Section data is laid out by kind
+        let offset = |name: &str| obj_file.section_by_name(name).unwrap().file_range().unwrap().0;
+        assert!(offset(".text") < offset(".rodata"));
+        assert!(offset(".rodata") < offset(".data"));
+    }
+
+    #[test]
+    fn test_preserve_file_offsets() {
+        let section = |name: &str, kind: ObjSectionKind, file_offset: u64| ObjSection {
+            file_offset,
+            ..test_section(name, kind, 0, vec![0xFF; 8])
+        };
+        let mut obj = test_obj(ObjKind::Relocatable, vec![], vec![
+            section(".text", ObjSectionKind::Code, 0x44),
+            section(".data", ObjSectionKind::Data, 0x100),
+            section(".bss", ObjSectionKind::Bss, 0),
+        ]);
+        obj.sections[2].data.clear();
+        let options = WriteElfOptions { preserve_file_offsets: true, ..Default::default() };
+        let data = write_elf_with_options(&obj, false, &options).unwrap();
+        let obj_file = object::read::File::parse(&*data).unwrap();
+        for (name, offset) in [(".text", 0x44), (".data", 0x100)] {
+            let section = obj_file.section_by_name(name).unwrap();
+            assert_eq!(section.file_range(), Some((offset, 8)), "{name}");
+        }
+
+        // Offsets must not decrease
+        obj.sections[1].file_offset = 0x48;
+        let err = write_elf_with_options(&obj, false, &options).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Section .data file offset 0x48 precedes the end of previous data (0x4C)"
+        );
+    }
+
+    #[test]
+    fn test_symtab_shndx() {
+        let sections = (0..5)
+            .map(|i| test_section(&format!(".data{i}"), ObjSectionKind::Data, 0, vec![0; 4]))
+            .collect();
+        let obj = test_obj(
+            ObjKind::Relocatable,
+            vec![ObjSymbol {
+                name: "value".to_string(),
+                section: Some(4),
+                size: 4,
+                size_known: true,
+                flags: ObjSymbolFlagSet(ObjSymbolFlags::Global.into()),
+                kind: ObjSymbolKind::Object,
+                ..Default::default()
+            }],
+            sections,
+        );
+        let options = WriteElfOptions::default();
+        let data = write_elf_with_options(&obj, false, &options).unwrap();
+        let obj_file = object::read::File::parse(&*data).unwrap();
+        assert!(obj_file.section_by_name(".symtab_shndx").is_none());
+
+        // Lower the threshold so that sections 4 and 5 need extended indices
+        let data = write_elf_impl(&obj, false, &options, 4).unwrap();
+        let obj_file = object::read::File::parse(&*data).unwrap();
+        let shndx = obj_file.section_by_name(".symtab_shndx").unwrap();
+        let indices = shndx
+            .data()
+            .unwrap()
+            .chunks_exact(4)
+            .map(|c| u32::from_be_bytes(c.try_into().unwrap()))
+            .collect::<Vec<_>>();
+        // Null and file symbols, one section symbol per section, then `value`
+        assert_eq!(indices, [0, 0, 1, 2, 3, 4, 5, 5]);
+
+        let out = parse_elf(&data, &ProcessElfOptions::default(), None).unwrap();
+        assert_eq!(out.sections.len(), 5);
+        let (_, symbol) = out.symbols.by_name("value").unwrap().unwrap();
+        assert_eq!(symbol.section, Some(4));
+    }
+
+    #[test]
+    fn test_producer_comment() {
+        let comment = b"\0GCC: (GNU) 4.4.0\0";
+        let (mut obj, _) = new_elf(&[0x4E, 0x80, 0x00, 0x20]);
+        let section = obj.add_section(vec![], b".comment".to_vec(), SectionKind::OtherString);
+        obj.append_section_data(section, comment, 1);
+        let data = obj.write().unwrap();
+
+        let obj = process_elf_bytes(&data, "").unwrap();
+        let (_, section) = obj.sections.by_name(".comment").unwrap().unwrap();
+        assert_eq!(section.kind, ObjSectionKind::Note);
+        assert_eq!(obj.producer().as_deref(), Some("GCC: (GNU) 4.4.0"));
+
+        let data = write_elf(&obj, false).unwrap();
+        let obj_file = object::read::File::parse(&*data).unwrap();
+        let out_section = obj_file.section_by_name(".comment").unwrap();
+        assert_eq!(out_section.data().unwrap(), comment);
+        let obj = process_elf_bytes(&data, "").unwrap();
+        assert_eq!(obj.producer().as_deref(), Some("GCC: (GNU) 4.4.0"));
+    }
+
+    #[test]
+    fn test_sorted_relocations() {
+        let mut obj = test_obj(
+            ObjKind::Executable,
+            vec![ObjSymbol {
+                name: "target".to_string(),
+                address: 0x80003100,
+                section: Some(0),
+                size: 4,
+                size_known: true,
+                kind: ObjSymbolKind::Function,
+                ..Default::default()
+            }],
+            vec![test_section(".text", ObjSectionKind::Code, 0x80003100, vec![0; 0x10])],
+        );
+        let reloc = |kind| test_reloc(kind, 0);
+        let relocations = &mut obj.sections[0].relocations;
+        // Written at 0x80003106, after the unaligned word at 0x80003105
+        relocations.insert(0x80003104, reloc(ObjRelocKind::PpcAddr16Lo)).unwrap();
+        relocations.replace(0x80003105, reloc(ObjRelocKind::Absolute));
+        relocations.insert(0x80003108, reloc(ObjRelocKind::PpcRel24)).unwrap();
+        let data = write_elf(&obj, false).unwrap();
+
+        let obj_file = object::read::File::parse(&*data).unwrap();
+        let offsets = obj_file
+            .section_by_name(".text")
+            .unwrap()
+            .relocations()
+            .map(|(offset, _)| offset)
+            .collect::<Vec<_>>();
+        assert_eq!(offsets, vec![0x80003105, 0x80003106, 0x80003108]);
+    }
+
+    #[test]
+    fn test_runtime_markers() {
+        let section = |name: &str, address: u64| {
+            test_section(name, ObjSectionKind::ReadOnlyData, address, vec![0; 8])
+        };
+        let symbol = |name: &str, section: u32, address: u64| ObjSymbol {
+            flags: ObjSymbolFlagSet(ObjSymbolFlags::Global.into()),
+            kind: ObjSymbolKind::Object,
+            ..test_symbol(name, section, address, 0)
+        };
+        let obj = test_obj(
+            ObjKind::Executable,
+            vec![
+                symbol("_ctors", 0, 0x80005000),
+                ObjSymbol { size: 4, ..symbol("__init_cpp_exceptions_reference", 0, 0x80005000) },
+                symbol("_dtors", 1, 0x80005020),
+            ],
+            vec![section(".ctors", 0x80005000), section(".dtors", 0x80005020)],
+        );
+        let data = write_elf(&obj, false).unwrap();
+
+        let obj = process_elf_bytes(&data, "").unwrap();
+        assert_eq!(obj.ctors_address, Some(0x80005000));
+        assert_eq!(obj.init_cpp_exceptions_reference, Some(0x80005000));
+        assert_eq!(obj.dtors_address, Some(0x80005020));
+    }
+
+    #[test]
+    fn test_zero_size_program_headers() {
+        let obj = test_obj(ObjKind::Executable, vec![], vec![
+            test_section(".text", ObjSectionKind::Code, 0x80003100, vec![0x4E, 0x80, 0x00, 0x20]),
+            test_section(".data", ObjSectionKind::Data, 0x80004000, vec![]),
+            test_section(".sdata", ObjSectionKind::Data, 0x80005000, vec![0xFF; 8]),
+        ]);
+        let data = write_elf(&obj, false).unwrap();
+
+        let obj_file = object::read::File::parse(&*data).unwrap();
+        let segments = obj_file.segments().map(|s| (s.address(), s.size())).collect::<Vec<_>>();
+        assert_eq!(segments, vec![(0x80003100, 4), (0x80005000, 8)]);
+    }
+
+    #[test]
+    fn test_note_round_trip() {
+        let note = [
+            0x00, 0x00, 0x00, 0x04, 0x00, 0x00, 0x00, 0x08, 0x00, 0x00, 0x00, 0x01, b'd', b't',
+            b'k', 0x00, 0xDE, 0xAD, 0xBE, 0xEF, 0x01, 0x23, 0x45, 0x67,
+        ];
+        let (mut obj, _) = new_elf(&[0x4E, 0x80, 0x00, 0x20]);
+        let section = obj.add_section(vec![], b".note.decomp".to_vec(), SectionKind::Note);
+        obj.append_section_data(section, &note, 4);
+        let data = obj.write().unwrap();
+
+        let obj = process_elf_bytes(&data, "").unwrap();
+        let (_, section) = obj.sections.by_name(".note.decomp").unwrap().unwrap();
+        assert_eq!(section.kind, ObjSectionKind::Note);
+        assert_eq!(section.data, note);
+
+        let data = write_elf(&obj, false).unwrap();
+        let obj_file = object::read::File::parse(&*data).unwrap();
+        let out_section = obj_file.section_by_name(".note.decomp").unwrap();
+        assert_eq!(out_section.kind(), SectionKind::Note);
+        assert_eq!(out_section.data().unwrap(), note);
+
+        let obj = process_elf_bytes(&data, "").unwrap();
+        let (_, section) = obj.sections.by_name(".note.decomp").unwrap().unwrap();
+        assert_eq!(section.kind, ObjSectionKind::Note);
+        assert_eq!(section.data, note);
+    }
+
+    #[test]
+    fn test_progress_events() {
+        let mut events = vec![];
+        let mut progress = |event| events.push(event);
+        parse_elf(&build_reloc_elf(), &ProcessElfOptions::default(), Some(&mut progress)).unwrap();
+        assert_eq!(events, vec![
+            ProgressEvent::SectionsParsed(1),
+            ProgressEvent::SymbolsParsed(2),
+            ProgressEvent::RelocationsParsed(1),
+        ]);
+    }
+
+    #[test]
+    fn test_synthesize_symbol_names() {
+        let (mut obj, text) = new_elf(&[0u8; 16]);
+        let data = obj.add_section(vec![], b".data".to_vec(), SectionKind::Data);
+        obj.append_section_data(data, &[0u8; 16], 4);
+        for (section, kind) in [(text, SymbolKind::Text), (data, SymbolKind::Data)] {
+            add_elf_symbol(&mut obj, b"", Some(section), 8, 4, kind, SymbolScope::Compilation);
+        }
+        let data = obj.write().unwrap();
+
+        assert!(parse_elf(&data, &ProcessElfOptions::default(), None).is_err());
+        let options = ProcessElfOptions { synthesize_symbol_names: true, ..Default::default() };
+        let obj = parse_elf(&data, &options, None).unwrap();
+        for name in ["lbl_00000008", "data_00000008"] {
+            let (_, symbol) = obj.symbols.by_name(name).unwrap().unwrap();
+            assert!(symbol.flags.is_local());
+            assert!(symbol.flags.is_autogenerated());
+        }
+    }
+
+    #[test]
+    fn test_rel14_prediction_round_trip() {
+        // bdnzt eq, target (y-bit clear)
+        let ins = 0x41020000u32;
+        for (r_type, kind) in [
+            (elf::R_PPC_REL14, ObjRelocKind::PpcRel14),
+            (elf::R_PPC_REL14_BRTAKEN, ObjRelocKind::PpcRel14BrTaken),
+            (elf::R_PPC_REL14_BRNTAKEN, ObjRelocKind::PpcRel14BrnTaken),
+        ] {
+            let mut data = ins.to_be_bytes().to_vec();
+            data.extend_from_slice(&[0x60, 0x00, 0x00, 0x00, 0x4E, 0x80, 0x00, 0x20]);
+            let (mut obj, text) = new_elf(&data);
+            let target = add_elf_symbol(
+                &mut obj,
+                b"target",
+                Some(text),
+                8,
+                4,
+                SymbolKind::Text,
+                SymbolScope::Compilation,
+            );
+            add_elf_reloc(&mut obj, text, 0, target, 0, r_type);
+            let data = obj.write().unwrap();
+
+            let obj = process_elf_bytes(&data, "").unwrap();
+            let (_, text) = obj.sections.by_name(".text").unwrap().unwrap();
+            assert_eq!(text.relocations.at(0).unwrap().kind, kind);
+
+            let data = write_elf(&obj, false).unwrap();
+            let obj_file = object::read::File::parse(&*data).unwrap();
+            let text = obj_file.section_by_name(".text").unwrap();
+            let (offset, reloc) = text.relocations().next().unwrap();
+            assert_eq!(offset, 0);
+            // The prediction comes from the relocation type, not the instruction
+            assert_eq!(reloc.flags(), RelocationFlags::Elf { r_type });
+            assert_eq!(text.data().unwrap()[..4], ins.to_be_bytes());
+        }
+    }
+
+    /// Builds a relocatable object with `.rel.text` (implicit addend) relocations against
+    /// the `.text` section symbol (1) or `target` (2) at `.text+0x10`.
+    fn build_implicit_addend_elf(text: &[u8], relocations: &[(u64, u32, u32)]) -> Vec<u8> {
+        let mut data = vec![];
+        let mut writer = Writer::new(Endianness::Big, false, &mut data);
+        writer.reserve_file_header();
+        let text_name = writer.add_section_name(b".text");
+        let text_index = writer.reserve_section_index();
+        let rel_name = writer.add_section_name(b".rel.text");
+        writer.reserve_section_index();
+        let target_name = writer.add_string(b"target");
+        writer.reserve_null_symbol_index();
+        writer.reserve_symbol_index(Some(text_index));
+        writer.reserve_symbol_index(Some(text_index));
+        let symtab_index = writer.reserve_symtab_section_index();
+        writer.reserve_strtab_section_index();
+        writer.reserve_shstrtab_section_index();
+        let text_offset = writer.reserve(text.len(), 4);
+        let rel_offset = writer.reserve_relocations(relocations.len(), false);
+        writer.reserve_symtab();
+        writer.reserve_strtab();
+        writer.reserve_shstrtab();
+        writer.reserve_section_headers();
+
+        writer
+            .write_file_header(&object::write::elf::FileHeader {
+                os_abi: elf::ELFOSABI_SYSV,
+                abi_version: 0,
+                e_type: elf::ET_REL,
+                e_machine: elf::EM_PPC,
+                e_entry: 0,
+                e_flags: 0,
+            })
+            .unwrap();
+        writer.write_align(4);
+        writer.write(text);
+        writer.write_align_relocation();
+        for &(r_offset, r_sym, r_type) in relocations {
+            writer.write_relocation(false, &Rel { r_offset, r_sym, r_type, r_addend: 0 });
+        }
+        writer.write_null_symbol();
+        writer.write_symbol(&object::write::elf::Sym {
+            name: None,
+            section: Some(text_index),
+            st_info: (elf::STB_LOCAL << 4) + elf::STT_SECTION,
+            st_other: elf::STV_DEFAULT,
+            st_shndx: 0,
+            st_value: 0,
+            st_size: 0,
+        });
+        writer.write_symbol(&object::write::elf::Sym {
+            name: Some(target_name),
+            section: Some(text_index),
+            st_info: (elf::STB_GLOBAL << 4) + elf::STT_FUNC,
+            st_other: elf::STV_DEFAULT,
+            st_shndx: 0,
+            st_value: 0x10,
+            st_size: 4,
+        });
+        writer.write_strtab();
+        writer.write_shstrtab();
+        writer.write_null_section_header();
+        writer.write_section_header(&SectionHeader {
+            name: Some(text_name),
+            sh_type: SHT_PROGBITS,
+            sh_flags: ObjSectionKind::Code.flags() as u64,
+            sh_addr: 0,
+            sh_offset: text_offset as u64,
+            sh_size: text.len() as u64,
+            sh_link: 0,
+            sh_info: 0,
+            sh_addralign: 4,
+            sh_entsize: 0,
+        });
+        writer.write_relocation_section_header(
+            rel_name,
+            text_index,
+            symtab_index,
+            rel_offset,
+            relocations.len(),
+            false,
+        );
+        writer.write_symtab_section_header(2);
+        writer.write_strtab_section_header();
+        writer.write_shstrtab_section_header();
+        data
+    }
+
+    #[test]
+    fn test_implicit_addends() {
+        let mut text = vec![0u8; 0x14];
+        // bl target+0x8
+        text[0x0..0x4].copy_from_slice(&0x48000009u32.to_be_bytes());
+        // lis r3, (.text+0x10000)@ha
+        text[0x4..0x8].copy_from_slice(&0x3C600001u32.to_be_bytes());
+        // addi r3, r3, (target-0x4)@l
+        text[0x8..0xC].copy_from_slice(&0x3863FFFCu32.to_be_bytes());
+        // .4byte .text+0x14
+        text[0xC..0x10].copy_from_slice(&0x14u32.to_be_bytes());
+        let data = build_implicit_addend_elf(&text, &[
+            (0x0, 2, elf::R_PPC_REL24),
+            (0x6, 1, elf::R_PPC_ADDR16_HA),
+            (0xA, 2, elf::R_PPC_ADDR16_LO),
+            (0xC, 1, elf::R_PPC_ADDR32),
+        ]);
+
+        let obj = parse_elf(&data, &ProcessElfOptions::default(), None).unwrap();
+        let (_, text) = obj.sections.by_name(".text").unwrap().unwrap();
+        let relocations = text
+            .relocations
+            .iter()
+            .map(|(address, reloc)| {
+                (address, reloc.kind, obj.symbols[reloc.target_symbol].name.as_str(), reloc.addend)
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(relocations, vec![
+            (0x0, ObjRelocKind::PpcRel24, "target", 0x8),
+            (0x4, ObjRelocKind::PpcAddr16Ha, ".text", 0x10000),
+            (0x8, ObjRelocKind::PpcAddr16Lo, "target", -0x4),
+            (0xC, ObjRelocKind::Absolute, ".text", 0x14),
+        ]);
+    }
+
+    #[test]
+    fn test_hi_lo_pairs() {
+        let mut text = vec![0u8; 0x10];
+        // lis r3, target@ha
+        text[0x0..0x4].copy_from_slice(&0x3C600000u32.to_be_bytes());
+        // addi r4, r3, (target+0x4)@l
+        text[0x4..0x8].copy_from_slice(&0x38830004u32.to_be_bytes());
+        // lwz r5, target@l(r3)
+        text[0x8..0xC].copy_from_slice(&0x80A30000u32.to_be_bytes());
+        // bl target
+        text[0xC..0x10].copy_from_slice(&0x48000001u32.to_be_bytes());
+        let data = build_implicit_addend_elf(&text, &[
+            (0x2, 2, elf::R_PPC_ADDR16_HA),
+            (0x6, 2, elf::R_PPC_ADDR16_LO),
+            (0xA, 2, elf::R_PPC_ADDR16_LO),
+            (0xC, 2, elf::R_PPC_REL24),
+        ]);
+
+        let obj = parse_elf(&data, &ProcessElfOptions::default(), None).unwrap();
+        let (_, text) = obj.sections.by_name(".text").unwrap().unwrap();
+        let pairs = text
+            .relocations
+            .iter()
+            .map(|(address, reloc)| (address, reloc.addend, reloc.pair))
+            .collect::<Vec<_>>();
+        // Both @l relocations point back to the @ha, which only records the first
+        assert_eq!(pairs, vec![
+            (0x0, 0, Some(0x4)),
+            (0x4, 0x4, Some(-0x4)),
+            (0x8, 0, Some(-0x8)),
+            (0xC, 0, None),
+        ]);
+    }
+}
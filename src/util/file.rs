@@ -16,6 +16,7 @@ use crate::{
     util::{
         ncompress::{decompress_yay0, decompress_yaz0, YAY0_MAGIC, YAZ0_MAGIC},
         path::check_path_buf,
+        reader::{Endian, FromBytes},
         Bytes,
     },
     vfs::{open_file, VfsFile},
@@ -59,6 +60,14 @@ where R: Read + Seek + ?Sized {
     Ok(s)
 }
 
+/// Reads a `u32` in the given byte order.
+pub fn read_u32<R>(reader: &mut R, e: Endian) -> io::Result<u32>
+where R: Read + ?Sized {
+    let mut buf = [0u8; 4];
+    reader.read_exact(&mut buf)?;
+    Ok(u32::from_bytes(buf, e))
+}
+
 /// Process response files (starting with '@') and glob patterns (*).
 pub fn process_rsp(files: &[Utf8NativePathBuf]) -> Result<Vec<Utf8NativePathBuf>> {
     let mut out = Vec::<Utf8NativePathBuf>::with_capacity(files.len());
@@ -216,3 +225,18 @@ where
     }
     Ok(hasher.finalize().into())
 }
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+
+    #[test]
+    fn test_read_u32() {
+        let data = [0x12, 0x34, 0x56, 0x78];
+        assert_eq!(read_u32(&mut Cursor::new(&data), Endian::Big).unwrap(), 0x12345678);
+        assert_eq!(read_u32(&mut Cursor::new(&data), Endian::Little).unwrap(), 0x78563412);
+        assert!(read_u32(&mut Cursor::new(&data[..3]), Endian::Big).is_err());
+    }
+}
@@ -0,0 +1,130 @@
+use anyhow::{Context, Result};
+
+use crate::obj::ObjInfo;
+
+/// Serializes an object to JSON for consumption by external tooling.
+/// Section data is encoded as base64. Fields only needed to drive analysis or to write
+/// objects back out are not included (see the field docs on [`ObjInfo`]):
+/// `mw_comment`, `split_meta`, `blocked_relocation_sources`, `blocked_relocation_targets`,
+/// `known_functions`, `line_table` and `unresolved_relocations`.
+pub fn write_json(obj: &ObjInfo) -> Result<String> {
+    serde_json::to_string(obj).context("Failed to serialize object to JSON")
+}
+
+/// Deserializes an object previously written with [`write_json`].
+pub fn read_json(json: &str) -> Result<ObjInfo> {
+    serde_json::from_str(json).context("Failed to deserialize object from JSON")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::obj::{
+        fixtures::{test_obj, test_reloc, test_section},
+        ObjKind, ObjRelocKind, ObjSection, ObjSectionKind, ObjSplit, ObjSymbol, ObjSymbolFlagSet,
+        ObjSymbolFlags, ObjSymbolKind, ObjUnit,
+    };
+
+    /// Compares every serialized field of two objects.
+    fn assert_obj_eq(a: &ObjInfo, b: &ObjInfo) {
+        let ObjInfo {
+            kind,
+            architecture,
+            name,
+            symbols,
+            sections,
+            entry,
+            mw_comment: _,
+            split_meta: _,
+            sda2_base,
+            sda_base,
+            stack_address,
+            stack_end,
+            db_stack_addr,
+            arena_lo,
+            arena_hi,
+            ctors_address,
+            dtors_address,
+            init_cpp_exceptions_reference,
+            link_order,
+            blocked_relocation_sources: _,
+            blocked_relocation_targets: _,
+            known_functions: _,
+            line_table: _,
+            skipped_relocations,
+            module_id,
+            unresolved_relocations: _,
+            data_stripped,
+        } = a;
+        assert_eq!(*kind, b.kind);
+        assert_eq!(*architecture, b.architecture);
+        assert_eq!(*name, b.name);
+        assert_eq!(*symbols, b.symbols);
+        assert_eq!(*sections, b.sections);
+        assert_eq!(*entry, b.entry);
+        assert_eq!(*sda2_base, b.sda2_base);
+        assert_eq!(*sda_base, b.sda_base);
+        assert_eq!(*stack_address, b.stack_address);
+        assert_eq!(*stack_end, b.stack_end);
+        assert_eq!(*db_stack_addr, b.db_stack_addr);
+        assert_eq!(*arena_lo, b.arena_lo);
+        assert_eq!(*arena_hi, b.arena_hi);
+        assert_eq!(*ctors_address, b.ctors_address);
+        assert_eq!(*dtors_address, b.dtors_address);
+        assert_eq!(*init_cpp_exceptions_reference, b.init_cpp_exceptions_reference);
+        assert_eq!(*link_order, b.link_order);
+        assert_eq!(*skipped_relocations, b.skipped_relocations);
+        assert_eq!(*module_id, b.module_id);
+        assert_eq!(*data_stripped, b.data_stripped);
+    }
+
+    #[test]
+    fn test_json_round_trip() {
+        let mut obj = test_obj(
+            ObjKind::Executable,
+            vec![ObjSymbol {
+                name: "func".to_string(),
+                address: 0x80003100,
+                section: Some(0),
+                size: 8,
+                size_known: true,
+                flags: ObjSymbolFlagSet(ObjSymbolFlags::Global.into()),
+                kind: ObjSymbolKind::Function,
+                ..Default::default()
+            }],
+            vec![ObjSection {
+                elf_index: 1,
+                file_offset: 0x100,
+                ..test_section(".text", ObjSectionKind::Code, 0x80003100, vec![
+                    0x48, 0x00, 0x00, 0x01, 0x4E, 0x80, 0x00, 0x20,
+                ])
+            }],
+        );
+        obj.entry = Some(0x80003100);
+        obj.sda_base = Some(0x80005000);
+        obj.skipped_relocations.insert(109, 2);
+        obj.link_order.push(ObjUnit {
+            name: "main.c".to_string(),
+            autogenerated: false,
+            comment_version: Some(14),
+            order: None,
+        });
+        let reloc = test_reloc(ObjRelocKind::PpcRel24, 0);
+        obj.sections[0].relocations.insert(0x80003100, reloc).unwrap();
+        obj.sections[0].splits.push(0x80003100, ObjSplit {
+            unit: "main.c".to_string(),
+            end: 0x80003108,
+            align: None,
+            common: false,
+            autogenerated: false,
+            skip: false,
+            rename: None,
+        });
+
+        let json = write_json(&obj).unwrap();
+        assert!(json.contains("\"SAAAAU6AACA=\""));
+        let out = read_json(&json).unwrap();
+        assert_obj_eq(&out, &obj);
+        assert_eq!(write_json(&out).unwrap(), json);
+    }
+}
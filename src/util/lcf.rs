@@ -1,8 +1,8 @@
 use anyhow::Result;
 use itertools::Itertools;
-use typed_path::{Utf8NativePathBuf, Utf8UnixPath};
+use typed_path::{Utf8NativePathBuf, Utf8UnixPathBuf};
 
-use crate::obj::{ObjInfo, ObjKind};
+use crate::obj::{ObjInfo, ObjKind, ObjSection};
 
 const LCF_TEMPLATE: &str = include_str!("../../assets/ldscript.lcf");
 const LCF_PARTIAL_TEMPLATE: &str = include_str!("../../assets/ldscript_partial.lcf");
@@ -16,17 +16,17 @@ pub fn generate_ldscript(
         return generate_ldscript_partial(obj, template, force_active);
     }
 
-    let origin = obj.sections.iter().map(|(_, s)| s.address).min().unwrap();
+    let origin = loadable_sections(obj).map(|s| s.address).min().unwrap();
     let stack_size = match (obj.stack_address, obj.stack_end) {
         (Some(stack_address), Some(stack_end)) => stack_address - stack_end,
         _ => 65535, // default
     };
 
-    let section_defs = obj
-        .sections
+    let sections = ordered_sections(obj);
+    let mut section_defs = sections
         .iter()
-        .map(|(_, s)| format!("{} ALIGN({:#X}):{{}}", s.name, s.align))
-        .join("\n        ");
+        .map(|s| section_def(s, if is_common_bss(obj, s) { BSS_COMMON } else { "" }))
+        .collect::<Vec<_>>();
 
     let mut force_files = Vec::with_capacity(obj.link_order.len());
     for unit in &obj.link_order {
@@ -42,13 +42,19 @@ pub fn generate_ldscript(
     }
 
     // Hack to handle missing .sbss2 section... what's the proper way?
-    let last_section_name = obj.sections.iter().next_back().unwrap().1.name.clone();
+    let mut last_section_name = loadable_sections(obj).next_back().unwrap().name.clone();
+    // Common symbols without a `.bss` section get one of their own at the end
+    if let Some(def) = common_bss_def(obj, &sections) {
+        section_defs.push(def);
+        last_section_name = ".bss".to_string();
+    }
     let last_section_symbol = format!("_f_{}", last_section_name.trim_start_matches('.'));
 
     let out = template
         .unwrap_or(LCF_TEMPLATE)
         .replace("$ORIGIN", &format!("{:#X}", origin))
-        .replace("$SECTIONS", &section_defs)
+        .replace("$SECTIONS", &section_defs.join("\n        "))
+        .replace("$KEEPSECTION", &keep_sections(obj))
         .replace("$LAST_SECTION_SYMBOL", &last_section_symbol)
         .replace("$LAST_SECTION_NAME", &last_section_name)
         .replace("$STACKSIZE", &format!("{:#X}", stack_size))
@@ -62,13 +68,22 @@ pub fn generate_ldscript_partial(
     template: Option<&str>,
     force_active: &[String],
 ) -> Result<String> {
-    let mut section_defs = obj
-        .sections
+    // Partial links group sections by kind, keeping the object's order within each kind
+    let mut sections = ordered_sections(obj);
+    sections.sort_by_key(|s| s.kind.canonical_order());
+    let mut section_defs = sections
         .iter()
-        .map(|(_, s)| {
-            let inner = if s.name == ".data" { " *(.data) *(extabindex) *(extab) " } else { "" };
-            format!("{} ALIGN({:#X}):{{{}}}", s.name, s.align, inner)
+        .map(|s| {
+            let inner = if s.name == ".data" {
+                " *(.data) *(extabindex) *(extab) "
+            } else if is_common_bss(obj, s) {
+                BSS_COMMON
+            } else {
+                ""
+            };
+            section_def(s, inner)
         })
+        .chain(common_bss_def(obj, &sections))
         .join("\n        ");
 
     // Some RELs have no entry point (`.text` was stripped) so mwld requires at least an empty
@@ -93,14 +108,213 @@ pub fn generate_ldscript_partial(
     let out = template
         .unwrap_or(LCF_PARTIAL_TEMPLATE)
         .replace("$SECTIONS", &section_defs)
+        .replace("$KEEPSECTION", &keep_sections(obj))
         .replace("$FORCEACTIVE", &force_active.join("\n    "));
     Ok(out)
 }
 
+/// `.bss` contents when the object has common symbols, which are allocated after it.
+const BSS_COMMON: &str = " *(.bss) *(COMMON) ";
+
+fn has_common_symbols(obj: &ObjInfo) -> bool {
+    obj.symbols.iter().any(|(_, s)| s.flags.is_common())
+}
+
+fn is_common_bss(obj: &ObjInfo, section: &ObjSection) -> bool {
+    section.name == ".bss" && has_common_symbols(obj)
+}
+
+/// Returns a `.bss` section definition holding common symbols, if the object has common
+/// symbols but no `.bss` section of its own.
+fn common_bss_def(obj: &ObjInfo, sections: &[&ObjSection]) -> Option<String> {
+    if !has_common_symbols(obj) || sections.iter().any(|s| s.name == ".bss") {
+        return None;
+    }
+    let align = obj
+        .symbols
+        .iter()
+        .filter(|(_, s)| s.flags.is_common())
+        .map(|(_, s)| s.align.unwrap_or(4))
+        .fold(4, u32::max);
+    Some(format!(".bss ALIGN({:#X}):{{{}}}", align, BSS_COMMON))
+}
+
+/// Sections that must be linked even when unreferenced, and that receive
+/// `__<name>_start`/`__<name>_end` boundary symbols.
+const KEEP_SECTIONS: [&str; 4] = [".init", ".fini", ".ctors", ".dtors"];
+
+/// Sections placed in memory by the linker. Notes (e.g. `.comment`) are omitted, as are
+/// unwind tables, which aren't split into units.
+fn loadable_sections(obj: &ObjInfo) -> impl DoubleEndedIterator<Item = &ObjSection> {
+    obj.sections.iter().map(|(_, s)| s).filter(|s| s.kind.loadable() && !s.is_unwind_table())
+}
+
+/// Returns the object's sections in linker script order. `.init`/`.fini` and
+/// `.ctors`/`.dtors` are placed adjacently at the position of the first of each pair,
+/// so that the static initializer arrays are contiguous.
+fn ordered_sections(obj: &ObjInfo) -> Vec<&ObjSection> {
+    let mut out: Vec<&ObjSection> = Vec::with_capacity(obj.sections.len() as usize);
+    for section in loadable_sections(obj) {
+        let group = match section.name.as_str() {
+            ".init" | ".fini" => [".init", ".fini"],
+            ".ctors" | ".dtors" => [".ctors", ".dtors"],
+            _ => {
+                out.push(section);
+                continue;
+            }
+        };
+        if out.iter().any(|s| group.contains(&s.name.as_str())) {
+            continue;
+        }
+        for name in group {
+            out.extend(loadable_sections(obj).find(|s| s.name == name));
+        }
+    }
+    out
+}
+
+fn section_def(section: &ObjSection, inner: &str) -> String {
+    if KEEP_SECTIONS.contains(&section.name.as_str()) {
+        let symbol = section.name.trim_start_matches('.');
+        format!(
+            "{} ALIGN({:#X}):{{ __{symbol}_start = .; *({}) __{symbol}_end = .; }}",
+            section.name, section.align, section.name
+        )
+    } else {
+        format!("{} ALIGN({:#X}):{{{}}}", section.name, section.align, inner)
+    }
+}
+
+fn keep_sections(obj: &ObjInfo) -> String {
+    let names =
+        KEEP_SECTIONS.iter().filter(|&&name| obj.section_by_name(name).is_some()).join(", ");
+    if names.is_empty() {
+        String::new()
+    } else {
+        format!("KEEP_SECTION {{ {} }}", names)
+    }
+}
+
+/// Normalizes a unit name into a relative Unix-style path. Backslash separators
+/// (as found in MW file symbols) are converted and drive letters are stripped.
+fn normalize_unit_path(unit: &str) -> Utf8UnixPathBuf {
+    let unit = unit.replace('\\', "/");
+    let unit = match unit.as_bytes() {
+        [drive, b':', ..] if drive.is_ascii_alphabetic() => &unit[2..],
+        _ => &unit,
+    };
+    Utf8UnixPathBuf::from(unit.trim_start_matches('/'))
+}
+
 pub fn obj_path_for_unit(unit: &str) -> Utf8NativePathBuf {
-    Utf8UnixPath::new(unit).with_encoding().with_extension("o")
+    normalize_unit_path(unit).with_encoding().with_extension("o")
 }
 
 pub fn asm_path_for_unit(unit: &str) -> Utf8NativePathBuf {
-    Utf8UnixPath::new(unit).with_encoding().with_extension("s")
+    normalize_unit_path(unit).with_encoding().with_extension("s")
+}
+
+/// Returns the directory portion of a unit's output path.
+pub fn dir_for_unit(unit: &str) -> Utf8NativePathBuf {
+    normalize_unit_path(unit)
+        .parent()
+        .map(|p| p.with_encoding())
+        .unwrap_or_else(Utf8NativePathBuf::new)
+}
+
+#[cfg(test)]
+mod tests {
+    use typed_path::Utf8UnixPath;
+
+    use super::*;
+    use crate::obj::{
+        fixtures::{test_obj, test_section_sized},
+        ObjSectionKind, ObjSymbol, ObjSymbolFlagSet, ObjSymbolFlags,
+    };
+
+    #[test]
+    fn test_ldscript_ctors_dtors() {
+        let obj = test_obj(ObjKind::Relocatable, vec![], vec![
+            test_section_sized(".text", ObjSectionKind::Code, 0, 0x10),
+            test_section_sized(".dtors", ObjSectionKind::ReadOnlyData, 0, 0x10),
+            test_section_sized(".rodata", ObjSectionKind::ReadOnlyData, 0, 0x10),
+            test_section_sized(".ctors", ObjSectionKind::ReadOnlyData, 0, 0x10),
+            test_section_sized(".comment", ObjSectionKind::Note, 0, 0x10),
+            test_section_sized(".eh_frame", ObjSectionKind::ReadOnlyData, 0, 0x10),
+        ]);
+        let out = generate_ldscript(&obj, None, &[]).unwrap();
+        let sections = out
+            .lines()
+            .map(str::trim)
+            .filter(|line| line.contains("ALIGN("))
+            .map(|line| line.split_once(' ').unwrap().0)
+            .collect::<Vec<_>>();
+        assert_eq!(sections, vec![".text", ".ctors", ".dtors", ".rodata"]);
+        assert!(
+            out.contains(".ctors ALIGN(0x4):{ __ctors_start = .; *(.ctors) __ctors_end = .; }\n")
+        );
+        assert!(
+            out.contains(".dtors ALIGN(0x4):{ __dtors_start = .; *(.dtors) __dtors_end = .; }\n")
+        );
+        assert!(out.contains("KEEP_SECTION { .ctors, .dtors }\n"));
+    }
+
+    #[test]
+    fn test_ldscript_partial_kind_order() {
+        let obj = test_obj(ObjKind::Relocatable, vec![], vec![
+            test_section_sized(".data", ObjSectionKind::Data, 0, 0x10),
+            test_section_sized(".bss", ObjSectionKind::Bss, 0, 0x10),
+            test_section_sized(".text", ObjSectionKind::Code, 0, 0x10),
+            test_section_sized(".rodata", ObjSectionKind::ReadOnlyData, 0, 0x10),
+        ]);
+        let out = generate_ldscript(&obj, None, &[]).unwrap();
+        let sections = out
+            .lines()
+            .map(str::trim)
+            .filter(|line| line.contains("ALIGN("))
+            .map(|line| line.split_once(' ').unwrap().0)
+            .collect::<Vec<_>>();
+        assert_eq!(sections, vec![".text", ".rodata", ".data", ".bss"]);
+    }
+
+    #[test]
+    fn test_ldscript_common_symbols() {
+        let common = |name: &str| ObjSymbol {
+            name: name.to_string(),
+            size: 8,
+            size_known: true,
+            flags: ObjSymbolFlagSet(ObjSymbolFlags::Global | ObjSymbolFlags::Common),
+            align: Some(8),
+            ..Default::default()
+        };
+        let mut obj = test_obj(ObjKind::Executable, vec![common("a"), common("b")], vec![
+            test_section_sized(".text", ObjSectionKind::Code, 0x80003100, 0x10),
+        ]);
+        let out = generate_ldscript(&obj, None, &[]).unwrap();
+        assert!(out.contains(".bss ALIGN(0x8):{ *(.bss) *(COMMON) }\n"));
+        assert!(out.contains("_stack_end = _f_bss + SIZEOF(.bss);"));
+
+        obj.sections.push(test_section_sized(".bss", ObjSectionKind::Bss, 0x80003110, 0x10));
+        obj.sections.push(test_section_sized(".sbss", ObjSectionKind::Bss, 0x80003120, 0x10));
+        let out = generate_ldscript(&obj, None, &[]).unwrap();
+        assert_eq!(out.matches("*(COMMON)").count(), 1);
+        assert!(out.contains(".bss ALIGN(0x4):{ *(.bss) *(COMMON) }\n"));
+        assert!(out.contains("_stack_end = _f_sbss + SIZEOF(.sbss);"));
+    }
+
+    #[test]
+    fn test_unit_paths_backslash() {
+        let unit = "C:\\src\\foo\\bar.cpp";
+        assert_eq!(obj_path_for_unit(unit), Utf8UnixPath::new("src/foo/bar.o").with_encoding());
+        assert_eq!(asm_path_for_unit(unit), Utf8UnixPath::new("src/foo/bar.s").with_encoding());
+        assert_eq!(dir_for_unit(unit), Utf8UnixPath::new("src/foo").with_encoding());
+    }
+
+    #[test]
+    fn test_unit_paths_mixed_separators() {
+        let unit = "src/foo\\bar/baz.c";
+        assert_eq!(obj_path_for_unit(unit), Utf8UnixPath::new("src/foo/bar/baz.o").with_encoding());
+        assert_eq!(dir_for_unit(unit), Utf8UnixPath::new("src/foo/bar").with_encoding());
+        assert_eq!(dir_for_unit("main.c"), Utf8NativePathBuf::new());
+    }
 }
@@ -0,0 +1,439 @@
+use std::collections::{btree_map, BTreeMap};
+
+use anyhow::{anyhow, bail, ensure, Context, Result};
+
+use crate::{
+    obj::{
+        ObjArchitecture, ObjInfo, ObjKind, ObjReloc, ObjSection, ObjSplit, ObjSymbol,
+        ObjSymbolFlagSet, ObjSymbolFlags, ObjSymbolKind, ObjUnit, SectionIndex, SymbolIndex,
+        SDA2_BASE_SYMBOL, SDA_BASE_SYMBOL,
+    },
+    util::align_up,
+};
+
+/// Placement of an output section in a linked executable.
+#[derive(Debug, Clone)]
+pub struct LinkSection {
+    pub name: String,
+    /// Load address. When `None`, the section directly follows the previous one.
+    pub address: Option<u64>,
+}
+
+/// Output section order and addresses for [`link`]. Input sections are matched to
+/// output sections by name.
+#[derive(Debug, Clone, Default)]
+pub struct LinkLayout {
+    pub sections: Vec<LinkSection>,
+}
+
+/// Links relocatable units, given in link order, into an executable.
+///
+/// Input sections are concatenated into the output sections described by `layout`, and
+/// undefined symbols are resolved against the global symbols of all units. Relocations
+/// are kept in the output and applied to its section data. Each input section is
+/// recorded as a split, so that the result can be split again into the same units.
+pub fn link(units: Vec<ObjInfo>, layout: &LinkLayout) -> Result<ObjInfo> {
+    // Place input sections: (output section index, load address) per unit section
+    let mut placements =
+        units.iter().map(|u| vec![None; u.sections.len() as usize]).collect::<Vec<_>>();
+    let mut sections = Vec::<ObjSection>::with_capacity(layout.sections.len());
+    let mut next_address = 0;
+    for link_section in &layout.sections {
+        let inputs = units
+            .iter()
+            .enumerate()
+            .flat_map(|(unit_index, unit)| {
+                unit.sections
+                    .iter()
+                    .filter(|(_, s)| s.name == link_section.name)
+                    .map(move |(section_index, s)| (unit_index, section_index, s))
+            })
+            .collect::<Vec<_>>();
+        let Some(&(_, _, first)) = inputs.first() else {
+            continue;
+        };
+        let kind = first.kind;
+        let align = inputs
+            .iter()
+            .map(|&(unit_index, section_index, s)| {
+                input_align(&units[unit_index], section_index, s)
+            })
+            .max()
+            .unwrap_or(1);
+        let start = align_up(link_section.address.unwrap_or(next_address) as u32, align as u32);
+        let out_index = sections.len() as SectionIndex;
+        let mut out = ObjSection {
+            name: link_section.name.clone(),
+            kind,
+            address: start as u64,
+            size: 0,
+            data: vec![].into(),
+            align,
+            elf_index: out_index + 1,
+            relocations: Default::default(),
+            virtual_address: None,
+            file_offset: 0,
+            section_known: true,
+            splits: Default::default(),
+        };
+        let mut address = start;
+        for (unit_index, section_index, section) in inputs {
+            let unit = &units[unit_index];
+            ensure!(
+                section.kind == kind,
+                "Section {} in {} is {:?}, expected {:?}",
+                section.name,
+                unit.name,
+                section.kind,
+                kind
+            );
+            address = align_up(address, input_align(unit, section_index, section) as u32);
+            placements[unit_index][section_index as usize] = Some((out_index, address as u64));
+            if section.size > 0 {
+                out.splits.push(address, ObjSplit {
+                    unit: unit.name.clone(),
+                    end: address + section.size as u32,
+                    align: None,
+                    common: false,
+                    autogenerated: false,
+                    skip: false,
+                    rename: None,
+                });
+            }
+            if !kind.is_bss() {
+                out.data.resize((address - start) as usize, 0);
+                out.data.extend_from_slice(&section.data);
+            }
+            address += section.size as u32;
+        }
+        out.size = (address - start) as u64;
+        if !kind.is_bss() {
+            out.data.resize(out.size as usize, 0);
+        }
+        next_address = address as u64;
+        sections.push(out);
+    }
+    for (unit, unit_placements) in units.iter().zip(&placements) {
+        for (section_index, section) in unit.sections.iter() {
+            ensure!(
+                unit_placements[section_index as usize].is_some()
+                    || section.size == 0
+                    || !section.kind.loadable(),
+                "Section {} in {} isn't placed by the link layout",
+                section.name,
+                unit.name
+            );
+        }
+    }
+
+    // Choose a definition for each global symbol. Strong definitions replace weak ones.
+    let is_defined = |unit_index: usize, symbol: &ObjSymbol| match symbol.section {
+        Some(section_index) => placements[unit_index][section_index as usize].is_some(),
+        None => symbol.address != 0,
+    };
+    let mut definitions = BTreeMap::<&str, (usize, SymbolIndex)>::new();
+    for (unit_index, unit) in units.iter().enumerate() {
+        for (symbol_index, symbol) in unit.symbols.iter() {
+            ensure!(
+                !symbol.flags.is_common(),
+                "Common symbol {} in {} must be allocated before linking",
+                symbol.name,
+                unit.name
+            );
+            if symbol.flags.is_local()
+                || symbol.kind == ObjSymbolKind::Section
+                || !is_defined(unit_index, symbol)
+            {
+                continue;
+            }
+            match definitions.entry(symbol.name.as_str()) {
+                btree_map::Entry::Vacant(e) => {
+                    e.insert((unit_index, symbol_index));
+                }
+                btree_map::Entry::Occupied(mut e) => {
+                    let (other_unit, other_index) = *e.get();
+                    if symbol.flags.is_weak() {
+                        continue;
+                    }
+                    ensure!(
+                        units[other_unit].symbols[other_index].flags.is_weak(),
+                        "Duplicate symbol {} defined in {} and {}",
+                        symbol.name,
+                        units[other_unit].name,
+                        unit.name
+                    );
+                    e.insert((unit_index, symbol_index));
+                }
+            }
+        }
+    }
+
+    // Output local symbols and chosen definitions, then resolve global references by name
+    let mut symbols = Vec::<ObjSymbol>::new();
+    let mut symbol_maps =
+        units.iter().map(|u| vec![None; u.symbols.count() as usize]).collect::<Vec<_>>();
+    let mut globals = BTreeMap::<&str, SymbolIndex>::new();
+    for (unit_index, unit) in units.iter().enumerate() {
+        for (symbol_index, symbol) in unit.symbols.iter() {
+            if symbol.kind == ObjSymbolKind::Section || !is_defined(unit_index, symbol) {
+                continue;
+            }
+            let is_definition = symbol.flags.is_local()
+                || definitions.get(symbol.name.as_str()) == Some(&(unit_index, symbol_index));
+            if !is_definition {
+                continue;
+            }
+            let mut out_symbol = symbol.clone();
+            if let Some(section_index) = symbol.section {
+                let (out_section, base) = placements[unit_index][section_index as usize].unwrap();
+                out_symbol.section = Some(out_section);
+                out_symbol.address = base + (symbol.address - unit.sections[section_index].address);
+            }
+            let out_index = symbols.len() as SymbolIndex;
+            symbols.push(out_symbol);
+            symbol_maps[unit_index][symbol_index as usize] = Some(out_index);
+            if symbol.flags.is_global() {
+                globals.insert(symbol.name.as_str(), out_index);
+            }
+        }
+    }
+    for (unit, symbol_map) in units.iter().zip(&mut symbol_maps) {
+        for (symbol_index, symbol) in unit.symbols.iter() {
+            if symbol_map[symbol_index as usize].is_none() && symbol.flags.is_global() {
+                symbol_map[symbol_index as usize] = globals.get(symbol.name.as_str()).copied();
+            }
+        }
+    }
+
+    // Move relocations into the output sections
+    let mut section_symbols = vec![None; sections.len()];
+    for (unit_index, unit) in units.iter().enumerate() {
+        for (section_index, section) in unit.sections.iter() {
+            let Some((out_section, base)) = placements[unit_index][section_index as usize] else {
+                continue;
+            };
+            for (address, reloc) in section.relocations.iter() {
+                ensure!(
+                    reloc.module.is_none(),
+                    "Relocation @ {} {:#010X} in {} targets another module",
+                    section.name,
+                    address,
+                    unit.name
+                );
+                let symbol = &unit.symbols[reloc.target_symbol];
+                let (target_symbol, addend) = if symbol.kind == ObjSymbolKind::Section {
+                    // Retarget to the output section, offset by the input section's position
+                    let target_section = symbol
+                        .section
+                        .ok_or_else(|| anyhow!("Section symbol {} without section", symbol.name))?;
+                    let Some((out_target, target_base)) =
+                        placements[unit_index][target_section as usize]
+                    else {
+                        bail!(
+                            "Relocation @ {} {:#010X} in {} targets unplaced section {}",
+                            section.name,
+                            address,
+                            unit.name,
+                            symbol.name
+                        );
+                    };
+                    let out_target_section = &sections[out_target as usize];
+                    let offset = (symbol.address as i64
+                        - unit.sections[target_section].address as i64)
+                        + (target_base as i64 - out_target_section.address as i64);
+                    let target_symbol = match section_symbols[out_target as usize] {
+                        Some(index) => index,
+                        None => {
+                            let index = symbols.len() as SymbolIndex;
+                            symbols.push(ObjSymbol {
+                                name: out_target_section.name.clone(),
+                                address: out_target_section.address,
+                                section: Some(out_target),
+                                flags: ObjSymbolFlagSet(ObjSymbolFlags::Local.into()),
+                                kind: ObjSymbolKind::Section,
+                                ..Default::default()
+                            });
+                            section_symbols[out_target as usize] = Some(index);
+                            index
+                        }
+                    };
+                    (target_symbol, reloc.addend + offset)
+                } else {
+                    let target_symbol = symbol_maps[unit_index][reloc.target_symbol as usize]
+                        .ok_or_else(|| {
+                            anyhow!("Undefined symbol {} referenced in {}", symbol.name, unit.name)
+                        })?;
+                    (target_symbol, reloc.addend)
+                };
+                let out_address = base + (address as u64 - section.address);
+                sections[out_section as usize]
+                    .relocations
+                    .insert(out_address as u32, ObjReloc { target_symbol, addend, ..reloc.clone() })
+                    .with_context(|| {
+                        format!(
+                            "Relocation @ {} {:#010X} in {} overlaps another",
+                            section.name, address, unit.name
+                        )
+                    })?;
+            }
+        }
+    }
+
+    let link_order = units
+        .iter()
+        .map(|unit| ObjUnit {
+            name: unit.name.clone(),
+            autogenerated: false,
+            comment_version: None,
+            order: None,
+        })
+        .collect();
+    let mut obj = ObjInfo::new(
+        ObjKind::Executable,
+        ObjArchitecture::PowerPc,
+        String::new(),
+        symbols,
+        sections,
+    );
+    obj.link_order = link_order;
+    for (_, symbol) in obj.symbols.iter().filter(|(_, s)| s.flags.is_global()) {
+        match symbol.name.as_str() {
+            SDA_BASE_SYMBOL => obj.sda_base = Some(symbol.address as u32),
+            SDA2_BASE_SYMBOL => obj.sda2_base = Some(symbol.address as u32),
+            "__start" => obj.entry = Some(symbol.address),
+            _ => {}
+        }
+    }
+
+    let base_addresses = obj.sections.iter().map(|(_, s)| s.address).collect::<Vec<_>>();
+    for section_index in 0..obj.sections.len() {
+        if obj.sections[section_index].kind.is_bss() {
+            continue;
+        }
+        let data = obj.apply_relocations(section_index, &base_addresses)?;
+        obj.sections[section_index].data = data.into();
+    }
+    Ok(obj)
+}
+
+/// Alignment of an input section, raised to satisfy the alignment of its symbols.
+fn input_align(unit: &ObjInfo, section_index: SectionIndex, section: &ObjSection) -> u64 {
+    unit.symbols
+        .for_section(section_index)
+        .filter_map(|(_, s)| s.align)
+        .fold(section.align.max(1), |align, symbol_align| align.max(symbol_align as u64))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::obj::{
+        fixtures::{test_obj, test_reloc, test_section, test_symbol},
+        ObjRelocKind, ObjSectionKind,
+    };
+
+    fn global(kind: ObjSymbolKind, symbol: ObjSymbol) -> ObjSymbol {
+        ObjSymbol { flags: ObjSymbolFlagSet(ObjSymbolFlags::Global.into()), kind, ..symbol }
+    }
+
+    /// `a.o` calls `helper` from `b.o`, which also holds a pointer to its own `.text`.
+    fn build_units() -> Vec<ObjInfo> {
+        let mut a = test_obj(
+            ObjKind::Relocatable,
+            vec![
+                global(ObjSymbolKind::Function, test_symbol("main", 0, 0, 4)),
+                global(ObjSymbolKind::Unknown, ObjSymbol {
+                    name: "helper".to_string(),
+                    size_known: true,
+                    ..Default::default()
+                }),
+            ],
+            vec![test_section(".text", ObjSectionKind::Code, 0, vec![
+                0x48, 0x00, 0x00, 0x01, 0x4E, 0x80, 0x00, 0x20,
+            ])],
+        );
+        a.name = "a.o".to_string();
+        a.sections[0].relocations.insert(0, test_reloc(ObjRelocKind::PpcRel24, 1)).unwrap();
+        let mut b = test_obj(
+            ObjKind::Relocatable,
+            vec![global(ObjSymbolKind::Function, test_symbol("helper", 0, 0, 4)), ObjSymbol {
+                name: ".text".to_string(),
+                section: Some(0),
+                flags: ObjSymbolFlagSet(ObjSymbolFlags::Local.into()),
+                kind: ObjSymbolKind::Section,
+                ..Default::default()
+            }],
+            vec![
+                test_section(".text", ObjSectionKind::Code, 0, vec![0x4E, 0x80, 0x00, 0x20]),
+                test_section(".data", ObjSectionKind::Data, 0, vec![0; 4]),
+            ],
+        );
+        b.name = "b.o".to_string();
+        b.sections[1].relocations.insert(0, test_reloc(ObjRelocKind::Absolute, 1)).unwrap();
+        vec![a, b]
+    }
+
+    fn layout() -> LinkLayout {
+        LinkLayout {
+            sections: vec![
+                LinkSection { name: ".text".to_string(), address: Some(0x80003100) },
+                LinkSection { name: ".data".to_string(), address: None },
+            ],
+        }
+    }
+
+    #[test]
+    fn test_link() {
+        let obj = link(build_units(), &layout()).unwrap();
+        assert_eq!(obj.kind, ObjKind::Executable);
+        let (_, text) = obj.sections.by_name(".text").unwrap().unwrap();
+        assert_eq!(text.address, 0x80003100);
+        assert_eq!(text.data, [
+            0x48, 0x00, 0x00, 0x09, 0x4E, 0x80, 0x00, 0x20, 0x4E, 0x80, 0x00, 0x20
+        ]);
+        assert_eq!(text.splits.for_address(0x80003108).unwrap().1.unit, "b.o");
+        let (_, data) = obj.sections.by_name(".data").unwrap().unwrap();
+        assert_eq!(data.address, 0x8000310C);
+        assert_eq!(data.data, 0x80003108u32.to_be_bytes());
+
+        let (_, helper) = obj.symbols.by_name("helper").unwrap().unwrap();
+        assert_eq!(helper.address, 0x80003108);
+        let reloc = text.relocations.at(0x80003100).unwrap();
+        assert_eq!(obj.symbols[reloc.target_symbol].name, "helper");
+        assert_eq!(obj.link_order.iter().map(|u| u.name.as_str()).collect::<Vec<_>>(), [
+            "a.o", "b.o"
+        ]);
+    }
+
+    #[test]
+    fn test_link_symbol_align() {
+        let mut units = build_units();
+        let b = &mut units[1];
+        let value =
+            ObjSymbol { align: Some(16), ..symbol("value", Some(1), ObjSymbolKind::Object) };
+        b.symbols.add_direct(value).unwrap();
+        let obj = link(units, &layout()).unwrap();
+        let (_, data) = obj.sections.by_name(".data").unwrap().unwrap();
+        assert_eq!(data.address, 0x80003110);
+        assert_eq!(data.align, 16);
+    }
+
+    #[test]
+    fn test_link_errors() {
+        let mut units = build_units();
+        let mut c = test_obj(
+            ObjKind::Relocatable,
+            vec![global(ObjSymbolKind::Function, test_symbol("main", 0, 0, 4))],
+            vec![test_section(".text", ObjSectionKind::Code, 0, vec![0x4E, 0x80, 0x00, 0x20])],
+        );
+        c.name = "c.o".to_string();
+        units.push(c);
+        let err = link(units, &layout()).unwrap_err().to_string();
+        assert_eq!(err, "Duplicate symbol main defined in a.o and c.o");
+
+        let mut units = build_units();
+        units.truncate(1);
+        let err = link(units, &layout()).unwrap_err().to_string();
+        assert_eq!(err, "Undefined symbol helper referenced in a.o");
+    }
+}
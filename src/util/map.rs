@@ -820,6 +820,7 @@ pub fn apply_map(mut result: MapInfo, obj: &mut ObjInfo) -> Result<()> {
                         }
                     }
                     ObjSectionKind::Bss => ".bss",
+                    ObjSectionKind::Note => continue,
                 };
                 log::warn!("Defaulting to {}", new_name);
                 section.rename(new_name.to_string())?;
@@ -915,7 +916,7 @@ pub fn create_obj(result: &MapInfo) -> Result<ObjInfo> {
                 kind,
                 address,
                 size,
-                data: vec![],
+                data: vec![].into(),
                 align: 0,
                 elf_index: 0,
                 relocations: Default::default(),
@@ -942,12 +943,18 @@ pub fn create_obj(result: &MapInfo) -> Result<ObjInfo> {
         db_stack_addr: None,
         arena_lo: None,
         arena_hi: None,
+        ctors_address: None,
+        dtors_address: None,
+        init_cpp_exceptions_reference: None,
         link_order: vec![],
         blocked_relocation_sources: Default::default(),
         blocked_relocation_targets: Default::default(),
         known_functions: Default::default(),
+        line_table: None,
         module_id: 0,
         unresolved_relocations: vec![],
+        skipped_relocations: Default::default(),
+        data_stripped: false,
     };
 
     // If every symbol the map has alignment 4, it's likely bogus
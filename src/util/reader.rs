@@ -123,10 +123,11 @@ impl<const N: usize> FromReader for [u32; N] {
         reader.read_exact(unsafe {
             std::slice::from_raw_parts_mut(buf.as_mut_ptr() as *mut u8, Self::STATIC_SIZE)
         })?;
-        if e == Endian::Big {
-            for x in buf.iter_mut() {
-                *x = u32::from_be(*x);
-            }
+        for x in buf.iter_mut() {
+            *x = match e {
+                Endian::Big => u32::from_be(*x),
+                Endian::Little => u32::from_le(*x),
+            };
         }
         Ok(buf)
     }
@@ -273,3 +274,26 @@ where
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+
+    #[test]
+    fn test_read_endian() {
+        let data: [u8; 8] = [0x12, 0x34, 0x56, 0x78, 0x9A, 0xBC, 0xDE, 0xF0];
+        let mut reader = Cursor::new(&data);
+        assert_eq!(u32::from_reader(&mut reader, Endian::Big).unwrap(), 0x12345678);
+        assert_eq!(u16::from_reader(&mut reader, Endian::Little).unwrap(), 0xBC9A);
+        reader.set_position(0);
+        assert_eq!(<[u32; 2]>::from_reader(&mut reader, Endian::Big).unwrap(), [
+            0x12345678, 0x9ABCDEF0
+        ]);
+        reader.set_position(0);
+        assert_eq!(<[u32; 2]>::from_reader(&mut reader, Endian::Little).unwrap(), [
+            0x78563412, 0xF0DEBC9A
+        ]);
+    }
+}
@@ -348,6 +348,69 @@ impl ToWriter for RelRelocRaw {
     fn write_size(&self) -> usize { Self::STATIC_SIZE }
 }
 
+/// A relocation read from a REL relocation stream, positioned by the preceding
+/// control entries.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+struct RelRelocEntry {
+    kind: ObjRelocKind,
+    /// Section being relocated.
+    section: u8,
+    /// Offset within the section being relocated.
+    address: u32,
+    target_section: u8,
+    addend: u32,
+}
+
+/// Reads a relocation stream up to `R_DOLPHIN_END`. Each entry's offset is relative to
+/// the previous entry, and `R_DOLPHIN_SECTION` switches to the start of another section.
+fn read_relocation_stream<R>(reader: &mut R) -> Result<Vec<RelRelocEntry>>
+where R: Read + Seek + ?Sized {
+    let mut entries = vec![];
+    let mut address = 0u32;
+    let mut section = None;
+    loop {
+        let reloc = RelRelocRaw::from_reader(reader, Endian::Big)?;
+        // Every entry advances the offset, including those that don't patch anything
+        address = address.wrapping_add(reloc.offset as u32);
+        let kind = match reloc.kind as u32 {
+            elf::R_PPC_NONE => continue,
+            elf::R_PPC_ADDR32 | elf::R_PPC_UADDR32 => ObjRelocKind::Absolute,
+            // elf::R_PPC_ADDR24 => ObjRelocKind::PpcAddr24,
+            // elf::R_PPC_ADDR16 => ObjRelocKind::PpcAddr16,
+            elf::R_PPC_ADDR16_LO => ObjRelocKind::PpcAddr16Lo,
+            elf::R_PPC_ADDR16_HI => ObjRelocKind::PpcAddr16Hi,
+            elf::R_PPC_ADDR16_HA => ObjRelocKind::PpcAddr16Ha,
+            // elf::R_PPC_ADDR14 => ObjRelocKind::PpcAddr14,
+            // elf::R_PPC_ADDR14_BRTAKEN => ObjRelocKind::PpcAddr14BrTaken,
+            // elf::R_PPC_ADDR14_BRNTAKEN => ObjRelocKind::PpcAddr14BrnTaken,
+            elf::R_PPC_REL24 => ObjRelocKind::PpcRel24,
+            elf::R_PPC_REL14 => ObjRelocKind::PpcRel14,
+            elf::R_PPC_REL14_BRTAKEN => ObjRelocKind::PpcRel14BrTaken,
+            elf::R_PPC_REL14_BRNTAKEN => ObjRelocKind::PpcRel14BrnTaken,
+            R_DOLPHIN_NOP => continue,
+            R_DOLPHIN_SECTION => {
+                address = 0;
+                section = Some(reloc.section);
+                continue;
+            }
+            R_DOLPHIN_END => break,
+            // R_DOLPHIN_MRKREF => ?
+            reloc_type => bail!("Unhandled REL relocation type {reloc_type}"),
+        };
+        let Some(section) = section else {
+            bail!("REL relocation {:?} before R_DOLPHIN_SECTION", kind);
+        };
+        entries.push(RelRelocEntry {
+            kind,
+            section,
+            address,
+            target_section: reloc.section,
+            addend: reloc.addend,
+        });
+    }
+    Ok(entries)
+}
+
 pub fn process_rel_header<R>(reader: &mut R) -> Result<RelHeader>
 where R: Read + Seek + ?Sized {
     RelHeader::from_reader(reader, Endian::Big).context("Failed to read REL header")
@@ -412,7 +475,7 @@ where R: Read + Seek + ?Sized {
             kind,
             address: 0,
             size: size as u64,
-            data,
+            data: data.into(),
             align: match offset {
                 0 => header.bss_align,
                 _ => None, // determined later
@@ -492,50 +555,17 @@ where R: Read + Seek + ?Sized {
 
         let position = reader.stream_position()?;
         reader.seek(SeekFrom::Start(import.offset as u64))?;
-        let mut address = 0u32;
-        let mut section = u8::MAX;
-        loop {
-            let reloc = RelRelocRaw::from_reader(reader, Endian::Big)?;
-            let kind = match reloc.kind as u32 {
-                elf::R_PPC_NONE => continue,
-                elf::R_PPC_ADDR32 | elf::R_PPC_UADDR32 => ObjRelocKind::Absolute,
-                // elf::R_PPC_ADDR24 => ObjRelocKind::PpcAddr24,
-                // elf::R_PPC_ADDR16 => ObjRelocKind::PpcAddr16,
-                elf::R_PPC_ADDR16_LO => ObjRelocKind::PpcAddr16Lo,
-                elf::R_PPC_ADDR16_HI => ObjRelocKind::PpcAddr16Hi,
-                elf::R_PPC_ADDR16_HA => ObjRelocKind::PpcAddr16Ha,
-                // elf::R_PPC_ADDR14 => ObjRelocKind::PpcAddr14,
-                // elf::R_PPC_ADDR14_BRTAKEN => ObjRelocKind::PpcAddr14BrTaken,
-                // elf::R_PPC_ADDR14_BRNTAKEN => ObjRelocKind::PpcAddr14BrnTaken,
-                elf::R_PPC_REL24 => ObjRelocKind::PpcRel24,
-                elf::R_PPC_REL14 => ObjRelocKind::PpcRel14,
-                // elf::R_PPC_REL14_BRTAKEN => ObjRelocKind::PpcRel14BrTaken,
-                // elf::R_PPC_REL14_BRNTAKEN => ObjRelocKind::PpcRel14BrnTaken,
-                R_DOLPHIN_NOP => {
-                    address += reloc.offset as u32;
-                    continue;
-                }
-                R_DOLPHIN_SECTION => {
-                    address = 0;
-                    section = reloc.section;
-                    continue;
-                }
-                R_DOLPHIN_END => break,
-                // R_DOLPHIN_MRKREF => ?
-                reloc_type => bail!("Unhandled REL relocation type {reloc_type}"),
-            };
-            address += reloc.offset as u32;
-            let reloc = RelReloc {
-                kind,
-                section,
-                address: address & !3,
+        for entry in read_relocation_stream(reader)? {
+            unresolved_relocations.push(RelReloc {
+                kind: entry.kind,
+                section: entry.section,
+                address: entry.address & !3,
                 module_id: import.module_id,
-                target_section: reloc.section,
-                addend: reloc.addend,
-                original_section: section,
-                original_target_section: reloc.section,
-            };
-            unresolved_relocations.push(reloc);
+                target_section: entry.target_section,
+                addend: entry.addend,
+                original_section: entry.section,
+                original_target_section: entry.target_section,
+            });
         }
         reader.seek(SeekFrom::Start(position))?;
     }
@@ -563,35 +593,15 @@ where R: Read + Seek + ?Sized {
 
         let position = reader.stream_position()?;
         reader.seek(SeekFrom::Start(import.offset as u64))?;
-        let mut address = 0u32;
-        let mut section = u8::MAX;
-        loop {
-            let reloc = RelRelocRaw::from_reader(reader, Endian::Big)?;
-            let kind = match reloc.kind as u32 {
-                elf::R_PPC_NONE => continue,
-                elf::R_PPC_ADDR32 | elf::R_PPC_UADDR32 => ObjRelocKind::Absolute,
-                elf::R_PPC_ADDR16_LO => ObjRelocKind::PpcAddr16Lo,
-                elf::R_PPC_ADDR16_HI => ObjRelocKind::PpcAddr16Hi,
-                elf::R_PPC_ADDR16_HA => ObjRelocKind::PpcAddr16Ha,
-                elf::R_PPC_REL24 => ObjRelocKind::PpcRel24,
-                elf::R_PPC_REL14 => ObjRelocKind::PpcRel14,
-                R_DOLPHIN_NOP => {
-                    address += reloc.offset as u32;
-                    continue;
-                }
-                R_DOLPHIN_SECTION => {
-                    address = 0;
-                    section = reloc.section;
-                    continue;
-                }
-                R_DOLPHIN_END => break,
-                // R_DOLPHIN_MRKREF => ?
-                reloc_type => bail!("Unhandled REL relocation type {reloc_type}"),
-            };
-            address += reloc.offset as u32;
+        for entry in read_relocation_stream(reader)? {
             println!(
                 "    {}:{:#X} {:?} -> {}:{}:{:#X}",
-                reloc.section, address, kind, import.module_id, section, reloc.addend
+                entry.section,
+                entry.address,
+                entry.kind,
+                import.module_id,
+                entry.target_section,
+                entry.addend
             );
         }
         reader.seek(SeekFrom::Start(position))?;
@@ -624,14 +634,20 @@ pub struct RelReloc {
 
 #[inline]
 fn reloc_can_be_applied(_module_id: u32, rel_reloc: &RelReloc) -> bool {
-    matches!(rel_reloc.kind, ObjRelocKind::PpcRel24 | ObjRelocKind::PpcRel14)
+    matches!(
+        rel_reloc.kind,
+        ObjRelocKind::PpcRel24
+            | ObjRelocKind::PpcRel14
+            | ObjRelocKind::PpcRel14BrTaken
+            | ObjRelocKind::PpcRel14BrnTaken
+    )
 }
 
 #[inline]
 fn skip_reloc(module_id: u32, rel_reloc: &RelReloc) -> bool {
     rel_reloc.module_id == module_id
         && rel_reloc.section == rel_reloc.target_section
-        && matches!(rel_reloc.kind, ObjRelocKind::PpcRel24 | ObjRelocKind::PpcRel14)
+        && reloc_can_be_applied(module_id, rel_reloc)
 }
 
 fn apply_relocation(
@@ -655,7 +671,7 @@ fn apply_relocation(
             ensure!((-0x2000000..0x2000000).contains(&diff), "R_PPC_REL24 relocation out of range");
             ins = (ins & !0x3fffffc) | (diff as u32 & 0x3fffffc);
         }
-        ObjRelocKind::PpcRel14 => {
+        ObjRelocKind::PpcRel14 | ObjRelocKind::PpcRel14BrTaken | ObjRelocKind::PpcRel14BrnTaken => {
             ensure!((-0x2000..0x2000).contains(&diff), "R_PPC_REL14 relocation out of range");
             ins = (ins & !0xfffc) | (diff as u32 & 0xfffc);
         }
@@ -920,6 +936,8 @@ where
                     ObjRelocKind::PpcAddr16Ha => elf::R_PPC_ADDR16_HA,
                     ObjRelocKind::PpcRel24 => elf::R_PPC_REL24,
                     ObjRelocKind::PpcRel14 => elf::R_PPC_REL14,
+                    ObjRelocKind::PpcRel14BrTaken => elf::R_PPC_REL14_BRTAKEN,
+                    ObjRelocKind::PpcRel14BrnTaken => elf::R_PPC_REL14_BRNTAKEN,
                     _ => bail!("Unsupported relocation kind {:?}", reloc.kind),
                 } as u8,
                 section: reloc.target_section,
@@ -1120,3 +1138,115 @@ pub fn update_rel_section_alignment(obj: &mut ObjInfo, header: &RelHeader) -> Re
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+
+    #[test]
+    fn test_header_alignment() {
+        let mut header = RelHeader {
+            module_id: 1,
+            num_sections: 3,
+            section_info_offset: 0,
+            name_offset: 0,
+            name_size: 0,
+            version: 3,
+            bss_size: 0x10,
+            rel_offset: 0x84,
+            imp_offset: 0x84,
+            imp_size: 0,
+            prolog_section: 0,
+            epilog_section: 0,
+            unresolved_section: 0,
+            prolog_offset: 0,
+            epilog_offset: 0,
+            unresolved_offset: 0,
+            align: Some(32),
+            bss_align: Some(64),
+            fix_size: Some(0x84),
+        };
+        header.section_info_offset = header.write_size() as u32;
+        let mut data = vec![];
+        header.to_writer(&mut data, Endian::Big).unwrap();
+        for section in [
+            RelSectionHeader::new(0, 0, false),
+            RelSectionHeader::new(0x80, 4, false),
+            RelSectionHeader::new(0, 0x10, false),
+        ] {
+            section.to_writer(&mut data, Endian::Big).unwrap();
+        }
+        data.resize(0x80, 0);
+        data.extend_from_slice(&[0xFF; 4]);
+
+        let (header, mut obj) = process_rel(&mut Cursor::new(&data), "test").unwrap();
+        assert_eq!(
+            (header.align, header.bss_align, header.fix_size),
+            (Some(32), Some(64), Some(0x84))
+        );
+        let (_, bss) = obj.sections.by_name(".bss").unwrap().unwrap();
+        assert_eq!(bss.align, 64);
+
+        // The data section follows the section table at the header's alignment
+        update_rel_section_alignment(&mut obj, &header).unwrap();
+        assert_eq!(obj.sections[0].align, 32);
+
+        let mut out = vec![];
+        header.to_writer(&mut out, Endian::Big).unwrap();
+        assert_eq!(out, data[..header.write_size()]);
+    }
+
+    #[test]
+    fn test_read_relocation_stream() {
+        let raw = |offset: u16, kind: u32, section: u8, addend: u32| RelRelocRaw {
+            offset,
+            kind: kind as u8,
+            section,
+            addend,
+        };
+        let mut data = vec![];
+        for reloc in [
+            raw(0, R_DOLPHIN_SECTION, 1, 0),
+            raw(4, elf::R_PPC_ADDR32, 2, 0x10),
+            raw(0x100, R_DOLPHIN_NOP, 0, 0),
+            raw(4, elf::R_PPC_NONE, 0, 0),
+            raw(8, elf::R_PPC_REL24, 1, 0x20),
+            raw(0, R_DOLPHIN_SECTION, 2, 0),
+            raw(6, elf::R_PPC_ADDR16_HA, 3, 0x30),
+            raw(0, R_DOLPHIN_END, 0, 0),
+        ] {
+            reloc.to_writer(&mut data, Endian::Big).unwrap();
+        }
+        let entries = read_relocation_stream(&mut Cursor::new(&data)).unwrap();
+        assert_eq!(entries, vec![
+            RelRelocEntry {
+                kind: ObjRelocKind::Absolute,
+                section: 1,
+                address: 4,
+                target_section: 2,
+                addend: 0x10,
+            },
+            RelRelocEntry {
+                kind: ObjRelocKind::PpcRel24,
+                section: 1,
+                address: 0x110,
+                target_section: 1,
+                addend: 0x20,
+            },
+            RelRelocEntry {
+                kind: ObjRelocKind::PpcAddr16Ha,
+                section: 2,
+                address: 6,
+                target_section: 3,
+                addend: 0x30,
+            },
+        ]);
+
+        // Relocations must be preceded by a section
+        let mut data = vec![];
+        raw(4, elf::R_PPC_ADDR32, 2, 0).to_writer(&mut data, Endian::Big).unwrap();
+        assert!(read_relocation_stream(&mut Cursor::new(&data)).is_err());
+    }
+}
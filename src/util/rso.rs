@@ -3,8 +3,9 @@ use std::{
     io::{Read, Seek, SeekFrom, Write},
 };
 
-use anyhow::{anyhow, ensure, Result};
+use anyhow::{anyhow, bail, ensure, Result};
 use cwdemangle::{demangle, DemangleOptions};
+use tracing::{debug, info};
 
 use crate::{
     obj::{
@@ -12,7 +13,7 @@ use crate::{
         ObjSymbolFlags, ObjSymbolKind, SectionIndex,
     },
     util::{
-        file::{read_c_string, read_string},
+        file::{read_c_string, read_string, read_u32},
         reader::{struct_size, Endian, FromReader, ToWriter, DYNAMIC_SIZE},
     },
 };
@@ -143,26 +144,26 @@ impl FromReader for RsoHeader {
 
     fn from_reader_args<R>(reader: &mut R, e: Endian, _args: Self::Args) -> io::Result<Self>
     where R: Read + Seek + ?Sized {
-        let next = u32::from_reader(reader, e)?;
+        let next = read_u32(reader, e)?;
         if next != 0 {
             return Err(io::Error::new(
                 io::ErrorKind::InvalidData,
                 format!("Expected 'next' to be 0, got {:#X}", next),
             ));
         }
-        let prev = u32::from_reader(reader, e)?;
+        let prev = read_u32(reader, e)?;
         if prev != 0 {
             return Err(io::Error::new(
                 io::ErrorKind::InvalidData,
                 format!("Expected 'prev' to be 0, got {:#X}", prev),
             ));
         }
-        let num_sections = u32::from_reader(reader, e)?;
-        let section_info_offset = u32::from_reader(reader, e)?;
-        let name_offset = u32::from_reader(reader, e)?;
-        let name_size = u32::from_reader(reader, e)?;
-        let version = u32::from_reader(reader, e)?;
-        let bss_size = u32::from_reader(reader, e)?;
+        let num_sections = read_u32(reader, e)?;
+        let section_info_offset = read_u32(reader, e)?;
+        let name_offset = read_u32(reader, e)?;
+        let name_size = read_u32(reader, e)?;
+        let version = read_u32(reader, e)?;
+        let bss_size = read_u32(reader, e)?;
         let prolog_section = u8::from_reader(reader, e)?;
         let epilog_section = u8::from_reader(reader, e)?;
         let unresolved_section = u8::from_reader(reader, e)?;
@@ -173,19 +174,19 @@ impl FromReader for RsoHeader {
                 format!("Expected 'bssSection' to be 0, got {:#X}", bss_section),
             ));
         }
-        let prolog_offset = u32::from_reader(reader, e)?;
-        let epilog_offset = u32::from_reader(reader, e)?;
-        let unresolved_offset = u32::from_reader(reader, e)?;
-        let internal_rel_offset = u32::from_reader(reader, e)?;
-        let internal_rel_size = u32::from_reader(reader, e)?;
-        let external_rel_offset = u32::from_reader(reader, e)?;
-        let external_rel_size = u32::from_reader(reader, e)?;
-        let export_table_offset = u32::from_reader(reader, e)?;
-        let export_table_size = u32::from_reader(reader, e)?;
-        let export_table_name_offset = u32::from_reader(reader, e)?;
-        let import_table_offset = u32::from_reader(reader, e)?;
-        let import_table_size = u32::from_reader(reader, e)?;
-        let import_table_name_offset = u32::from_reader(reader, e)?;
+        let prolog_offset = read_u32(reader, e)?;
+        let epilog_offset = read_u32(reader, e)?;
+        let unresolved_offset = read_u32(reader, e)?;
+        let internal_rel_offset = read_u32(reader, e)?;
+        let internal_rel_size = read_u32(reader, e)?;
+        let external_rel_offset = read_u32(reader, e)?;
+        let external_rel_size = read_u32(reader, e)?;
+        let export_table_offset = read_u32(reader, e)?;
+        let export_table_size = read_u32(reader, e)?;
+        let export_table_name_offset = read_u32(reader, e)?;
+        let import_table_offset = read_u32(reader, e)?;
+        let import_table_size = read_u32(reader, e)?;
+        let import_table_name_offset = read_u32(reader, e)?;
 
         Ok(Self {
             num_sections,
@@ -266,10 +267,7 @@ impl FromReader for RsoSectionHeader {
 
     fn from_reader_args<R>(reader: &mut R, e: Endian, _args: Self::Args) -> io::Result<Self>
     where R: Read + Seek + ?Sized {
-        Ok(Self {
-            offset_and_flags: u32::from_reader(reader, e)?,
-            size: u32::from_reader(reader, e)?,
-        })
+        Ok(Self { offset_and_flags: read_u32(reader, e)?, size: read_u32(reader, e)? })
     }
 }
 
@@ -322,9 +320,9 @@ impl FromReader for RsoRelocation {
     fn from_reader_args<R>(reader: &mut R, e: Endian, _args: Self::Args) -> io::Result<Self>
     where R: Read + Seek + ?Sized {
         Ok(Self {
-            offset: u32::from_reader(reader, e)?,
-            id_and_type: u32::from_reader(reader, e)?,
-            target_offset: u32::from_reader(reader, e)?,
+            offset: read_u32(reader, e)?,
+            id_and_type: read_u32(reader, e)?,
+            target_offset: read_u32(reader, e)?,
         })
     }
 }
@@ -384,14 +382,10 @@ impl FromReader for RsoSymbol {
     fn from_reader_args<R>(reader: &mut R, e: Endian, args: Self::Args) -> io::Result<Self>
     where R: Read + Seek + ?Sized {
         Ok(Self {
-            name_offset: u32::from_reader(reader, e)?,
-            offset: u32::from_reader(reader, e)?,
-            section_index: u32::from_reader(reader, e)?,
-            hash: if args == RsoSymbolKind::Export {
-                Some(u32::from_reader(reader, e)?)
-            } else {
-                None
-            },
+            name_offset: read_u32(reader, e)?,
+            offset: read_u32(reader, e)?,
+            section_index: read_u32(reader, e)?,
+            hash: if args == RsoSymbolKind::Export { Some(read_u32(reader, e)?) } else { None },
         })
     }
 }
@@ -412,14 +406,32 @@ impl ToWriter for RsoSymbol {
     fn write_size(&self) -> usize { Self::STATIC_SIZE }
 }
 
+/// Parses an RSO module from an in-memory buffer.
+pub fn process_rso_bytes(data: &[u8]) -> Result<ObjInfo> { process_rso(&mut io::Cursor::new(data)) }
+
 pub fn process_rso<R>(reader: &mut R) -> Result<ObjInfo>
 where R: Read + Seek + ?Sized {
-    let header = RsoHeader::from_reader(reader, Endian::Big)?;
+    process_rso_with_options(reader, &DemangleOptions::default())
+}
+
+/// Parses an RSO module, demangling exported symbol names with the given options.
+pub fn process_rso_with_options<R>(
+    reader: &mut R,
+    demangle_options: &DemangleOptions,
+) -> Result<ObjInfo>
+where
+    R: Read + Seek + ?Sized,
+{
+    // RSO modules are always big-endian
+    let e = Endian::Big;
+    let header = RsoHeader::from_reader(reader, e)?;
+    let file_len = reader.seek(SeekFrom::End(0))?;
+    validate_header(&header, file_len)?;
     let mut sections = Vec::with_capacity(header.num_sections as usize);
     reader.seek(SeekFrom::Start(header.section_info_offset as u64))?;
     let mut total_bss_size = 0;
     for idx in 0..header.num_sections {
-        let section = RsoSectionHeader::from_reader(reader, Endian::Big)?;
+        let section = RsoSectionHeader::from_reader(reader, e)?;
         let offset = section.offset();
         let size = section.size();
         if size == 0 {
@@ -450,8 +462,8 @@ where R: Read + Seek + ?Sized {
             },
             address: 0,
             size: size as u64,
-            data,
-            align: 0,
+            data: data.into(),
+            align: infer_section_align(offset),
             elf_index: idx as SectionIndex,
             relocations: Default::default(),
             virtual_address: None, // TODO option to set?
@@ -471,19 +483,22 @@ where R: Read + Seek + ?Sized {
     );
 
     let mut symbols = Vec::new();
+    // Symbols whose size is inferred from the following symbol
+    let mut unsized_symbols = Vec::new();
     let mut add_symbol = |rel_section_idx: u8, offset: u32, name: &str| -> Result<()> {
         if rel_section_idx > 0 {
-            let (section_index, _) = sections
+            let (section_index, section) = sections
                 .iter()
                 .enumerate()
                 .find(|&(_, section)| section.elf_index == rel_section_idx as SectionIndex)
                 .ok_or_else(|| anyhow!("Failed to locate {name} section {rel_section_idx}"))?;
-            log::debug!("Adding {name} section {rel_section_idx} offset {offset:#X}");
+            debug!("Adding {name} section {rel_section_idx} offset {offset:#X}");
+            unsized_symbols.push(symbols.len());
             symbols.push(ObjSymbol {
                 name: name.to_string(),
-                address: offset as u64,
+                address: section.address + offset as u64,
                 section: Some(section_index as SectionIndex),
-                flags: ObjSymbolFlagSet(ObjSymbolFlags::Global.into()),
+                flags: ObjSymbolFlagSet(ObjSymbolFlags::Global | ObjSymbolFlags::Hidden),
                 kind: ObjSymbolKind::Function,
                 ..Default::default()
             });
@@ -495,10 +510,14 @@ where R: Read + Seek + ?Sized {
     add_symbol(header.unresolved_section, header.unresolved_offset, "_unresolved")?;
 
     reader.seek(SeekFrom::Start(header.external_rel_offset as u64))?;
-    while reader.stream_position()? < (header.external_rel_offset + header.external_rel_size) as u64
-    {
-        let reloc = RsoRelocation::from_reader(reader, Endian::Big)?;
-        log::debug!(
+    let mut external_rel_data = vec![0u8; header.external_rel_size as usize];
+    reader.read_exact(&mut external_rel_data)?;
+    let external_relocations = read_external_relocations(
+        &external_rel_data,
+        external_relocation_count(&external_rel_data)?,
+    )?;
+    for reloc in &external_relocations {
+        debug!(
             "Reloc offset: {:#X}, id: {}, type: {}, sym offset: {:#X}",
             reloc.offset(),
             reloc.id(),
@@ -510,7 +529,7 @@ where R: Read + Seek + ?Sized {
     reader.seek(SeekFrom::Start(header.export_table_offset as u64))?;
     while reader.stream_position()? < (header.export_table_offset + header.export_table_size) as u64
     {
-        let symbol = RsoSymbol::from_reader_args(reader, Endian::Big, RsoSymbolKind::Export)?;
+        let symbol = RsoSymbol::from_reader_args(reader, e, RsoSymbolKind::Export)?;
         let name =
             read_c_string(reader, (header.export_table_name_offset + symbol.name_offset) as u64)?;
         let calc = symbol_hash(&name);
@@ -522,15 +541,25 @@ where R: Read + Seek + ?Sized {
             hash_n,
             calc
         );
-        let demangled_name = demangle(&name, &DemangleOptions::default());
-        let section = sections
+        let demangled_name = demangle(&name, demangle_options);
+        // Symbol offsets are section-relative, so convert them to addresses like other symbols
+        let (section, address, kind) = match sections
             .iter()
             .enumerate()
             .find(|&(_, section)| section.elf_index == symbol.section_index as SectionIndex)
-            .map(|(idx, _)| idx)
-            // HACK: selfiles won't have any sections
-            .unwrap_or(symbol.section_index as usize);
-        log::debug!(
+        {
+            Some((idx, section)) => {
+                unsized_symbols.push(symbols.len());
+                let kind = match section.kind {
+                    ObjSectionKind::Code => ObjSymbolKind::Function,
+                    _ => ObjSymbolKind::Object,
+                };
+                (idx, section.address + symbol.offset as u64, kind)
+            }
+            // HACK: selfiles won't have any sections, so keep the raw section index and offset
+            None => (symbol.section_index as usize, symbol.offset as u64, ObjSymbolKind::Unknown),
+        };
+        debug!(
             "Export: {}, sym off: {:#X}, section: {}, ELF hash: {:#X}",
             demangled_name.as_deref().unwrap_or(&name),
             symbol.offset,
@@ -540,34 +569,185 @@ where R: Read + Seek + ?Sized {
         symbols.push(ObjSymbol {
             name,
             demangled_name,
-            address: symbol.offset as u64,
+            address,
             section: Some(section as SectionIndex),
+            kind,
             ..Default::default()
         });
     }
     reader.seek(SeekFrom::Start(header.import_table_offset as u64))?;
     while reader.stream_position()? < (header.import_table_offset + header.import_table_size) as u64
     {
-        let symbol = RsoSymbol::from_reader_args(reader, Endian::Big, RsoSymbolKind::Import)?;
+        let symbol = RsoSymbol::from_reader_args(reader, e, RsoSymbolKind::Import)?;
         let name =
             read_c_string(reader, (header.import_table_name_offset + symbol.name_offset) as u64)?;
-        log::debug!(
+        let demangled_name = demangle(&name, demangle_options);
+        debug!(
             "Import: {}, sym off: {}, section: {}",
-            name,
+            demangled_name.as_deref().unwrap_or(&name),
             symbol.offset,
             symbol.section_index
         );
+        // Imports are resolved against other modules, so they remain undefined
+        symbols.push(ObjSymbol {
+            name,
+            demangled_name,
+            flags: ObjSymbolFlagSet(ObjSymbolFlags::Global.into()),
+            ..Default::default()
+        });
     }
 
+    infer_symbol_sizes(&mut symbols, &unsized_symbols, &sections);
+
     let name = match header.name_offset {
         0 => String::new(),
         _ => read_string(reader, header.name_offset as u64, header.name_size as usize)?,
     };
+    info!(
+        "Loaded RSO module '{}': {} sections, {} symbols, {} external relocations",
+        name,
+        sections.len(),
+        symbols.len(),
+        external_relocations.len()
+    );
 
     let obj = ObjInfo::new(ObjKind::Relocatable, ObjArchitecture::PowerPc, name, symbols, sections);
     Ok(obj)
 }
 
+/// Verifies that every table referenced by the header lies within the file before
+/// seeking to it, so that malformed modules fail early instead of reading garbage.
+fn validate_header(header: &RsoHeader, file_len: u64) -> Result<()> {
+    let section_table_size = header.num_sections as u64 * RsoSectionHeader::STATIC_SIZE as u64;
+    let section_table_end = header.section_info_offset as u64 + section_table_size;
+    ensure!(
+        section_table_end <= file_len,
+        "Invalid section count {}: section table {:#X}..{:#X} exceeds file size {:#X}",
+        header.num_sections,
+        header.section_info_offset,
+        section_table_end,
+        file_len
+    );
+    let tables = [
+        ("Name", header.name_offset, header.name_size),
+        ("Internal relocation", header.internal_rel_offset, header.internal_rel_size),
+        ("External relocation", header.external_rel_offset, header.external_rel_size),
+        ("Export", header.export_table_offset, header.export_table_size),
+        ("Export name", header.export_table_name_offset, 0),
+        ("Import", header.import_table_offset, header.import_table_size),
+        ("Import name", header.import_table_name_offset, 0),
+    ];
+    for (name, offset, size) in tables {
+        let end = offset as u64 + size as u64;
+        ensure!(
+            end <= file_len,
+            "{} table {:#X}..{:#X} exceeds file size {:#X}",
+            name,
+            offset,
+            end,
+            file_len
+        );
+    }
+    Ok(())
+}
+
+/// Infers the number of external relocation entries. Entries are usually 12 bytes, but some
+/// modules omit the (always zero) target offset and use 8-byte entries instead.
+fn external_relocation_count(data: &[u8]) -> Result<usize> {
+    if data.len() % 12 == 0 && data.chunks_exact(12).all(|entry| entry[8..12] == [0, 0, 0, 0]) {
+        Ok(data.len() / 12)
+    } else if data.len() % 8 == 0 {
+        Ok(data.len() / 8)
+    } else {
+        bail!("Invalid external relocation table size {:#X}", data.len());
+    }
+}
+
+/// Reads `count` external relocations, deriving the entry stride from the table size.
+/// 8-byte entries have an implicit target offset of 0.
+fn read_external_relocations(data: &[u8], count: usize) -> Result<Vec<RsoRelocation>> {
+    if count == 0 {
+        ensure!(data.is_empty(), "Invalid external relocation table size {:#X}", data.len());
+        return Ok(vec![]);
+    }
+    let stride = data.len() / count;
+    ensure!(
+        data.len() % count == 0 && matches!(stride, 8 | 12),
+        "Invalid external relocation table size {:#X} for {} entries",
+        data.len(),
+        count
+    );
+    let mut reader = io::Cursor::new(data);
+    let mut relocations = Vec::with_capacity(count);
+    for _ in 0..count {
+        if stride == 12 {
+            relocations.push(RsoRelocation::from_reader(&mut reader, Endian::Big)?);
+        } else {
+            let offset = read_u32(&mut reader, Endian::Big)?;
+            let id_and_type = read_u32(&mut reader, Endian::Big)?;
+            relocations.push(RsoRelocation { offset, id_and_type, target_offset: 0 });
+        }
+    }
+    Ok(relocations)
+}
+
+/// Infers a section's alignment from its file offset, between 4 and 32 bytes.
+/// BSS sections have no offset, so their alignment is unknown.
+fn infer_section_align(offset: u32) -> u64 {
+    if offset == 0 {
+        return 0;
+    }
+    (1u64 << offset.trailing_zeros()).clamp(4, 32)
+}
+
+/// Infers the sizes of `_prolog`, `_epilog`, `_unresolved` and exported symbols,
+/// which the RSO format doesn't record.
+fn infer_symbol_sizes(symbols: &mut [ObjSymbol], indices: &[usize], sections: &[ObjSection]) {
+    for &index in indices {
+        let section =
+            symbols[index].section.and_then(|section_index| sections.get(section_index as usize));
+        let section_end = section.map(|section| section.address + section.size).unwrap_or_default();
+        let mut size = infer_symbol_size(symbols, index, section_end);
+        if let Some(section) = section {
+            if symbols[index].kind == ObjSymbolKind::Function {
+                size = trim_nop_padding(&symbols[index], section, size);
+            }
+        }
+        let symbol = &mut symbols[index];
+        debug!("Inferred size of {}: {:#X}", symbol.name, size);
+        symbol.size = size;
+        symbol.size_known = size != 0;
+    }
+}
+
+/// Infers a symbol's size as the distance to the next symbol in the same section,
+/// or to the end of the section if no symbol follows it. The result never extends
+/// past the end of the section.
+fn infer_symbol_size(symbols: &[ObjSymbol], index: usize, section_end: u64) -> u64 {
+    let symbol = &symbols[index];
+    let end = symbols
+        .iter()
+        .filter(|other| other.section == symbol.section && other.address > symbol.address)
+        .map(|other| other.address)
+        .min()
+        .map_or(section_end, |next| next.min(section_end));
+    end.saturating_sub(symbol.address)
+}
+
+/// Excludes trailing `nop` instructions from a function's size, since they're
+/// alignment padding before the following function rather than part of its code.
+fn trim_nop_padding(symbol: &ObjSymbol, section: &ObjSection, mut size: u64) -> u64 {
+    let start = (symbol.address - section.address) as usize;
+    while size > 4 {
+        let end = start + size as usize;
+        match section.data.get(end - 4..end) {
+            Some([0x60, 0x00, 0x00, 0x00]) => size -= 4,
+            _ => break,
+        }
+    }
+    size
+}
+
 pub fn symbol_hash(s: &str) -> u32 {
     s.bytes().fold(0u32, |hash, c| {
         let mut m = (hash << 4).wrapping_add(c as u32);
@@ -578,3 +758,240 @@ pub fn symbol_hash(s: &str) -> u32 {
         m & !n
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+    use crate::obj::fixtures::{test_section, test_symbol};
+
+    /// Log writer appending to a shared buffer.
+    struct SharedBuf(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for SharedBuf {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> { self.0.lock().unwrap().write(buf) }
+
+        fn flush(&mut self) -> io::Result<()> { Ok(()) }
+    }
+
+    /// Builds a symbol in section 0 whose size is left for inference.
+    fn unsized_symbol(name: &str, kind: ObjSymbolKind, address: u64) -> ObjSymbol {
+        ObjSymbol { kind, size_known: false, ..test_symbol(name, 0, address, 0) }
+    }
+
+    #[test]
+    fn test_infer_symbol_size() {
+        let symbols = vec![
+            unsized_symbol("_prolog", ObjSymbolKind::Function, 0x0),
+            unsized_symbol("_epilog", ObjSymbolKind::Function, 0x20),
+            unsized_symbol("_unresolved", ObjSymbolKind::Function, 0x40),
+            unsized_symbol("func", ObjSymbolKind::Function, 0x4C),
+        ];
+        assert_eq!(infer_symbol_size(&symbols, 0, 0x100), 0x20);
+        assert_eq!(infer_symbol_size(&symbols, 1, 0x100), 0x20);
+        assert_eq!(infer_symbol_size(&symbols, 2, 0x100), 0xC);
+        // Last symbol extends to the end of the section
+        assert_eq!(infer_symbol_size(&symbols, 3, 0x100), 0xB4);
+        // Sizes are clamped to the end of the section
+        assert_eq!(infer_symbol_size(&symbols, 2, 0x48), 0x8);
+        assert_eq!(infer_symbol_size(&symbols, 3, 0x48), 0);
+    }
+
+    #[test]
+    fn test_infer_trailing_data_size() {
+        let section = ObjSection {
+            elf_index: 1,
+            ..test_section(".data", ObjSectionKind::Data, 0x1000, vec![0; 0x20])
+        };
+        let mut symbols = vec![
+            unsized_symbol("value", ObjSymbolKind::Object, 0x1000),
+            unsized_symbol("array", ObjSymbolKind::Object, 0x1008),
+        ];
+        infer_symbol_sizes(&mut symbols, &[0, 1], &[section]);
+        assert_eq!((symbols[0].size, symbols[0].size_known), (0x8, true));
+        // The final symbol extends to the end of the section
+        assert_eq!((symbols[1].size, symbols[1].size_known), (0x18, true));
+    }
+
+    #[test]
+    fn test_trim_nop_padding() {
+        let mut data = vec![0x38, 0x60, 0x00, 0x00, 0x4E, 0x80, 0x00, 0x20];
+        data.extend_from_slice(&[0x60, 0x00, 0x00, 0x00, 0x60, 0x00, 0x00, 0x00]);
+        data.extend_from_slice(&[0x4E, 0x80, 0x00, 0x20]);
+        let section = ObjSection {
+            elf_index: 1,
+            ..test_section(".text", ObjSectionKind::Code, 0x1000, data)
+        };
+        let mut symbols = vec![
+            unsized_symbol("func", ObjSymbolKind::Function, 0x1000),
+            unsized_symbol("next", ObjSymbolKind::Function, 0x1010),
+        ];
+        infer_symbol_sizes(&mut symbols, &[0, 1], &[section]);
+        assert_eq!(symbols[0].size, 0x8);
+        assert_eq!(symbols[1].size, 0x4);
+    }
+
+    #[test]
+    fn test_infer_section_align() {
+        assert_eq!(infer_section_align(0x120), 32);
+        assert_eq!(infer_section_align(0x1E0), 32);
+        assert_eq!(infer_section_align(0x110), 16);
+        assert_eq!(infer_section_align(0x108), 8);
+        assert_eq!(infer_section_align(0x104), 4);
+        assert_eq!(infer_section_align(0x102), 4);
+        assert_eq!(infer_section_align(0), 0);
+    }
+
+    #[test]
+    fn test_read_external_relocations() {
+        let relocs = [(0x100, 0x102), (0x104, 0x206), (0x10C, 0x301)];
+        let mut data = vec![];
+        for (offset, id_and_type) in relocs {
+            data.extend_from_slice(&u32::to_be_bytes(offset));
+            data.extend_from_slice(&u32::to_be_bytes(id_and_type));
+            data.extend_from_slice(&[0; 4]);
+        }
+        assert_eq!(external_relocation_count(&data).unwrap(), 3);
+        let out = read_external_relocations(&data, 3).unwrap();
+        assert_eq!(out.iter().map(|r| (r.offset, r.id_and_type)).collect::<Vec<_>>(), relocs);
+
+        let mut data = vec![];
+        for (offset, id_and_type) in relocs {
+            data.extend_from_slice(&u32::to_be_bytes(offset));
+            data.extend_from_slice(&u32::to_be_bytes(id_and_type));
+        }
+        assert_eq!(external_relocation_count(&data).unwrap(), 3);
+        let out = read_external_relocations(&data, 3).unwrap();
+        assert_eq!(out.iter().map(|r| (r.offset, r.id_and_type)).collect::<Vec<_>>(), relocs);
+        assert!(out.iter().all(|r| r.target_offset == 0));
+
+        // Only 8- and 12-byte strides are valid
+        assert!(read_external_relocations(&data, 1).is_err());
+        assert!(read_external_relocations(&data, 5).is_err());
+        assert!(external_relocation_count(&[0; 10]).is_err());
+    }
+
+    #[test]
+    fn test_process_rso_bytes() {
+        let mut header = RsoHeader::new();
+        header.num_sections = 2;
+        header.section_info_offset = RsoHeader::STATIC_SIZE as u32;
+        let data_offset = header.section_info_offset + 2 * RsoSectionHeader::STATIC_SIZE as u32;
+        let end = data_offset + 8;
+        header.internal_rel_offset = end;
+        header.external_rel_offset = end;
+        header.export_table_offset = end;
+        header.export_table_name_offset = end;
+        header.import_table_offset = end;
+        header.import_table_name_offset = end;
+
+        let mut data = Vec::<u8>::new();
+        header.to_writer(&mut data, Endian::Big).unwrap();
+        RsoSectionHeader::new(0, 0, false).to_writer(&mut data, Endian::Big).unwrap();
+        RsoSectionHeader::new(data_offset, 8, true).to_writer(&mut data, Endian::Big).unwrap();
+        data.extend_from_slice(&[0x38, 0x60, 0x00, 0x00, 0x4E, 0x80, 0x00, 0x20]);
+
+        let obj = process_rso_bytes(&data).unwrap();
+        assert_eq!(obj.sections.len(), 1);
+        let section = &obj.sections[0];
+        assert_eq!(section.kind, ObjSectionKind::Code);
+        assert_eq!(section.file_offset, data_offset as u64);
+        assert_eq!(section.data, data[data_offset as usize..]);
+    }
+
+    /// Builds a module with a single code section, exporting `export` at offset 4 and
+    /// optionally importing `import`.
+    fn build_rso(export: &str, import: Option<&str>) -> Vec<u8> {
+        let export_name = [export.as_bytes(), &[0]].concat();
+        let import_name = import.map(|name| [name.as_bytes(), &[0]].concat()).unwrap_or_default();
+        let mut header = RsoHeader::new();
+        header.num_sections = 2;
+        header.section_info_offset = RsoHeader::STATIC_SIZE as u32;
+        let data_offset = header.section_info_offset + 2 * RsoSectionHeader::STATIC_SIZE as u32;
+        let end = data_offset + 8;
+        header.internal_rel_offset = end;
+        header.external_rel_offset = end;
+        header.export_table_offset = end;
+        header.export_table_size = 16;
+        header.export_table_name_offset = end + 16;
+        header.import_table_offset = end + 16 + export_name.len() as u32;
+        header.import_table_size = if import.is_some() { 12 } else { 0 };
+        header.import_table_name_offset = header.import_table_offset + header.import_table_size;
+
+        let mut data = Vec::<u8>::new();
+        header.to_writer(&mut data, Endian::Big).unwrap();
+        RsoSectionHeader::new(0, 0, false).to_writer(&mut data, Endian::Big).unwrap();
+        RsoSectionHeader::new(data_offset, 8, true).to_writer(&mut data, Endian::Big).unwrap();
+        data.extend_from_slice(&[0x38, 0x60, 0x00, 0x00, 0x4E, 0x80, 0x00, 0x20]);
+        RsoSymbol { name_offset: 0, offset: 4, section_index: 1, hash: None }
+            .to_writer(&mut data, Endian::Big)
+            .unwrap();
+        data.extend_from_slice(&symbol_hash(export).to_be_bytes());
+        data.extend_from_slice(&export_name);
+        if import.is_some() {
+            RsoSymbol { name_offset: 0, offset: 0, section_index: 0, hash: None }
+                .to_writer(&mut data, Endian::Big)
+                .unwrap();
+            data.extend_from_slice(&import_name);
+        }
+        data
+    }
+
+    #[test]
+    fn test_validate_header() {
+        let mut data = build_rso("exported", None);
+        // export_table_offset
+        data[RsoHeader::STATIC_SIZE - 24..RsoHeader::STATIC_SIZE - 20]
+            .copy_from_slice(&0x10000u32.to_be_bytes());
+        let err = process_rso_bytes(&data).unwrap_err().to_string();
+        assert!(err.starts_with("Export table 0x10000..0x10010 exceeds file size"), "{err}");
+
+        let mut data = build_rso("exported", None);
+        // num_sections
+        data[8..12].copy_from_slice(&0x10000000u32.to_be_bytes());
+        let err = process_rso_bytes(&data).unwrap_err().to_string();
+        assert!(err.starts_with("Invalid section count 268435456"), "{err}");
+    }
+
+    #[test]
+    fn test_export_symbol_address() {
+        let obj = process_rso_bytes(&build_rso("exported", None)).unwrap();
+        let (_, symbol) = obj.symbols.by_name("exported").unwrap().unwrap();
+        assert_eq!(symbol.section, Some(0));
+        assert_eq!((symbol.kind, symbol.size), (ObjSymbolKind::Function, 4));
+        let section = &obj.sections[0];
+        let offset = (symbol.address - section.address) as usize;
+        assert_eq!(section.data[offset..offset + 4], [0x4E, 0x80, 0x00, 0x20]);
+    }
+
+    #[test]
+    fn test_info_summary() {
+        let output = Arc::new(Mutex::new(Vec::<u8>::new()));
+        let buf = output.clone();
+        let subscriber = tracing_subscriber::fmt()
+            .with_writer(move || SharedBuf(buf.clone()))
+            .with_max_level(tracing::Level::INFO)
+            .with_ansi(false)
+            .without_time()
+            .finish();
+        let data = build_rso("exported", None);
+        tracing::subscriber::with_default(subscriber, || process_rso_bytes(&data).unwrap());
+
+        let output = String::from_utf8(output.lock().unwrap().clone()).unwrap();
+        let lines = output.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 1, "{output}");
+        assert!(lines[0].contains("1 sections, 1 symbols, 0 external relocations"), "{output}");
+    }
+
+    #[test]
+    fn test_demangle_imports() {
+        let obj = process_rso_bytes(&build_rso("func__Fv", Some("func__Fv"))).unwrap();
+        let symbols = obj.symbols.iter().filter(|(_, s)| s.name == "func__Fv").collect::<Vec<_>>();
+        assert_eq!(symbols.len(), 2);
+        let (_, export) = symbols.iter().find(|(_, s)| s.section.is_some()).unwrap();
+        let (_, import) = symbols.iter().find(|(_, s)| s.section.is_none()).unwrap();
+        assert_eq!(import.demangled_name.as_deref(), Some("func()"));
+        assert_eq!(import.demangled_name, export.demangled_name);
+    }
+}
@@ -174,7 +174,12 @@ pub fn apply_signature(
             | (&Relocation::Ha(RelocationTarget::Address(addr)), ObjRelocKind::PpcAddr16Ha)
             | (&Relocation::Lo(RelocationTarget::Address(addr)), ObjRelocKind::PpcAddr16Lo)
             | (&Relocation::Rel24(RelocationTarget::Address(addr)), ObjRelocKind::PpcRel24)
-            | (&Relocation::Rel14(RelocationTarget::Address(addr)), ObjRelocKind::PpcRel14)
+            | (
+                &Relocation::Rel14(RelocationTarget::Address(addr)),
+                ObjRelocKind::PpcRel14
+                | ObjRelocKind::PpcRel14BrTaken
+                | ObjRelocKind::PpcRel14BrnTaken,
+            )
             | (&Relocation::Sda21(RelocationTarget::Address(addr)), ObjRelocKind::PpcEmbSda21) => {
                 SectionAddress::new(
                     addr.section,
@@ -191,6 +196,7 @@ pub fn apply_signature(
             target_symbol: target_symbol_idx,
             addend: sig_reloc.addend as i64,
             module: None,
+            pair: None,
         };
         // log::info!("Applying relocation {:#010X?}", obj_reloc);
         obj.sections[addr.section].relocations.insert(reloc_addr.address, obj_reloc)?;
@@ -333,13 +339,14 @@ pub fn generate_signature(
                     btree_map::Entry::Occupied(e) => *e.get(),
                 };
                 match reloc.kind {
-                    ObjRelocKind::Absolute => {
+                    ObjRelocKind::Absolute | ObjRelocKind::Other(_) => {
                         *ins = 0;
                         *pat = 0;
                     }
                     ObjRelocKind::PpcAddr16Hi
                     | ObjRelocKind::PpcAddr16Ha
-                    | ObjRelocKind::PpcAddr16Lo => {
+                    | ObjRelocKind::PpcAddr16Lo
+                    | ObjRelocKind::PpcSdaRel16 => {
                         *ins &= !0xFFFF;
                         *pat = !0xFFFF;
                     }
@@ -347,7 +354,9 @@ pub fn generate_signature(
                         *ins &= !0x3FFFFFC;
                         *pat = !0x3FFFFFC;
                     }
-                    ObjRelocKind::PpcRel14 => {
+                    ObjRelocKind::PpcRel14
+                    | ObjRelocKind::PpcRel14BrTaken
+                    | ObjRelocKind::PpcRel14BrnTaken => {
                         *ins &= !0xFFFC;
                         *pat = !0xFFFC;
                     }
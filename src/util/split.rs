@@ -15,7 +15,7 @@ use crate::{
     obj::{
         ObjArchitecture, ObjInfo, ObjKind, ObjReloc, ObjRelocations, ObjSection, ObjSectionKind,
         ObjSplit, ObjSymbol, ObjSymbolFlagSet, ObjSymbolFlags, ObjSymbolKind, ObjSymbolScope,
-        ObjUnit, SectionIndex, SymbolIndex,
+        ObjUnit, SectionIndex, SymbolIndex, SDA2_BASE_SYMBOL, SDA_BASE_SYMBOL,
     },
     util::{align_up, comment::MWComment},
 };
@@ -310,6 +310,9 @@ fn create_gap_splits(obj: &mut ObjInfo) -> Result<()> {
     let mut new_splits = BTreeMap::<SectionAddress, ObjSplit>::new();
 
     for (section_index, section) in obj.sections.iter() {
+        if section.is_unwind_table() {
+            continue;
+        }
         let mut current_address = SectionAddress::new(section_index, section.address as u32);
         let section_end = end_for_section(obj, section_index)?;
         let mut file_iter = section
@@ -531,7 +534,11 @@ fn validate_splits(obj: &ObjInfo) -> Result<()> {
 /// Add padding symbols to fill in gaps between splits and symbols.
 fn add_padding_symbols(obj: &mut ObjInfo) -> Result<()> {
     for (section_index, section, addr, _split) in obj.sections.all_splits() {
-        if section.name == ".ctors" || section.name == ".dtors" {
+        if section.name == ".ctors"
+            || section.name == ".dtors"
+            || !section.kind.loadable()
+            || section.is_unwind_table()
+        {
             continue;
         }
 
@@ -542,6 +549,7 @@ fn add_padding_symbols(obj: &mut ObjInfo) -> Result<()> {
                 ObjSectionKind::Data => ObjSymbolKind::Object,
                 ObjSectionKind::ReadOnlyData => ObjSymbolKind::Object,
                 ObjSectionKind::Bss => ObjSymbolKind::Object,
+                ObjSectionKind::Note => ObjSymbolKind::Object,
             })?
             .is_none()
         {
@@ -569,9 +577,10 @@ fn add_padding_symbols(obj: &mut ObjInfo) -> Result<()> {
                 ),
                 kind: match section.kind {
                     ObjSectionKind::Code => ObjSymbolKind::Function,
-                    ObjSectionKind::Data | ObjSectionKind::ReadOnlyData | ObjSectionKind::Bss => {
-                        ObjSymbolKind::Object
-                    }
+                    ObjSectionKind::Data
+                    | ObjSectionKind::ReadOnlyData
+                    | ObjSectionKind::Bss
+                    | ObjSectionKind::Note => ObjSymbolKind::Object,
                 },
                 ..Default::default()
             })?;
@@ -581,7 +590,11 @@ fn add_padding_symbols(obj: &mut ObjInfo) -> Result<()> {
     // Add padding symbols for gaps between symbols
     let common_bss = obj.sections.common_bss_start();
     for (section_index, section) in obj.sections.iter() {
-        if section.name == ".ctors" || section.name == ".dtors" {
+        if section.name == ".ctors"
+            || section.name == ".dtors"
+            || !section.kind.loadable()
+            || section.is_unwind_table()
+        {
             continue;
         }
 
@@ -640,7 +653,8 @@ fn add_padding_symbols(obj: &mut ObjInfo) -> Result<()> {
                             ObjSectionKind::Code => ObjSymbolKind::Function,
                             ObjSectionKind::Data
                             | ObjSectionKind::ReadOnlyData
-                            | ObjSectionKind::Bss => ObjSymbolKind::Object,
+                            | ObjSectionKind::Bss
+                            | ObjSectionKind::Note => ObjSymbolKind::Object,
                         },
                         ..Default::default()
                     });
@@ -962,6 +976,10 @@ pub fn split_obj(obj: &ObjInfo, module_name: Option<&str>) -> Result<Vec<ObjInfo
     }
 
     for (section_index, section) in obj.sections.iter() {
+        if section.is_unwind_table() {
+            log::warn!("Unwind table {} can't be split and will be omitted", section.name);
+            continue;
+        }
         let mut current_address = SectionAddress::new(section_index, section.address as u32);
         let section_end = end_for_section(obj, section_index)?;
         let mut split_iter = section
@@ -1066,6 +1084,12 @@ pub fn split_obj(obj: &ObjInfo, module_name: Option<&str>) -> Result<Vec<ObjInfo
                         target_symbol: o.target_symbol,
                         addend: o.addend,
                         module: o.module,
+                        // Drop pairs split into another unit
+                        pair: o.pair.filter(|&offset| {
+                            let pair = addr as i64 + offset as i64;
+                            pair >= current_address.address as i64
+                                && pair < split_end.address as i64
+                        }),
                     })
                 })
                 .collect_vec();
@@ -1135,7 +1159,7 @@ pub fn split_obj(obj: &ObjInfo, module_name: Option<&str>) -> Result<Vec<ObjInfo
                     kind: section.kind,
                     address: 0,
                     size: split_end.address as u64 - current_address.address as u64,
-                    data,
+                    data: data.into(),
                     align,
                     elf_index: out_section_idx + 1,
                     relocations: ObjRelocations::new(out_relocations)?,
@@ -1222,7 +1246,7 @@ pub fn split_obj(obj: &ObjInfo, module_name: Option<&str>) -> Result<Vec<ObjInfo
                                 target_addr,
                                 target_split.unit,
                                 target_sym.address,
-                                target_sym.demangled_name.as_deref().unwrap_or(&target_sym.name),
+                                target_sym.display_name(),
                             );
                         }
                     }
@@ -1336,8 +1360,8 @@ pub fn is_linker_generated_label(name: &str) -> bool {
             | "_heap_end"
             | "_nbfunctions"
             | "SIZEOF_HEADERS"
-            | "_SDA_BASE_"
-            | "_SDA2_BASE_"
+            | SDA_BASE_SYMBOL
+            | SDA2_BASE_SYMBOL
             | "_ABS_SDA_BASE_"
             | "_ABS_SDA2_BASE_"
     )
@@ -1445,3 +1469,55 @@ fn unit_exists(
             .any(|(_, split)| split.unit.eq_ignore_ascii_case(unit_name))
         || new_splits.values().any(|split| split.unit.eq_ignore_ascii_case(unit_name))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::obj::fixtures::{test_obj, test_section};
+
+    #[test]
+    fn test_split_obj_unwind_table() {
+        let mut obj = test_obj(
+            ObjKind::Executable,
+            vec![ObjSymbol {
+                name: "func".to_string(),
+                address: 0x80003100,
+                section: Some(0),
+                size: 8,
+                size_known: true,
+                kind: ObjSymbolKind::Function,
+                ..Default::default()
+            }],
+            vec![
+                test_section(".text", ObjSectionKind::Code, 0x80003100, vec![
+                    0x60, 0x00, 0x00, 0x00, // nop
+                    0x4E, 0x80, 0x00, 0x20, // blr
+                ]),
+                test_section(".eh_frame", ObjSectionKind::ReadOnlyData, 0x80003108, vec![1; 8]),
+            ],
+        );
+        obj.link_order.push(ObjUnit {
+            name: "main.c".to_string(),
+            autogenerated: false,
+            comment_version: None,
+            order: None,
+        });
+        obj.sections[0].splits.push(0x80003100, ObjSplit {
+            unit: "main.c".to_string(),
+            end: 0x80003108,
+            align: None,
+            common: false,
+            autogenerated: false,
+            skip: false,
+            rename: None,
+        });
+
+        // No gap split is created for the unwind table, and it's left out of the units
+        create_gap_splits(&mut obj).unwrap();
+        assert!(obj.sections[1].splits.iter().next().is_none());
+        let split_objs = split_obj(&obj, None).unwrap();
+        assert_eq!(split_objs.len(), 1);
+        let names = split_objs[0].sections.iter().map(|(_, s)| s.name.as_str()).collect_vec();
+        assert_eq!(names, [".text"]);
+    }
+}